@@ -2,8 +2,10 @@ use std::env;
 use std::fs::File;
 use std::io::Write;
 use std::path::Path;
+use std::process::Command;
 
-/// Put the linker script somewhere the linker can find it.
+/// Put the linker script somewhere the linker can find it, and note which
+/// commit the firmware is built from, and whether the tree had changes on top.
 fn main() {
     let out_dir = env::var("OUT_DIR").expect("No out dir");
     let dest_path = Path::new(&out_dir);
@@ -15,6 +17,27 @@ fn main() {
 
     println!("cargo:rustc-link-search={}", dest_path.display());
 
+    let git_hash = Command::new("git")
+        .args(["rev-parse", "--short=8", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|hash| hash.trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    let dirty = Command::new("git")
+        .args(["status", "--porcelain", "--untracked-files=no"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .is_some_and(|output| !output.stdout.is_empty());
+    let git_hash = if dirty { format!("{git_hash}-dirty") } else { git_hash };
+    println!("cargo:rustc-env=TEDIUM_SOC_GIT_HASH={git_hash}");
+
     println!("cargo:rerun-if-changed=memory.x");
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+    println!("cargo:rerun-if-changed=../.git/refs");
+    println!("cargo:rerun-if-changed=../.git/index");
+    println!("cargo:rerun-if-changed=src");
     println!("cargo:rerun-if-changed=build.rs");
 }
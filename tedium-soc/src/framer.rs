@@ -61,4 +61,13 @@ impl FramerControl {
             p.write_volatile(value as u32);
         }
     }
+
+    /// Choose the channel whose recovered clock the gateware drives onto every TxSERCLK.
+    pub fn set_tx_clock_select(&self, channel: usize) {
+        unsafe {
+            let p = self.p as *mut u32;
+            let p = p.offset(2);
+            p.write_volatile(channel as u32);
+        }
+    }
 }
@@ -54,20 +54,27 @@ pub(crate) enum EndpointNumber {
 // TODO: This is a duplicate of the data in the `tedium-tool` project.
 const FRAMER_CONTROL_BYTES_MAX: usize = 512;
 
+// TODO: Keep synchronized with `bcdDevice` in `gateware/descriptors_vendor.py`.
+const DESCRIPTOR_VERSION: u16 = 0x0101;
+
+/// How `configure` sets up every channel. Reported to the host by `GetInfo`.
+const PROFILE_FRAMING: T1Framing = T1Framing::ExtendedSuperFrame;
+const PROFILE_CLOCK_SOURCE: ClockSource = ClockSource::External;
+
 fn configure_channel<D: Xyz>(channel: &Channel<D>) -> Result<()> {
     // THEORY?
     // NOTE: I *think* the clock loss detection feature is not effective
     // in our case, as channels are currently configured to use TxSERCLK_n
     // as their transmit clock source ("External Timing Modee"). The FPGA
-    // is "wired" to take the recovered clock from one of the channels and
-    // mirror it to the TxSERCLK on all channels.
+    // takes the recovered clock from the channel `set_tx_clock_select`
+    // picks and mirrors it to the TxSERCLK on all channels.
 
     channel.csr().write(|w| w
         .with_LCV_Insert(0)
         .with_Set_T1_Mode(1)
         .with_Sync_All_Transmitters_to_8kHz(0)
         .with_Clock_Loss_Detect(1)
-        .with_CSS(ClockSource::External)
+        .with_CSS(PROFILE_CLOCK_SOURCE)
     )?;
 
     channel.licr().write(|w| w
@@ -85,7 +92,7 @@ fn configure_channel<D: Xyz>(channel: &Channel<D>) -> Result<()> {
         .with_J1_MODE(0)
         .with_ONEONLY(1)    // Not the default, maybe more reliable sync?
         .with_FASTSYNC(0)
-        .with_FSI(T1Framing::ExtendedSuperFrame)
+        .with_FSI(PROFILE_FRAMING)
     )?;
 
     channel.smr().write(|w| w
@@ -165,22 +172,16 @@ fn configure_channel<D: Xyz>(channel: &Channel<D>) -> Result<()> {
         .with_RxIMODE(0b00)
     )?;
 
+    // Short haul (DSX-1, 0-133 feet), receive jitter attenuator, 100 ohm
+    // internal termination. `tedium-tool liu set` changes these per channel.
     channel.liuccr0().write(|w| w
         .with_QRSS_n_PRBS_n(PRBSPattern::PRBS)
         .with_PRBS_Rx_n_PRBS_Tx_n(PRBSDestination::TTIP_TRING)
         .with_RXON_n(1)
-        .with_EQC(0x08)
+        .with_EQC(xrt86vx38_pac::default::LIU_EQC)
     )?;
 
-    channel.liuccr1().write(|w| w
-        .with_RXTSEL_n(Termination::Internal)
-        .with_TXTSEL_n(Termination::Internal)
-        .with_TERSEL(TerminationImpedance::Ohms100)
-        .with_RxJASEL_n(1)
-        .with_TxJASEL_n(1)
-        .with_JABW_n(0)
-        .with_FIFOS_n(0)
-    )?;
+    channel.liuccr1().write(|_| LIUCCR1::from(xrt86vx38_pac::default::LIU_LIUCCR1))?;
 
     channel.liuccr2().write(|w| w
         .with_INVQRSS_n(0)
@@ -314,23 +315,6 @@ fn enable_interrupts<D: Xyz>(channel: &Channel<D>) -> Result<()> {
         // )?;
     }
 
-    // In-band loopback (not applicable for ESF, right?)
-    // channel.lccr0().modify(|m| m
-    //     .with_RXLBCALEN(0b11)
-    //     .with_RXLBCDLEN(0b11)
-    //     .with_TXLBCLEN(0b11)
-    //     .with_FRAMED(1)
-    //     .with_AUTOENB(1)
-    // )?;
-    // channel.rxlbac().modify(|m| m
-    //     .with_RXLBAC(0b000_1110)
-    //     .with_RXLBACEN(1)
-    // )?;
-    // channel.rxldcr().modify(|m| m
-    //     .with_RXLBDC(0b011_1000)
-    //     .with_RXLBDCEN(1)
-    // )?;
-
     // channel.rifr().modify(|m| m
     //     .with_FRAlarmMask(1)    // "mask" terminology is very confusing!
     // )?;
@@ -340,10 +324,12 @@ fn enable_interrupts<D: Xyz>(channel: &Channel<D>) -> Result<()> {
     // )?;
 
     // Enable all interrupts, even though it *seems* the status registers
-    // reflect events even if interrupts aren't enabled?
+    // reflect events even if interrupts aren't enabled? ONESEC and LBCODE are
+    // left to the host, which turns them on once it's reading (and so clearing)
+    // the PMON counters, or has programmed the loopback codes.
     channel.bier().modify(|m| m
         .with_LBCODE_ENB(0)
-        .with_RXCLKLOSS(0)
+        .with_RXCLKLOSS(1)
         .with_ONESEC_ENB(0)
         .with_HDLC_ENB(1)
         .with_SLIP_ENB(1)
@@ -370,15 +356,17 @@ fn enable_interrupts<D: Xyz>(channel: &Channel<D>) -> Result<()> {
         .with_FE_ENB(1)     // Framing bit errors don't necessarily indicate that synchronization has been lost.
     )?;
 
-    // Data Link (HDLC1) Interrupts
-    channel.dlier1().modify(|m| m
-        .with_TxSOT_ENB(0)      // For what it's worth, automatic reporting produces this interrupt.
-        .with_RxSOT_ENB(0)
-        .with_TxEOT_ENB(0)      // For what it's worth, automatic reporting produces this interrupt.
-        .with_RxEOT_ENB(1)
-        .with_FCS_ERR_ENB(0)    // Seems like status we should check on RxEOT, but doesn't require interrupting
-        .with_RxIDLE_ENB(0)     // Not sure this has any value.
-    )?;
+    // Data Link (HDLC1, HDLC2, HDLC3) Interrupts
+    for hdlc_index in 0..3 {
+        channel.dlier(hdlc_index).modify(|m| m
+            .with_TxSOT_ENB(0)      // For what it's worth, automatic reporting produces this interrupt.
+            .with_RxSOT_ENB(0)
+            .with_TxEOT_ENB(1)      // Host-transmitted frames are paced by this. Automatic reporting also produces it.
+            .with_RxEOT_ENB(1)
+            .with_FCS_ERR_ENB(0)    // Seems like status we should check on RxEOT, but doesn't require interrupting
+            .with_RxIDLE_ENB(0)     // Not sure this has any value.
+        )?;
+    }
 
     // Slip Buffer Interrupts
     // We'll keep a close eye on these for now, assuming my audio pump isn't very refined or well-behaved yet.
@@ -408,8 +396,8 @@ fn enable_interrupts<D: Xyz>(channel: &Channel<D>) -> Result<()> {
 
     // Enable Change in Receive AIS-CI / RAI-CI Condition interrupt?
     channel.ciaier().modify(|m| m
-        .with_RxAIS_CI_ENB(0)
-        .with_RxRAI_CI_ENB(0)
+        .with_RxAIS_CI_ENB(1)
+        .with_RxRAI_CI_ENB(1)
     )?;
 
     // Enable T1 BOC interrupts?
@@ -473,6 +461,69 @@ fn enable_interrupts<D: Xyz>(channel: &Channel<D>) -> Result<()> {
     Ok(())
 }
 
+/// `TimingReference` argument for running every transmitter from MCLK.
+const TIMING_REFERENCE_FREE_RUN: u8 = 0xff;
+
+/// Loop-time the reference channel's transmitter to its recovered clock, and
+/// have the gateware mirror that clock to the other channels' TxSERCLK. Or
+/// free-run every transmitter from the MCLK PLL if there is no reference.
+fn set_timing_reference(device: &Device, framer_control: &FramerControl, reference: Option<usize>) -> Result<()> {
+    if let Some(reference) = reference {
+        framer_control.set_tx_clock_select(reference);
+    }
+
+    for (channel_index, channel) in device.channels().enumerate() {
+        let source = match reference {
+            None => ClockSource::Internal,
+            Some(reference) if reference == channel_index => ClockSource::Loop,
+            Some(_) => PROFILE_CLOCK_SOURCE,
+        };
+        channel.csr().modify(|m| m
+            .with_CSS(source)
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Largest frame that fits in one LAPD buffer. The framer appends the FCS.
+const HDLC_TRANSMIT_BYTES_MAX: usize = 96;
+
+#[derive(Copy, Clone, Debug)]
+#[repr(u8)]
+enum HdlcTransmitStatus {
+    Queued = 0,
+    Busy = 1,
+}
+
+fn hdlc_transmit<D: Xyz>(channel: &Channel<D>, hdlc_index: usize, data: &[u8]) -> Result<()> {
+    channel.dlcr(hdlc_index).modify(|m| m
+        .with_Tx_FCS_EN(1)
+        .with_MOS_BOSn(1)
+    )?;
+
+    // Reading TDLBCR tells us which buffer is available. Writing it with the
+    // byte count starts the transmission from that buffer.
+    let buffer = channel.tdlbcr(hdlc_index).read()?.TxHDLC_BUFAvail_BUFSel();
+
+    // Each buffer is a single address with an auto-incrementing pointer, the
+    // same as the receive side reads it.
+    let lapdbcr = match buffer {
+        0 => channel.lapdbcr0(0),
+        _ => channel.lapdbcr1(0),
+    };
+    for &v in data {
+        lapdbcr.write(|_| v)?;
+    }
+
+    channel.tdlbcr(hdlc_index).write(|w| w
+        .with_TxHDLC_BUFAvail_BUFSel(buffer)
+        .with_TDLBC(data.len() as u8)
+    )?;
+
+    Ok(())
+}
+
 fn dump_registers<D: Xyz>(device: &D, uart: &Uart) {
     for s in 0..1 {
         for r in 0x100..0x200 {
@@ -493,20 +544,41 @@ fn dump_registers<D: Xyz>(device: &D, uart: &Uart) {
     }
 }
 
+// TODO: Keep synchronized with `FramerControlError` in `tedium-tool`.
+#[derive(Copy, Clone, Debug)]
+#[repr(u8)]
 enum HostRequestError {
-    WrongEndpoint,
-    ShortPacket,
-    LongPacket,
-    InvalidCommand,
+    ShortPacket = 2,
+    LongPacket = 3,
+    InvalidCommand = 4,
+    RegisterAccess = 5,
 }
 
 fn error_str(e: HostRequestError) -> &'static str {
     match e {
-        HostRequestError::InvalidCommand => "invalid",
-        HostRequestError::ShortPacket    => "short",
-        HostRequestError::LongPacket     => "long",
-        HostRequestError::WrongEndpoint  => "endpoint",
+        HostRequestError::InvalidCommand => "invalid\n",
+        HostRequestError::ShortPacket    => "short\n",
+        HostRequestError::LongPacket     => "long\n",
+        HostRequestError::RegisterAccess => "register\n",
+    }
+}
+
+/// Every request is answered with a status byte (0 for success, otherwise a
+/// `HostRequestError`), the request's sequence number, and then whatever the
+/// command returns. The host matches responses to requests by sequence
+/// number, so a response to a request it gave up on isn't mistaken for the
+/// next one's.
+fn host_response(usb_in: &USBEndpointIn, sequence: u8, result: HostRequestResult<&[u8]>) {
+    let (status, payload) = match result {
+        Ok(payload) => (0, payload),
+        Err(e) => (e as u8, &[][..]),
+    };
+    usb_in.write_fifo(status);
+    usb_in.write_fifo(sequence);
+    for &v in payload {
+        usb_in.write_fifo(v);
     }
+    usb_in.transmit(EndpointNumber::FramerControl as u8);
 }
 
 type HostRequestResult<T> = core::result::Result<T, HostRequestError>;
@@ -515,6 +587,73 @@ type HostRequestResult<T> = core::result::Result<T, HostRequestError>;
 enum HostRequestCommand {
     RegisterRead(u16),
     RegisterWrite(u16, u8),
+    HdlcTransmit { channel: usize, hdlc_index: usize, length: usize, data: [u8; HDLC_TRANSMIT_BYTES_MAX] },
+    GetInfo,
+    Resync,
+    InterruptEnable { channel: usize, source: usize, mask: u8 },
+    ChannelEnable { channel: usize, enable: bool },
+    TimingReference { reference: Option<usize> },
+}
+
+/// Interrupt enable registers the host can set, by `InterruptEnable` source number.
+///
+// TODO: Keep synchronized with `InterruptEnable` in `tedium-tool`.
+const INTERRUPT_ENABLE_OFFSETS: [u16; 15] = [
+    0xb01,                  // BIER
+    0xb05,                  // FIER
+    0xb03,                  // AEIER
+    0xb07, 0xb17, 0xb27,    // DLIER1-3
+    0xb09,                  // SBIER
+    0xb0b, 0xb15, 0xb1b, 0xb1d, 0xb1f, 0xb21, 0xb23, 0xb25,    // RLCIER0-7
+];
+
+/// Bits the host can't clear from an interrupt enable register, by source
+/// number. Frames from the host are paced by TxEOT (see `hdlc_transmit_busy`),
+/// which only arrives through the HDLC block interrupt.
+fn interrupt_enable_required(source: usize) -> u8 {
+    match source {
+        0 => BIER::new().with_HDLC_ENB(1).into(),
+        3..=5 => DLIERx::new().with_TxEOT_ENB(1).into(),
+        _ => 0,
+    }
+}
+
+/// Commands `parse_host_command` understands, as a mask of command codes.
+const HOST_REQUEST_COMMANDS: u32 = (1 << 0x00) | (1 << 0x01) | (1 << 0x02) | (1 << 0x03) | (1 << 0x04) | (1 << 0x05) | (1 << 0x06) | (1 << 0x07);
+
+const INFO_BYTES: usize = 28;
+
+/// Response to `GetInfo`, so the host can tell whether it's talking to
+/// firmware it understands:
+///
+/// * 0..3: firmware version, major, minor, patch
+/// * 3..19: git hash the firmware was built from, ASCII, zero-padded
+/// * 19..21: USB descriptor version the firmware expects, little-endian BCD
+/// * 21..25: `HOST_REQUEST_COMMANDS`, little-endian
+/// * 25: channel count
+/// * 26: profile framing (FSR FSI)
+/// * 27: profile clock source (CSR CSS)
+///
+// TODO: Keep synchronized with `FirmwareInfo` in `tedium-tool`.
+fn info() -> [u8; INFO_BYTES] {
+    let mut info = [0u8; INFO_BYTES];
+
+    let version = |s: &str| s.parse::<u8>().unwrap_or(0);
+    info[0] = version(env!("CARGO_PKG_VERSION_MAJOR"));
+    info[1] = version(env!("CARGO_PKG_VERSION_MINOR"));
+    info[2] = version(env!("CARGO_PKG_VERSION_PATCH"));
+
+    for (d, s) in info[3..19].iter_mut().zip(env!("TEDIUM_SOC_GIT_HASH").bytes()) {
+        *d = s;
+    }
+
+    info[19..21].copy_from_slice(&DESCRIPTOR_VERSION.to_le_bytes());
+    info[21..25].copy_from_slice(&HOST_REQUEST_COMMANDS.to_le_bytes());
+    info[25] = 8;
+    info[26] = PROFILE_FRAMING as u8;
+    info[27] = PROFILE_CLOCK_SOURCE as u8;
+
+    info
 }
 
 struct USBOutReader<'a> {
@@ -529,18 +668,44 @@ impl<'a> USBOutReader<'a> {
     }
 
     fn read(&self) -> HostRequestResult<u8> {
-        if self.ep.get_have() != 0 {
+        if self.remaining() {
             Ok(self.ep.get_data())
         } else {
             Err(HostRequestError::ShortPacket)
         }
     }
+
+    fn remaining(&self) -> bool {
+        self.ep.get_have() != 0
+    }
+
+    fn drain(&self) {
+        while self.remaining() {
+            self.ep.get_data();
+        }
+    }
 }
 
-fn parse_host_request(usb_out: &USBEndpointOut) -> HostRequestResult<HostRequestCommand> {
+/// Requests start with a command byte and a sequence number, echoed in the
+/// response. The sequence number is zero if the packet is too short to hold one.
+fn parse_host_request(usb_out: &USBEndpointOut) -> (u8, HostRequestResult<HostRequestCommand>) {
     let reader = USBOutReader::from_endpoint(usb_out);
 
-    let command = reader.read()?;
+    let command = reader.read();
+    let sequence = reader.read().unwrap_or(0);
+    let result = command.and_then(|command| parse_host_command(command, &reader));
+
+    // Anything left over means the host and firmware disagree about the command.
+    let result = match result {
+        Ok(_) if reader.remaining() => Err(HostRequestError::LongPacket),
+        result => result,
+    };
+    reader.drain();
+
+    (sequence, result)
+}
+
+fn parse_host_command(command: u8, reader: &USBOutReader) -> HostRequestResult<HostRequestCommand> {
     match command {
         0x00 => {
             let l = reader.read()?;
@@ -555,6 +720,46 @@ fn parse_host_request(usb_out: &USBEndpointOut) -> HostRequestResult<HostRequest
             let value = reader.read()?;
             Ok(HostRequestCommand::RegisterWrite(address, value))
         },
+        0x02 => {
+            let channel = reader.read()? as usize;
+            let hdlc_index = reader.read()? as usize;
+            let length = reader.read()? as usize;
+            if channel >= 8 || hdlc_index >= 3 || length == 0 || length > HDLC_TRANSMIT_BYTES_MAX {
+                return Err(HostRequestError::InvalidCommand);
+            }
+            let mut data = [0u8; HDLC_TRANSMIT_BYTES_MAX];
+            for v in &mut data[0..length] {
+                *v = reader.read()?;
+            }
+            Ok(HostRequestCommand::HdlcTransmit { channel, hdlc_index, length, data })
+        },
+        0x03 => Ok(HostRequestCommand::GetInfo),
+        0x04 => Ok(HostRequestCommand::Resync),
+        0x05 => {
+            let channel = reader.read()? as usize;
+            let source = reader.read()? as usize;
+            let mask = reader.read()?;
+            if channel >= 8 || source >= INTERRUPT_ENABLE_OFFSETS.len() {
+                return Err(HostRequestError::InvalidCommand);
+            }
+            Ok(HostRequestCommand::InterruptEnable { channel, source, mask })
+        },
+        0x06 => {
+            let channel = reader.read()? as usize;
+            let enable = reader.read()? != 0;
+            if channel >= 8 {
+                return Err(HostRequestError::InvalidCommand);
+            }
+            Ok(HostRequestCommand::ChannelEnable { channel, enable })
+        },
+        0x07 => {
+            let reference = match reader.read()? {
+                TIMING_REFERENCE_FREE_RUN => None,
+                channel if channel < 8 => Some(channel as usize),
+                _ => return Err(HostRequestError::InvalidCommand),
+            };
+            Ok(HostRequestCommand::TimingReference { reference })
+        },
 
         _ => Err(HostRequestError::InvalidCommand),
     }
@@ -599,11 +804,23 @@ fn main() -> ! {
         enable_interrupts(&channel);
     }
 
-    // Set true to mimic all interrupt types being asserted,
-    // thereby sending all the current interrupt state and clearing
-    // all pending interrupts.
-    let mut resync_start = false;
-    let mut resync = false;
+    // Set true to mimic all interrupt types being asserted on a channel,
+    // thereby sending all its current interrupt state and clearing its
+    // pending interrupts. The host asks for this with `Resync` when it
+    // starts listening, or has missed interrupts. Enabling a channel does
+    // the same, as its interrupts went unreported while it was disabled.
+    let mut resync_pending = [false; 8];
+
+    // Set when a host frame is handed to an HDLC controller, cleared by the
+    // controller's TxEOT. The host is told to try again later while set.
+    let mut hdlc_transmit_busy = [[false; 3]; 8];
+
+    // Channels the host wants to hear about. The others aren't polled.
+    let mut channel_enabled = [true; 8];
+
+    // Recovered clock loss is a level, not an event. Only changes (and
+    // resyncs) are passed along to the host.
+    let mut clock_loss_reported = [false; 8];
 
     usb_out.set_ev_pending(usb_out.get_ev_pending());
     usb_out.set_ev_enable(1);
@@ -636,31 +853,79 @@ fn main() -> ! {
                             uart.write_str("IN: !empty\n");
                         }
 
-                        match parse_host_request(&usb_out) {
+                        let (sequence, request) = parse_host_request(&usb_out);
+                        match request {
                             Ok(cmd) => {
                                 match cmd {
                                     HostRequestCommand::RegisterRead(address) => {
                                         if let Ok(value) = device_access.read(address) {
-                                            usb_in.write_fifo(value);
-                                            usb_in.transmit(EndpointNumber::FramerControl as u8);
+                                            host_response(&usb_in, sequence, Ok(&[value]));
                                         } else {
                                             uart.write_str("rr ");
                                             uart.write_hex_u16(address);
                                             uart.write_str(" failed\n");
+                                            host_response(&usb_in, sequence, Err(HostRequestError::RegisterAccess));
                                         }
                                     },
                                     HostRequestCommand::RegisterWrite(address, value) => {
                                         if let Ok(()) = device_access.write(address, value) {
-                                            usb_in.transmit(EndpointNumber::FramerControl as u8);
+                                            host_response(&usb_in, sequence, Ok(&[]));
                                         } else {
                                             uart.write_str("wr ");
                                             uart.write_hex_u16(address);
                                             uart.write_str(" write failed\n");
+                                            host_response(&usb_in, sequence, Err(HostRequestError::RegisterAccess));
+                                        }
+                                    },
+                                    HostRequestCommand::HdlcTransmit { channel, hdlc_index, length, data } => {
+                                        if hdlc_transmit_busy[channel][hdlc_index] {
+                                            host_response(&usb_in, sequence, Ok(&[HdlcTransmitStatus::Busy as u8]));
+                                        } else if let Ok(()) = hdlc_transmit(&device.channel(channel), hdlc_index, &data[0..length]) {
+                                            hdlc_transmit_busy[channel][hdlc_index] = true;
+                                            host_response(&usb_in, sequence, Ok(&[HdlcTransmitStatus::Queued as u8]));
+                                        } else {
+                                            uart.write_str("hdlc tx failed\n");
+                                            host_response(&usb_in, sequence, Err(HostRequestError::RegisterAccess));
+                                        }
+                                    },
+                                    HostRequestCommand::GetInfo => {
+                                        host_response(&usb_in, sequence, Ok(&info()));
+                                    },
+                                    HostRequestCommand::Resync => {
+                                        resync_pending = [true; 8];
+                                        host_response(&usb_in, sequence, Ok(&[]));
+                                    },
+                                    HostRequestCommand::InterruptEnable { channel, source, mask } => {
+                                        let address = ((channel as u16) << 12) | INTERRUPT_ENABLE_OFFSETS[source];
+                                        let mask = mask | interrupt_enable_required(source);
+                                        if let Ok(()) = device_access.write(address, mask) {
+                                            host_response(&usb_in, sequence, Ok(&[]));
+                                        } else {
+                                            uart.write_str("ier failed\n");
+                                            host_response(&usb_in, sequence, Err(HostRequestError::RegisterAccess));
+                                        }
+                                    },
+                                    HostRequestCommand::ChannelEnable { channel, enable } => {
+                                        if enable && !channel_enabled[channel] {
+                                            resync_pending[channel] = true;
+                                        }
+                                        channel_enabled[channel] = enable;
+                                        host_response(&usb_in, sequence, Ok(&[]));
+                                    },
+                                    HostRequestCommand::TimingReference { reference } => {
+                                        if let Ok(()) = set_timing_reference(&device, &framer_control, reference) {
+                                            host_response(&usb_in, sequence, Ok(&[]));
+                                        } else {
+                                            uart.write_str("timing reference failed\n");
+                                            host_response(&usb_in, sequence, Err(HostRequestError::RegisterAccess));
                                         }
                                     },
                                 }
                             },
-                            Err(e) => uart.write_str(error_str(e)),
+                            Err(e) => {
+                                uart.write_str(error_str(e));
+                                host_response(&usb_in, sequence, Err(e));
+                            },
                         }
                     }
                     
@@ -681,25 +946,35 @@ fn main() -> ! {
                 test_points.toggle(2);
             }
 
-            if resync_start && channel_index == 0 {
-                resync_start = false;
-                resync = true;
-            }
-
-            let bisr = if resync {
+            let bisr = if !channel_enabled[channel_index] {
+                // Nothing to report. Pending status waits until the channel is enabled again.
                 BISR::new()
-                    .with_LBCODE(1)
-                    .with_HDLC(1)
-                    .with_SLIP(1)
-                    .with_ALARM(1)
-                    .with_T1FRAME(1)
             } else {
-                channel.bisr().read().unwrap()
+                let bisr = channel.bisr().read().unwrap();
+                let resync = core::mem::take(&mut resync_pending[channel_index]);
+
+                let clock_loss = bisr.RxClkLOS() != 0;
+                let clock_loss_changed = clock_loss != clock_loss_reported[channel_index];
+                clock_loss_reported[channel_index] = clock_loss;
+
+                let bisr = if resync {
+                    BISR::new()
+                        .with_LBCODE(1)
+                        .with_ONESEC(bisr.ONESEC())
+                        .with_HDLC(1)
+                        .with_SLIP(1)
+                        .with_ALARM(1)
+                        .with_T1FRAME(1)
+                } else {
+                    bisr
+                };
+                bisr.with_RxClkLOS((resync || clock_loss_changed) as u8)
             };
             let bisr_u8: u8 = bisr.into();
 
-            // Ignore the ONESEC interrupt, which apparently we can't shut off.
-            if bisr_u8 & 0b01101111u8 != 0 {
+            // ONESEC has no status of its own to read, but the host uses it
+            // to pace its performance monitor sampling.
+            if bisr_u8 & 0b01111111u8 != 0 {
                 usb_in_int.write_fifo(channel_index as u8);
                 usb_in_int.write_fifo(bisr_u8);
 
@@ -739,6 +1014,9 @@ fn main() -> ! {
                     // 
                     // NOTE : This bit is only active if the clock loss detection feature is
                     // enabled (Register - 0xN100)
+
+                    // Send the current state: 1 = clock lost, 0 = clock recovered.
+                    usb_in_int.write_fifo(clock_loss_reported[channel_index] as u8);
                 }
 
                 if bisr.ONESEC() != 0 {
@@ -750,6 +1028,8 @@ fn main() -> ! {
                     // declaring the "One Second" interrupt.
                     // 1 = Indicates that the T1 Receive Framer Block is currently declar-
                     // ing the "One Second" interrupt.
+
+                    // Nothing more to send. The bit itself is the tick.
                 }
 
                 if bisr.HDLC() != 0 {
@@ -773,6 +1053,10 @@ fn main() -> ! {
                         let dlsr = channel.dlsr(hdlc_index).read().unwrap();
                         usb_in_int.write_fifo(dlsr.into());
 
+                        if dlsr.TxEOT() != 0 {
+                            hdlc_transmit_busy[channel_index][hdlc_index] = false;
+                        }
+
                         let rdlbcr = channel.rdlbcr(hdlc_index).read().unwrap();
                         usb_in_int.write_fifo(rdlbcr.into());
 
@@ -869,10 +1153,6 @@ fn main() -> ! {
                 // decides the interrupt endpoint has died?
                 usb_in_int.transmit(EndpointNumber::Interrupt as u8);
             }
-
-            if channel_index == 7 {
-                resync = false;
-            }
         }
     }
 }
@@ -51,16 +51,22 @@ while True:
                 rlcisrs = ' '.join(f"{v:02x}" for v in rlcisrs)
                 print(f"{channel} RLCISRx=[{rlcisrs}]")
 
+            if bisr & 0x20: # RxClkLOS
+                lost = r.read(1)[0]
+                print(f"{channel} RxClkLOS={'lost' if lost else 'restored'}")
+
+            if bisr & 0x10: # ONESEC
+                print(f"{channel} ONESEC")
+
             if bisr & 0x08: # HDLC
                 for i in range(3):
-                    dlsr = r.read(1)[0]
-                    if dlsr != 0:
-                        rdlbcr = r.read(1)[0]
-                        rdlbc = rdlbcr & 0x7f
-                        lapdbcr = r.read(rdlbc)
+                    dlsr, rdlbcr = r.read(2)
+                    rdlbc = rdlbcr & 0x7f
+                    lapdbcr = r.read(rdlbc)
+                    ss7sr = r.read(1)[0]
+                    if dlsr != 0 or rdlbc != 0:
                         lapdbcr_s = format_bytes_hex(lapdbcr)
                         print(f"{channel} DLSR{i}={dlsr:02x} HDLC{i}=[{lapdbcr_s}]")
-                    ss7sr = r.read(1)[0]
             
             if bisr & 0x04: # SLIP
                 sbisr = r.read(1)[0]
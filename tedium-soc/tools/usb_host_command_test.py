@@ -38,6 +38,7 @@ class HostCommand:
                 and usb.util.endpoint_address(e.bEndpointAddress) == ENDPOINT
         )
         self._command_count = 0
+        self._sequence = 0
 
     def _send_command(self, b: bytes):
         try:
@@ -64,16 +65,31 @@ class HostCommand:
             else:
                 raise e
 
-    def _execute(self, b: bytes) -> bytes:
-        self._send_command(b)
-        return self._await_response()
+    def _execute(self, command: int, arguments: list) -> bytes:
+        # Requests are [command, sequence, arguments...], responses are
+        # [status, sequence, payload...]. A response with another sequence
+        # number belongs to an earlier request that timed out.
+        self._sequence = (self._sequence + 1) & 0xff
+        self._send_command([command, self._sequence] + arguments)
+        while True:
+            r = self._await_response()
+            if r is None:
+                return None
+            if len(r) < 2:
+                raise RuntimeError(f"short response: {bytes(r).hex()}")
+            status, sequence = r[0], r[1]
+            if sequence != self._sequence:
+                continue
+            if status != 0:
+                raise RuntimeError(f"command {command:#04x} failed with status {status}")
+            return r[2:]
 
     def register_read(self, address: int) -> int:
-        r = self._execute([0x00, address & 0xff, address >> 8])
+        r = self._execute(0x00, [address & 0xff, address >> 8])
         return r[0]
 
     def register_write(self, address: int, value: int):
-        self._execute([0x01, address & 0xff, address >> 8, value])
+        self._execute(0x01, [address & 0xff, address >> 8, value])
 
 def test_fast_writes(command: HostCommand):
     while True:
@@ -56,8 +56,9 @@ class FramerControlPeripheral(Peripheral, Elaboratable):
         self._pins = pins
 
         bank = self.csr_bank()
-        self._reset     = bank.csr(1, "rw")
-        self._if_enable = bank.csr(1, "rw")
+        self._reset           = bank.csr(1, "rw")
+        self._if_enable       = bank.csr(1, "rw")
+        self._tx_clock_select = bank.csr(3, "rw")
 
         self._bridge = self.bridge(data_width=32, granularity=8, alignment=2)
         self.bus = self._bridge.bus
@@ -78,6 +79,12 @@ class FramerControlPeripheral(Peripheral, Elaboratable):
                 self._pins.output_enable.eq(self._if_enable.w_data),
             ]
 
+        with m.If(self._tx_clock_select.w_stb):
+            m.d.sync += [
+                self._tx_clock_select.r_data.eq(self._tx_clock_select.w_data),
+                self._pins.tx_clock_select.eq(self._tx_clock_select.w_data),
+            ]
+
         return m
 
 class FramerRegistersPeripheral(Peripheral, Elaboratable):
@@ -165,6 +172,8 @@ class SoC(CPUSoC, Elaboratable):
         self.framer_control_pins = Record([
             ('reset',         [('o', 1)]),
             ('output_enable', [('o', 1)]),
+            # Channel whose recovered clock drives every TxSERCLK, for `TxClockSelect`.
+            ('tx_clock_select', [('o', 3)]),
         ])
 
         sync_clk_freq = 60.0e6
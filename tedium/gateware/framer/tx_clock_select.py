@@ -0,0 +1,29 @@
+from amaranth import *
+
+class TxClockSelect(Elaboratable):
+    """
+    Picks which span's recovered clock drives TxSERCLK on every channel.
+
+    `sclk` are the raw recovered clocks from the receivers, one bit per
+    channel.
+
+    `select` is the channel the SoC has chosen as the timing reference.
+    Channels with CSS set to External time their transmitters from
+    `serclk`, so they follow the reference when it changes.
+
+    The mux is combinational, as the clocks aren't in the `sync` domain.
+    Changing `select` may produce a runt clock pulse, which costs a slip
+    on each span. A failover costs that anyway.
+    """
+    def __init__(self, channels: int):
+        self.sclk = Signal(channels)
+        self.select = Signal(range(channels))
+
+        self.serclk = Signal()
+
+    def elaborate(self, platform) -> Module:
+        m = Module()
+
+        m.d.comb += self.serclk.eq(self.sclk.bit_select(self.select, 1))
+
+        return m
@@ -0,0 +1,56 @@
+//! Field layouts for `framer::map`, taken from the bitfield structs in
+//! `src/framer/register.rs`, so fields can be decoded and set by name.
+
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+const REGISTERS: &str = "src/framer/register.rs";
+
+fn main() {
+    println!("cargo:rerun-if-changed={REGISTERS}");
+
+    let source = fs::read_to_string(REGISTERS).expect("register definitions");
+    let mut out = String::new();
+
+    let mut lines = source.lines();
+    while let Some(line) = lines.next() {
+        if !line.starts_with("#[bitfield(bits=8)]") {
+            continue;
+        }
+
+        let name = lines.by_ref()
+            .find_map(|l| l.strip_prefix("pub struct ")?.strip_suffix(" {"))
+            .expect("struct after #[bitfield]");
+        writeln!(out, "impl RegisterFields for {name} {{").unwrap();
+        writeln!(out, "    const FIELDS: &'static [Field] = &[").unwrap();
+
+        // Fields in order, most significant first, as modular-bitfield-msb packs them.
+        for line in lines.by_ref().take_while(|l| !l.starts_with('}')) {
+            let line = line.split("//").next().unwrap().trim();
+            if line.is_empty() || line.starts_with("///") {
+                continue;
+            }
+            let (reserved, line) = match line.strip_prefix("#[skip]") {
+                Some(line) => (true, line.trim()),
+                None => (false, line),
+            };
+            let (field, specifier) = line.trim_start_matches("pub ")
+                .trim_end_matches(',')
+                .split_once(": ")
+                .unwrap_or_else(|| panic!("{name} field {line:?}"));
+            if reserved {
+                writeln!(out, "        Field::new::<{specifier}>(None),").unwrap();
+            } else {
+                writeln!(out, "        Field::new::<{specifier}>(Some({field:?})),").unwrap();
+            }
+        }
+
+        writeln!(out, "    ];").unwrap();
+        writeln!(out, "}}").unwrap();
+    }
+
+    let path = Path::new(&env::var("OUT_DIR").unwrap()).join("register_fields.rs");
+    fs::write(path, out).unwrap();
+}
@@ -0,0 +1,25 @@
+#![no_main]
+
+//! Throw arbitrary bytes at the interrupt endpoint packet parser.
+//!
+//! `cargo fuzz run interrupt_status`
+
+use libfuzzer_sys::fuzz_target;
+
+// tedium-tool is a binary crate, so borrow the parser and what it depends on.
+#[allow(dead_code)]
+#[path = "../../src/framer/register.rs"]
+mod register;
+
+#[allow(dead_code)]
+#[path = "../../src/framer/interrupt_status.rs"]
+mod interrupt_status;
+
+use interrupt_status::FramerInterruptStatus;
+
+fuzz_target!(|packet: &[u8]| {
+    // Whatever parses must be exactly what the firmware would have sent.
+    if let Ok(status) = FramerInterruptStatus::from_slice(packet) {
+        assert_eq!(status.to_bytes(), packet);
+    }
+});
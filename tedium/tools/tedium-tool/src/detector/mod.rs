@@ -11,3 +11,4 @@ pub trait Detector {
 
 pub mod goertzel;
 pub mod dtmf;
+pub mod prbs;
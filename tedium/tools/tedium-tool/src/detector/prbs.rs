@@ -0,0 +1,192 @@
+use crate::generator::prbs::PrbsPattern;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum PrbsCheckerEvent {
+    SyncAcquired,
+    SyncLost,
+}
+
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub struct PrbsCheckerStatistics {
+    /// Bits checked while in sync.
+    pub bits: u64,
+    pub bit_errors: u64,
+    pub sync_losses: u32,
+    pub in_sync: bool,
+}
+
+/// Receive side of a PRBS test.
+///
+/// While hunting, the checker predicts each bit from the bits received before
+/// it, and declares sync after a long enough run of correct predictions. Once
+/// in sync, it predicts from its own register instead, so a line error counts
+/// once rather than once per tap. A block with too many errors means the
+/// pattern has slipped, and the checker goes back to hunting.
+///
+pub struct PrbsChecker {
+    pattern: PrbsPattern,
+    state: u32,
+    in_sync: bool,
+    run: u32,
+    block_bits: u32,
+    block_errors: u32,
+    statistics: PrbsCheckerStatistics,
+}
+
+impl PrbsChecker {
+    const SYNC_BITS: u32 = 64;
+    const BLOCK_BITS: u32 = 256;
+    /// A quarter of the block in error is well beyond any line error rate
+    /// worth measuring, and well short of the half a slip produces.
+    const BLOCK_ERRORS_MAX: u32 = Self::BLOCK_BITS / 4;
+
+    pub fn new(pattern: PrbsPattern) -> Self {
+        Self {
+            pattern,
+            state: 0,
+            in_sync: false,
+            run: 0,
+            block_bits: 0,
+            block_errors: 0,
+            statistics: PrbsCheckerStatistics::default(),
+        }
+    }
+
+    pub fn statistics(&self) -> PrbsCheckerStatistics {
+        PrbsCheckerStatistics {
+            in_sync: self.in_sync,
+            ..self.statistics
+        }
+    }
+
+    pub fn push_bit(&mut self, bit: bool) -> Option<PrbsCheckerEvent> {
+        let predicted = self.pattern.feedback(self.state);
+
+        if !self.in_sync {
+            // An all-zeros register predicts all zeros forever; don't lock to an idle line.
+            if predicted == bit && self.state != 0 {
+                self.run += 1;
+            } else {
+                self.run = 0;
+            }
+            self.shift(bit);
+
+            if self.run >= Self::SYNC_BITS {
+                self.in_sync = true;
+                self.block_bits = 0;
+                self.block_errors = 0;
+                return Some(PrbsCheckerEvent::SyncAcquired);
+            }
+            return None;
+        }
+
+        self.shift(predicted);
+        self.statistics.bits += 1;
+        self.block_bits += 1;
+        if bit != predicted {
+            self.statistics.bit_errors += 1;
+            self.block_errors += 1;
+        }
+
+        if self.block_bits < Self::BLOCK_BITS {
+            return None;
+        }
+
+        let lost = self.block_errors >= Self::BLOCK_ERRORS_MAX;
+        if lost {
+            // Those errors were the slip, not the line.
+            self.statistics.bits -= self.block_bits as u64;
+            self.statistics.bit_errors -= self.block_errors as u64;
+            self.statistics.sync_losses += 1;
+            self.in_sync = false;
+            self.run = 0;
+        }
+        self.block_bits = 0;
+        self.block_errors = 0;
+
+        lost.then_some(PrbsCheckerEvent::SyncLost)
+    }
+
+    pub fn push_byte(&mut self, byte: u8) -> Option<PrbsCheckerEvent> {
+        (0..8).rev().fold(None, |event, n| self.push_bit((byte >> n) & 1 != 0).or(event))
+    }
+
+    /// Account for bits we know were lost before they reached us, so the
+    /// pattern doesn't appear to slip.
+    pub fn skip_bits(&mut self, count: u64) {
+        if self.in_sync {
+            for _ in 0..count {
+                let predicted = self.pattern.feedback(self.state);
+                self.shift(predicted);
+            }
+        }
+    }
+
+    fn shift(&mut self, bit: bool) {
+        self.state = ((self.state << 1) | bit as u32) & self.pattern.mask();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::prbs::PrbsGenerator;
+
+    fn run(dut: &mut PrbsChecker, bytes: impl Iterator<Item=u8>) -> Vec<PrbsCheckerEvent> {
+        bytes.filter_map(|b| dut.push_byte(b)).collect()
+    }
+
+    #[test]
+    fn sync_and_count_errors() {
+        let mut generator = PrbsGenerator::new(PrbsPattern::Prbs15);
+        let mut dut = PrbsChecker::new(PrbsPattern::Prbs15);
+
+        // Idle line doesn't sync.
+        assert!(run(&mut dut, [0x00u8; 64].into_iter().chain([0xff; 64])).is_empty());
+
+        assert_eq!(run(&mut dut, (0..100).map(|_| generator.next_byte())), [PrbsCheckerEvent::SyncAcquired]);
+
+        // A single bit error counts once.
+        let errored = generator.next_byte() ^ 0x10;
+        assert!(dut.push_byte(errored).is_none());
+        assert!(run(&mut dut, (0..100).map(|_| generator.next_byte())).is_empty());
+
+        let statistics = dut.statistics();
+        assert!(statistics.in_sync);
+        assert_eq!(statistics.bit_errors, 1);
+        assert_eq!(statistics.sync_losses, 0);
+    }
+
+    #[test]
+    fn slip_loses_sync() {
+        let mut generator = PrbsGenerator::new(PrbsPattern::Prbs11);
+        let mut dut = PrbsChecker::new(PrbsPattern::Prbs11);
+        run(&mut dut, (0..100).map(|_| generator.next_byte()));
+        let bit_errors_before = dut.statistics().bit_errors;
+
+        // Drop a byte: everything after it is out of step.
+        generator.next_byte();
+        assert_eq!(run(&mut dut, (0..100).map(|_| generator.next_byte())), [
+            PrbsCheckerEvent::SyncLost,
+            PrbsCheckerEvent::SyncAcquired,
+        ]);
+
+        let statistics = dut.statistics();
+        assert_eq!(statistics.sync_losses, 1);
+        assert!(statistics.bit_errors - bit_errors_before < 64);
+    }
+
+    #[test]
+    fn skipped_bits_keep_sync() {
+        let mut generator = PrbsGenerator::new(PrbsPattern::Prbs23);
+        let mut dut = PrbsChecker::new(PrbsPattern::Prbs23);
+        run(&mut dut, (0..100).map(|_| generator.next_byte()));
+
+        for _ in 0..3 {
+            generator.next_byte();
+        }
+        dut.skip_bits(24);
+        assert!(run(&mut dut, (0..100).map(|_| generator.next_byte())).is_empty());
+        assert_eq!(dut.statistics().bit_errors, 0);
+    }
+}
@@ -0,0 +1,250 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use super::interrupt::AlarmStatus;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Alarm {
+    /// Loss of signal or loss of frame at this end.
+    Red,
+    /// The far end reports it has lost our signal (RAI).
+    Yellow,
+    /// The far end, or something between, is sending all ones (AIS).
+    Blue,
+}
+
+/// Instantaneous defects, as last reported by the framer.
+///
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub struct Defects {
+    pub los: bool,
+    pub oof: bool,
+    pub ais: bool,
+    pub rai: bool,
+}
+
+impl Defects {
+    pub fn from_status(status: &AlarmStatus) -> Self {
+        let (aeisr, ciasr) = (status.aeisr, status.ciasr);
+
+        // AIS-CI and RAI-CI are the customer installation's flavours of AIS
+        // and RAI. They still mean the span is down.
+        Self {
+            los: aeisr.LOS_State() != 0,
+            oof: aeisr.RxOOF_State() != 0,
+            ais: aeisr.RxAIS_State() != 0 || ciasr.RxAIS_CI_state() != 0,
+            rai: aeisr.RxYEL_State() != 0 || ciasr.RxRAI_CI_state() != 0,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct AlarmTransition {
+    pub timestamp: Instant,
+    pub alarm: Alarm,
+    pub active: bool,
+}
+
+/// Declares an alarm once its defect has persisted for the integration time,
+/// and clears it once the defect has been absent for the clearing time.
+///
+struct AlarmIntegrator {
+    active: bool,
+    /// When the defect last stopped agreeing with `active`.
+    pending_since: Option<Instant>,
+}
+
+impl AlarmIntegrator {
+    fn new() -> Self {
+        Self {
+            active: false,
+            pending_since: None,
+        }
+    }
+
+    fn update(&mut self, defect: bool, now: Instant, integration: Duration, clearing: Duration) -> Option<bool> {
+        if defect == self.active {
+            self.pending_since = None;
+            return None;
+        }
+
+        let since = *self.pending_since.get_or_insert(now);
+        let threshold = if defect { integration } else { clearing };
+        if now.duration_since(since) >= threshold {
+            self.active = defect;
+            self.pending_since = None;
+            Some(self.active)
+        } else {
+            None
+        }
+    }
+}
+
+/// Red, yellow, and blue alarm state for one span.
+///
+/// Defects come from the framer's alarm interrupts, which only arrive when
+/// something changes, so `tick` must be called regularly to let the
+/// integration and clearing timers run out.
+///
+pub struct SpanAlarms {
+    defects: Defects,
+    red: AlarmIntegrator,
+    yellow: AlarmIntegrator,
+    blue: AlarmIntegrator,
+    clearing: Duration,
+    history: VecDeque<AlarmTransition>,
+}
+
+impl SpanAlarms {
+    /// 2.5 seconds of a continuous defect declares the alarm (T1.231).
+    pub const INTEGRATION: Duration = Duration::from_millis(2500);
+
+    /// Clearing time. Bellcore allows 10 to 20 seconds; some carriers use 15.
+    pub const CLEARING_DEFAULT: Duration = Duration::from_secs(10);
+
+    const HISTORY_CAPACITY: usize = 64;
+
+    pub fn new() -> Self {
+        Self::with_clearing(Self::CLEARING_DEFAULT)
+    }
+
+    pub fn with_clearing(clearing: Duration) -> Self {
+        Self {
+            defects: Defects::default(),
+            red: AlarmIntegrator::new(),
+            yellow: AlarmIntegrator::new(),
+            blue: AlarmIntegrator::new(),
+            clearing,
+            history: VecDeque::with_capacity(Self::HISTORY_CAPACITY),
+        }
+    }
+
+    pub fn defects(&self) -> Defects {
+        self.defects
+    }
+
+    pub fn is_active(&self, alarm: Alarm) -> bool {
+        match alarm {
+            Alarm::Red => self.red.active,
+            Alarm::Yellow => self.yellow.active,
+            Alarm::Blue => self.blue.active,
+        }
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        !self.red.active && !self.yellow.active && !self.blue.active
+    }
+
+    /// Most recent transitions, oldest first.
+    pub fn history(&self) -> impl Iterator<Item=&AlarmTransition> {
+        self.history.iter()
+    }
+
+    pub fn update<F>(&mut self, defects: Defects, now: Instant, transition_fn: F)
+        where F: FnMut(AlarmTransition)
+    {
+        self.defects = defects;
+        self.tick(now, transition_fn);
+    }
+
+    pub fn tick<F>(&mut self, now: Instant, mut transition_fn: F)
+        where F: FnMut(AlarmTransition)
+    {
+        let defects = self.defects;
+        let (integration, clearing) = (Self::INTEGRATION, self.clearing);
+
+        let changes = [
+            (Alarm::Red, self.red.update(defects.los || defects.oof, now, integration, clearing)),
+            (Alarm::Yellow, self.yellow.update(defects.rai, now, integration, clearing)),
+            (Alarm::Blue, self.blue.update(defects.ais, now, integration, clearing)),
+        ];
+
+        for (alarm, change) in changes {
+            if let Some(active) = change {
+                let transition = AlarmTransition { timestamp: now, alarm, active };
+                if self.history.len() == Self::HISTORY_CAPACITY {
+                    self.history.pop_front();
+                }
+                self.history.push_back(transition);
+                transition_fn(transition);
+            }
+        }
+    }
+}
+
+impl fmt::Display for SpanAlarms {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_healthy() {
+            return write!(f, "OK");
+        }
+
+        let names = [(Alarm::Red, "RED"), (Alarm::Yellow, "YEL"), (Alarm::Blue, "BLU")];
+        let active: Vec<_> = names.iter()
+            .filter(|(alarm, _)| self.is_active(*alarm))
+            .map(|(_, name)| *name)
+            .collect();
+        write!(f, "{}", active.join("+"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(dut: &mut SpanAlarms, now: Instant) -> Vec<(Alarm, bool)> {
+        let mut transitions = Vec::new();
+        dut.tick(now, |t| transitions.push((t.alarm, t.active)));
+        transitions
+    }
+
+    #[test]
+    fn red_alarm_integrates_and_clears() {
+        let t0 = Instant::now();
+        let s = |ms: u64| t0 + Duration::from_millis(ms);
+
+        let mut dut = SpanAlarms::new();
+        dut.update(Defects { los: true, ..Default::default() }, t0, |_| panic!());
+
+        assert!(run(&mut dut, s(2400)).is_empty());
+        assert_eq!(run(&mut dut, s(2500)), [(Alarm::Red, true)]);
+        assert!(!dut.is_healthy());
+
+        // A brief recovery doesn't clear the alarm...
+        dut.update(Defects::default(), s(3000), |_| panic!());
+        dut.update(Defects { oof: true, ..Default::default() }, s(8000), |_| panic!());
+        assert!(run(&mut dut, s(14000)).is_empty());
+
+        // ...but ten clean seconds do.
+        dut.update(Defects::default(), s(15000), |_| panic!());
+        assert!(run(&mut dut, s(24900)).is_empty());
+        assert_eq!(run(&mut dut, s(25000)), [(Alarm::Red, false)]);
+        assert!(dut.is_healthy());
+
+        let history: Vec<_> = dut.history().map(|t| (t.timestamp, t.active)).collect();
+        assert_eq!(history, [(s(2500), true), (s(25000), false)]);
+    }
+
+    #[test]
+    fn short_defect_ignored() {
+        let t0 = Instant::now();
+        let mut dut = SpanAlarms::new();
+        dut.update(Defects { ais: true, ..Default::default() }, t0, |_| panic!());
+        dut.update(Defects::default(), t0 + Duration::from_secs(2), |_| panic!());
+        assert!(run(&mut dut, t0 + Duration::from_secs(60)).is_empty());
+    }
+
+    #[test]
+    fn independent_alarms() {
+        let t0 = Instant::now();
+        let mut dut = SpanAlarms::with_clearing(Duration::from_secs(15));
+        dut.update(Defects { ais: true, rai: true, ..Default::default() }, t0, |_| panic!());
+        assert_eq!(run(&mut dut, t0 + Duration::from_secs(3)), [(Alarm::Yellow, true), (Alarm::Blue, true)]);
+        assert_eq!(dut.to_string(), "YEL+BLU");
+
+        dut.update(Defects { ais: true, ..Default::default() }, t0 + Duration::from_secs(4), |_| panic!());
+        assert!(run(&mut dut, t0 + Duration::from_secs(18)).is_empty());
+        assert_eq!(run(&mut dut, t0 + Duration::from_secs(19)), [(Alarm::Yellow, false)]);
+        assert_eq!(dut.to_string(), "BLU");
+    }
+}
@@ -2,15 +2,17 @@ use std::collections::HashMap;
 use std::mem::size_of;
 use std::slice;
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::thread;
 
 use crate::codec::ulaw;
 use crate::detector::{dtmf, Detector};
+use crate::detector::prbs::{PrbsChecker, PrbsCheckerEvent, PrbsCheckerStatistics};
 use crate::framer::device::open_device;
 use crate::framer::usb::{InterfaceNumber, AlternateSetting, EndpointNumber, Transfer, CallbackIn, CallbackInWrapper, CallbackOut, CallbackOutWrapper};
 use crate::generator::ToneGenerator;
 use crate::generator::dual_tone::DualToneGenerator;
+use crate::generator::prbs::{PrbsGenerator, PrbsPattern};
 
 use audio_thread_priority::promote_current_thread_to_real_time;
 use bytemuck::{Pod, Zeroable};
@@ -23,6 +25,8 @@ use rusb::constants::{LIBUSB_TRANSFER_COMPLETED, LIBUSB_SUCCESS};
 use thiserror::Error;
 
 use super::FramerEvent;
+use super::crc6::{Crc6, Crc6Monitor};
+use super::fdl::BitOrientedMessageDecoder;
 
 #[derive(Error, Debug)]
 pub enum PumpError {
@@ -65,6 +69,10 @@ pub enum Patch {
     Idle,
     Input(TimeslotAddress),
     Tone(ToneSource),
+    /// One byte of the channel's PRBS per frame. All the timeslots on a channel
+    /// patched to the same pattern share a generator, in timeslot order, so a
+    /// group of timeslots carries one fractional T1 bit stream.
+    Prbs(PrbsPattern),
 }
 
 struct Patching {
@@ -119,16 +127,99 @@ impl Default for Patching {
 #[derive(Copy, Clone, Debug)]
 pub enum ProcessorMessage {
     Patch(TimeslotAddress, Patch),
+    /// Check a PRBS received on a channel, in the timeslots set in the mask
+    /// (bit 0 is timeslot 0). `None` stops checking.
+    CheckPrbs(usize, u32, Option<PrbsPattern>),
+}
+
+/// Software BERT results for one channel.
+///
+/// The frame stream can slip on its way through USB: the gateware FIFOs can
+/// underflow or overflow, and the host can drop or fail to supply a frame.
+/// Those slips look exactly like line slips to the PRBS checker, so sync
+/// losses shortly after a USB slip are counted against USB rather than the line.
+///
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub struct SoftBertStatistics {
+    pub checker: PrbsCheckerStatistics,
+    pub usb_slips: u32,
+    pub usb_sync_losses: u32,
+    pub line_sync_losses: u32,
+}
+
+impl SoftBertStatistics {
+    pub fn bit_error_rate(&self) -> Option<f64> {
+        if self.checker.bits > 0 {
+            Some(self.checker.bit_errors as f64 / self.checker.bits as f64)
+        } else {
+            None
+        }
+    }
+}
+
+struct SoftBert {
+    channel: usize,
+    timeslots: u32,
+    checker: PrbsChecker,
+    statistics: SoftBertStatistics,
+    frames_since_report: u32,
+}
+
+impl SoftBert {
+    /// How long after a USB slip a sync loss is still blamed on it.
+    const USB_SLIP_FRAMES: u32 = 8000;
+    const REPORT_FRAMES: u32 = 8000;
+
+    fn new(channel: usize, timeslots: u32, pattern: PrbsPattern) -> Self {
+        Self {
+            channel,
+            timeslots,
+            checker: PrbsChecker::new(pattern),
+            statistics: SoftBertStatistics::default(),
+            frames_since_report: 0,
+        }
+    }
+
+    fn bits_per_frame(&self) -> u64 {
+        self.timeslots.count_ones() as u64 * 8
+    }
+
+    fn process_frame(&mut self, frame_in: &InternalFrame, frames_since_usb_slip: u32) -> Option<SoftBertStatistics> {
+        for timeslot in (0..TIMESLOTS_PER_CHANNEL).filter(|n| (self.timeslots >> n) & 1 != 0) {
+            let sample = frame_in.frame.timeslot(&TimeslotAddress::new(self.channel, timeslot));
+            if let Some(PrbsCheckerEvent::SyncLost) = self.checker.push_byte(sample) {
+                if frames_since_usb_slip < Self::USB_SLIP_FRAMES {
+                    self.statistics.usb_sync_losses += 1;
+                } else {
+                    self.statistics.line_sync_losses += 1;
+                }
+            }
+        }
+
+        self.frames_since_report += 1;
+        if self.frames_since_report >= Self::REPORT_FRAMES {
+            self.frames_since_report = 0;
+            self.statistics.checker = self.checker.statistics();
+            Some(self.statistics)
+        } else {
+            None
+        }
+    }
 }
 
 struct AudioProcessor {
     patching: Patching,
     tone_plant: HashMap<ToneSource, Box<dyn ToneGenerator>>,
+    prbs_plant: HashMap<(usize, PrbsPattern), PrbsGenerator>,
+    soft_berts: Vec<SoftBert>,
+    frames_since_usb_slip: u32,
+    frame_count_discontinuity_monitor: FrameCountDiscontinuityMonitor,
     processor_receiver: Receiver<ProcessorMessage>,
+    event_sender: Sender<FramerEvent>,
 }
 
 impl AudioProcessor {
-    fn new(processor_receiver: Receiver<ProcessorMessage>) -> Self {
+    fn new(processor_receiver: Receiver<ProcessorMessage>, event_sender: Sender<FramerEvent>) -> Self {
         let mut tone_plant: HashMap<ToneSource, Box<dyn ToneGenerator>> = HashMap::new();
         tone_plant.insert(ToneSource::DialTonePrecise, Box::new(DualToneGenerator::new(350.0, 440.0)));
         tone_plant.insert(ToneSource::Ringback, Box::new(DualToneGenerator::new(440.0, 480.0)));
@@ -136,7 +227,12 @@ impl AudioProcessor {
         Self {
             patching: Patching::default(),
             tone_plant,
+            prbs_plant: HashMap::new(),
+            soft_berts: Vec::new(),
+            frames_since_usb_slip: u32::MAX,
+            frame_count_discontinuity_monitor: FrameCountDiscontinuityMonitor::new(),
             processor_receiver,
+            event_sender,
         }
     }
 
@@ -145,14 +241,53 @@ impl AudioProcessor {
             ProcessorMessage::Patch(address, patch) => {
                 *self.patching.timeslot_mut(&address) = patch;
             },
+            ProcessorMessage::CheckPrbs(channel, timeslots, pattern) => {
+                self.soft_berts.retain(|bert| bert.channel != channel);
+                if let Some(pattern) = pattern {
+                    self.soft_berts.push(SoftBert::new(channel, timeslots, pattern));
+                }
+            },
         }
     }
 
-    fn process_frame(&mut self, frame_in: &Frame) -> Frame {
+    /// Note a frame lost or repeated somewhere between here and the framer.
+    fn usb_slip(&mut self) {
+        self.frames_since_usb_slip = 0;
+        for bert in &mut self.soft_berts {
+            bert.statistics.usb_slips += 1;
+        }
+    }
+
+    fn check_frame(&mut self, frame_in: &InternalFrame) {
+        if let Some(missing_frames_count) = self.frame_count_discontinuity_monitor.update(frame_in.frame_count) {
+            // The receive pattern can skip the missing frames, but the transmit
+            // pattern (looped from them) is short those frames too.
+            self.usb_slip();
+            for bert in &mut self.soft_berts {
+                let bits = bert.bits_per_frame() * missing_frames_count as u64;
+                bert.checker.skip_bits(bits);
+            }
+        }
+
+        for bert in &mut self.soft_berts {
+            if let Some(statistics) = bert.process_frame(frame_in, self.frames_since_usb_slip) {
+                if let Err(e) = self.event_sender.send(FramerEvent::SoftBert(frame_in.frame_count, bert.channel, statistics)) {
+                    eprintln!("AudioProcessor: event_sender.send(): {e:?}");
+                }
+            }
+        }
+
+        self.frames_since_usb_slip = self.frames_since_usb_slip.saturating_add(1);
+    }
+
+    fn process_frame(&mut self, internal_frame_in: &InternalFrame) -> Frame {
         while let Ok(message) = self.processor_receiver.try_recv() {
             self.process_message(message);
         }
 
+        self.check_frame(internal_frame_in);
+        let frame_in = &internal_frame_in.frame;
+
         // Update generator outputs.
         for generator in self.tone_plant.values_mut() {
             generator.advance();
@@ -177,6 +312,11 @@ impl AudioProcessor {
                             };
                             ulaw::encode(output)
                         },
+                        Patch::Prbs(pattern) => {
+                            self.prbs_plant.entry((out_channel, *pattern))
+                                .or_insert_with(|| PrbsGenerator::new(*pattern))
+                                .next_byte()
+                        },
                     };
             }
         }
@@ -286,6 +426,7 @@ struct ExtendedSuperFrameState {
     crc_bits: u32,
     fdl_bits: u32,
     bits_collected: u32,
+    crc: Crc6,
 }
 
 impl ExtendedSuperFrameState {
@@ -296,28 +437,50 @@ impl ExtendedSuperFrameState {
             crc_bits: 0,
             fdl_bits: 0,
             bits_collected: 0,
+            crc: Crc6::new(),
         }
     }
 
-    fn process_frame(&mut self, frame: &InternalFrame, mf: bool) -> bool {
+    fn process_frame(&mut self, frame: &InternalFrame, channel: usize) -> bool {
         let frame_in_superframe = frame.frame_count.wrapping_sub(self.superframe_frame_count);
         if frame_in_superframe >= 24 {
             return false;
         }
 
-        let mf_phase = frame_in_superframe & 3;
-        let mf_bit = mf as u32;
-        match mf_phase {
-            0 | 2 => self.fdl_bits     = (self.fdl_bits     << 1) | mf_bit,
-            1     => self.crc_bits     = (self.crc_bits     << 1) | mf_bit,
-            3     => self.framing_bits = (self.framing_bits << 1) | mf_bit,
+        self.bits_collected |= 1 << frame_in_superframe;
+
+        // CRC-6 is computed with the F-bit forced to one.
+        self.crc.update_bit(true);
+        for timeslot_index in 0..24 {
+            let address = TimeslotAddress::new(channel, timeslot_index);
+            self.crc.update_byte(frame.frame.timeslot(&address));
+        }
+
+        let f = (frame.f_bits >> channel) & 1 != 0;
+
+        // ESF F-bit assignments, counting frames from zero:
+        // * FDL (4 kbit/s data link) in even frames.
+        // * CRC-6 in frames 1, 5, 9, 13, 17, 21.
+        // * Framing pattern sequence (001011) in frames 3, 7, 11, 15, 19, 23.
+        let f_phase = frame_in_superframe & 3;
+        let f_bit = f as u32;
+        match f_phase {
+            0 | 2 => self.fdl_bits     = (self.fdl_bits     << 1) | f_bit,
+            1     => self.crc_bits     = (self.crc_bits     << 1) | f_bit,
+            3     => self.framing_bits = (self.framing_bits << 1) | f_bit,
             _     => unreachable!(),
         }
 
         let last_frame = frame_in_superframe == 23;
-        let frame_valid = self.bits_collected == ((1 << 23) - 1);
+        let frame_valid = self.bits_collected == ((1 << 24) - 1);
         last_frame && frame_valid
     }
+
+    /// FDL bits, in the order they were received.
+    fn fdl(&self) -> impl Iterator<Item=bool> {
+        let fdl_bits = self.fdl_bits;
+        (0..12).rev().map(move |n| (fdl_bits >> n) & 1 != 0)
+    }
 }
 
 impl Default for ExtendedSuperFrameState {
@@ -328,6 +491,7 @@ impl Default for ExtendedSuperFrameState {
             crc_bits: 0,
             fdl_bits: 0,
             bits_collected: 0,
+            crc: Crc6::new(),
         }
     }
 }
@@ -365,12 +529,18 @@ impl FrameCountDiscontinuityMonitor {
 #[derive(Copy, Clone, Debug)]
 struct SuperframeState {
     robbed_bit_debouncer: RobbedBitDebouncer,
+    extended_superframe: ExtendedSuperFrameState,
+    bit_oriented_message_decoder: BitOrientedMessageDecoder,
+    crc6_monitor: Crc6Monitor,
 }
 
 impl Default for SuperframeState {
     fn default() -> Self {
         Self {
             robbed_bit_debouncer: RobbedBitDebouncer::new(),
+            extended_superframe: ExtendedSuperFrameState::default(),
+            bit_oriented_message_decoder: BitOrientedMessageDecoder::new(),
+            crc6_monitor: Crc6Monitor::new(),
         }
     }
 }
@@ -405,12 +575,32 @@ impl SignalingProcessor {
             let mf = mf_bit != 0;
             if mf {
                 state.robbed_bit_debouncer.new_frame(frame_in.frame_count);
+                state.extended_superframe = ExtendedSuperFrameState::from_timestamp(frame_in.frame_count);
             }
             state.robbed_bit_debouncer.process_frame(&frame_in, channel_index, |timestamp_changed, timeslot_address, rbs_state| {
                 if let Err(e) = self.event_sender.send(FramerEvent::RobbedBitState(timestamp_changed, timeslot_address, rbs_state)) {
                     eprintln!("SignalingProcessor: event_sender.send(): {e:?}");
                 }
             });
+
+            if state.extended_superframe.process_frame(&frame_in, channel_index) {
+                let superframe_frame_count = state.extended_superframe.superframe_frame_count;
+                for fdl_bit in state.extended_superframe.fdl() {
+                    state.bit_oriented_message_decoder.push_bit(fdl_bit, |message| {
+                        if let Err(e) = self.event_sender.send(FramerEvent::BitOrientedMessage(superframe_frame_count, channel_index, message)) {
+                            eprintln!("SignalingProcessor: event_sender.send(): {e:?}");
+                        }
+                    });
+                }
+
+                let computed_crc = state.extended_superframe.crc.value();
+                let received_crc = state.extended_superframe.crc_bits as u8;
+                if let Some(statistics) = state.crc6_monitor.superframe(superframe_frame_count, computed_crc, received_crc) {
+                    if let Err(e) = self.event_sender.send(FramerEvent::Crc6Statistics(superframe_frame_count, channel_index, statistics)) {
+                        eprintln!("SignalingProcessor: event_sender.send(): {e:?}");
+                    }
+                }
+            }
         }
 
         // Update detectors with new input samples.
@@ -535,6 +725,7 @@ struct InternalFrame {
     frame: Frame,
     frame_count: u32,
     mf_bits: u8,
+    f_bits: u8,
 }
 
 struct LoopbackFrameHandler {
@@ -543,6 +734,8 @@ struct LoopbackFrameHandler {
     processed_frames_producer: Producer<InternalFrame>,
     processed_frames_consumer: Consumer<InternalFrame>,
     processor: AudioProcessor,
+    /// Gateware FIFO underflow and overflow counts, as last seen.
+    fifo_error_counts: Option<(u16, u16)>,
     debug_sender: Sender<DebugMessage>,
 }
 
@@ -576,7 +769,8 @@ impl LoopbackFrameHandler {
             unprocessed_frames_consumer,
             processed_frames_producer,
             processed_frames_consumer,
-            processor: AudioProcessor::new(processor_receiver),
+            processor: AudioProcessor::new(processor_receiver, event_sender),
+            fifo_error_counts: None,
             debug_sender,
         }
     }
@@ -590,6 +784,7 @@ impl LoopbackFrameHandler {
 struct RxFrameReport {
     frame_count: u32,
     mf_bits: u8,
+    f_bits: u8,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -796,6 +991,7 @@ impl RxPacketProcessor {
                 frame: frame_in.frame,
                 frame_count: frame_in.report.frame_count,
                 mf_bits: frame_in.report.mf_bits,
+                f_bits: frame_in.report.f_bits,
             };
             if let Err(_) = self.unprocessed_frames_producer.push(frame) {
                 self.framer_cumulative_statistics.ringbuf_full_drop_count += 1;
@@ -854,12 +1050,27 @@ impl LoopbackFrameHandler {
             (self.rx_packet_processor.tx_fifo_level_min, self.rx_packet_processor.tx_fifo_level_max)
         )).unwrap();
 
+        let statistics = &self.rx_packet_processor.framer_cumulative_statistics;
+        let fifo_error_counts = (statistics.rx_fifo_underflow_count, statistics.tx_fifo_overflow_count);
+        if self.fifo_error_counts != Some(fifo_error_counts) {
+            if self.fifo_error_counts.is_some() {
+                self.processor.usb_slip();
+            }
+            let (rx_underflow_count, tx_overflow_count) = fifo_error_counts;
+            let event = FramerEvent::UsbFifoErrors { timestamp: Instant::now(), rx_underflow_count, tx_overflow_count };
+            if let Err(e) = self.processor.event_sender.send(event) {
+                eprintln!("LoopbackFrameHandler: event_sender.send(): {e:?}");
+            }
+        }
+        self.fifo_error_counts = Some(fifo_error_counts);
+
         while let Some(unprocessed_frame) = self.unprocessed_frames_consumer.pop() {
-            let processed_frame = self.processor.process_frame(&unprocessed_frame.frame);
+            let processed_frame = self.processor.process_frame(&unprocessed_frame);
             self.processed_frames_producer.push(InternalFrame {
                 frame: processed_frame,
                 frame_count: unprocessed_frame.frame_count,
                 mf_bits: unprocessed_frame.mf_bits,
+                f_bits: unprocessed_frame.f_bits,
             }).unwrap();
         }
     }
@@ -873,6 +1084,7 @@ impl LoopbackFrameHandler {
                 // Simple way to draw down the TX FIFO level if it's too high.
                 // We're dropping a frame here...
                 let _ = self.processed_frames_consumer.pop();
+                self.processor.usb_slip();
                 eprint!("D");
             }
         }
@@ -905,6 +1117,7 @@ impl LoopbackFrameHandler {
                     frame.frame = frame_out.frame;
                     frame.report.frame_count = frame_out.frame_count;
                 } else {
+                    self.processor.usb_slip();
                     eprint!("O");
                 }
             }
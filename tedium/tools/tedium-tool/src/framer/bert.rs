@@ -0,0 +1,176 @@
+use std::time::Duration;
+
+use super::device::{Channel, Result};
+use super::register::*;
+
+/// Patterns the framer's BERT can generate and check.
+///
+#[derive(Copy, Clone, Debug)]
+pub enum BertPatternSelect {
+    /// 2^15-1, from the original PRBS generator.
+    Prbs15,
+    /// Everything else comes from the enhanced generator (BCR).
+    Pattern(BERTPattern),
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct BertConfig {
+    pub pattern: BertPatternSelect,
+    pub error_insertion: BitErrorInsertionRate,
+    /// Fill all 193 bits of each frame, F-bit included.
+    pub unframed: bool,
+}
+
+impl BertConfig {
+    /// Bits per second the pattern occupies on the line.
+    pub fn bit_rate(&self) -> u32 {
+        if self.unframed { 1_544_000 } else { 1_536_000 }
+    }
+}
+
+/// BERT registers as found, so they can be put back afterward.
+///
+#[derive(Copy, Clone, Debug)]
+pub struct BertSavedState {
+    bertcsr0: BERTCSR0,
+    bertcsr1: BERTCSR1,
+    bcr: BCR,
+}
+
+pub fn bert_start(channel: &Channel, config: &BertConfig) -> Result<BertSavedState> {
+    let saved = BertSavedState {
+        bertcsr0: channel.bertcsr0().read()?,
+        bertcsr1: channel.bertcsr1().read()?,
+        bcr: channel.bcr().read()?,
+    };
+
+    let (switch, prbs_type) = match config.pattern {
+        BertPatternSelect::Prbs15 => (0, 0),
+        BertPatternSelect::Pattern(pattern) => {
+            channel.bcr().write(|w| w
+                .with_BERT(pattern)
+            )?;
+            (1, 0)
+        },
+    };
+
+    channel.bertcsr0().write(|w| w
+        .with_BERT_Switch(switch)
+        .with_BER(config.error_insertion)
+        .with_UnFramedBERT(config.unframed as u8)
+    )?;
+
+    channel.bertcsr1().modify(|m| m
+        .with_PRBSTyp(prbs_type)
+        .with_ERRORIns(0)
+        .with_DATAInv(0)
+        .with_RxBERTEnb(1)
+        .with_TxBERTEnb(1)
+        .with_RxBypass(0)
+        .with_TxBypass(0)
+    )?;
+
+    // Start from a clean slate; the counter resets when read.
+    bert_error_count(channel)?;
+
+    Ok(saved)
+}
+
+pub fn bert_stop(channel: &Channel, saved: &BertSavedState) -> Result<()> {
+    channel.bertcsr1().write(|_| saved.bertcsr1)?;
+    channel.bertcsr0().write(|_| saved.bertcsr0)?;
+    channel.bcr().write(|_| saved.bcr)
+}
+
+pub fn bert_locked(channel: &Channel) -> Result<bool> {
+    Ok(channel.bertcsr1().read()?.RxBERTLock() != 0)
+}
+
+/// Bit errors since the last call, from the PMON PRBS bit error counter.
+/// Anything else reading PMON (`monitor --performance`) takes them first.
+pub fn bert_error_count(channel: &Channel) -> Result<u16> {
+    let upper = channel.pbecu().read()?;
+    let lower = channel.pbecl().read()?;
+    Ok(u16::from_be_bytes([upper, lower]))
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum BertEvent {
+    SyncAcquired,
+    SyncLost,
+}
+
+/// Accumulates once-a-second BERT samples into a test result.
+///
+#[derive(Copy, Clone, Default, Debug)]
+pub struct BertStatistics {
+    pub seconds: u32,
+    pub bits: u64,
+    pub bit_errors: u64,
+    /// Seconds with bit errors, or without pattern sync.
+    pub errored_seconds: u32,
+    pub sync_losses: u32,
+    locked: bool,
+}
+
+impl BertStatistics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, locked: bool, bit_errors: u16, elapsed: Duration, bit_rate: u32) -> Option<BertEvent> {
+        let event = match (self.locked, locked) {
+            (false, true) => Some(BertEvent::SyncAcquired),
+            (true, false) => {
+                self.sync_losses += 1;
+                Some(BertEvent::SyncLost)
+            },
+            _ => None,
+        };
+        self.locked = locked;
+
+        self.seconds += 1;
+        // Without sync the error counter is meaningless, so don't count those bits.
+        if locked {
+            self.bits += (elapsed.as_secs_f64() * bit_rate as f64) as u64;
+            self.bit_errors += bit_errors as u64;
+        }
+        if !locked || bit_errors > 0 {
+            self.errored_seconds += 1;
+        }
+
+        event
+    }
+
+    pub fn bit_error_rate(&self) -> Option<f64> {
+        if self.bits > 0 {
+            Some(self.bit_errors as f64 / self.bits as f64)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statistics() {
+        let mut dut = BertStatistics::new();
+        let second = Duration::from_secs(1);
+
+        assert_eq!(dut.update(false, 0, second, 1_536_000), None);
+        assert_eq!(dut.update(true, 3, second, 1_536_000), Some(BertEvent::SyncAcquired));
+        assert_eq!(dut.update(true, 0, second, 1_536_000), None);
+        assert_eq!(dut.update(false, 500, second, 1_536_000), Some(BertEvent::SyncLost));
+        assert_eq!(dut.update(true, 0, second, 1_536_000), Some(BertEvent::SyncAcquired));
+
+        assert_eq!(dut.seconds, 5);
+        assert_eq!(dut.bits, 3 * 1_536_000);
+        assert_eq!(dut.bit_errors, 3);
+        assert_eq!(dut.errored_seconds, 3);
+        assert_eq!(dut.sync_losses, 1);
+        assert_eq!(dut.bit_error_rate(), Some(3.0 / (3.0 * 1_536_000.0)));
+    }
+}
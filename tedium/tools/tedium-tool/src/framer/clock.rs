@@ -0,0 +1,226 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use super::alarm::{Defects, SpanAlarms};
+use super::control::TimingReference;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SwitchReason {
+    /// The reference span lost signal, frame, or its recovered clock.
+    ReferenceFailed,
+    /// A candidate span has been clean long enough to time from.
+    CandidateQualified,
+}
+
+impl fmt::Display for SwitchReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ReferenceFailed => write!(f, "reference failed"),
+            Self::CandidateQualified => write!(f, "candidate qualified"),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ClockSwitch {
+    pub timestamp: Instant,
+    /// `None` for whatever the framer was timed from before we took over.
+    pub from: Option<TimingReference>,
+    pub to: TimingReference,
+    pub reason: SwitchReason,
+}
+
+/// Whether a span's recovered clock is fit to time from.
+///
+#[derive(Copy, Clone, Default)]
+struct ChannelHealth {
+    defects: Defects,
+    clock_lost: bool,
+    /// When the span last went bad or good. `None` until the framer reports on it.
+    since: Option<Instant>,
+}
+
+impl ChannelHealth {
+    fn is_defective(&self) -> bool {
+        self.defects.los || self.defects.oof || self.clock_lost
+    }
+
+    fn set(&mut self, defects: Defects, clock_lost: bool, now: Instant) {
+        let was_defective = self.is_defective();
+        self.defects = defects;
+        self.clock_lost = clock_lost;
+        if self.since.is_none() || self.is_defective() != was_defective {
+            self.since = Some(now);
+        }
+    }
+
+    /// Bad for at least `duration`.
+    fn failed(&self, now: Instant, duration: Duration) -> bool {
+        self.is_defective() && self.since.is_some_and(|t| now.saturating_duration_since(t) >= duration)
+    }
+
+    /// Good for at least `duration`.
+    fn qualified(&self, now: Instant, duration: Duration) -> bool {
+        !self.is_defective() && self.since.is_some_and(|t| now.saturating_duration_since(t) >= duration)
+    }
+}
+
+/// Picks which span the transmitters are timed from.
+///
+/// Candidates are in order of preference. The reference is abandoned once it
+/// has been defective for `FAILOVER`, for the most preferred candidate that
+/// has been clean for `QUALIFY`, or for free-run if none has. Switching is
+/// non-revertive: a better candidate coming back doesn't displace a working
+/// reference, but any qualified candidate ends free-run.
+///
+/// Until the first switch, the framer keeps whatever timing it already has,
+/// which is left alone until a candidate qualifies, or every candidate fails.
+///
+/// Defects only arrive when something changes, so `tick` must be called
+/// regularly to let the timers run out.
+///
+pub struct ClockFailover {
+    candidates: Vec<usize>,
+    channels: [ChannelHealth; 8],
+    reference: Option<TimingReference>,
+}
+
+impl ClockFailover {
+    /// Same as integrating a red alarm, so we switch when the span is declared down.
+    pub const FAILOVER: Duration = SpanAlarms::INTEGRATION;
+
+    /// Same as clearing an alarm, so a flapping span can't win the reference.
+    pub const QUALIFY: Duration = SpanAlarms::CLEARING_DEFAULT;
+
+    /// Starts with the framer's current timing, until a candidate qualifies or fails.
+    pub fn new(candidates: &[usize]) -> Self {
+        Self {
+            candidates: candidates.to_vec(),
+            channels: Default::default(),
+            reference: None,
+        }
+    }
+
+    pub fn defects(&mut self, channel_index: usize, defects: Defects, now: Instant) -> Option<ClockSwitch> {
+        let channel = &mut self.channels[channel_index];
+        channel.set(defects, channel.clock_lost, now);
+        self.tick(now)
+    }
+
+    pub fn recovered_clock(&mut self, channel_index: usize, lost: bool, now: Instant) -> Option<ClockSwitch> {
+        let channel = &mut self.channels[channel_index];
+        channel.set(channel.defects, lost, now);
+        self.tick(now)
+    }
+
+    pub fn tick(&mut self, now: Instant) -> Option<ClockSwitch> {
+        let reason = match self.reference {
+            Some(TimingReference::Channel(channel_index)) if self.channels[channel_index].failed(now, Self::FAILOVER) => SwitchReason::ReferenceFailed,
+            Some(TimingReference::Channel(_)) => return None,
+            Some(TimingReference::FreeRun) => SwitchReason::CandidateQualified,
+            None if self.candidates.iter().all(|&c| self.channels[c].failed(now, Self::FAILOVER)) => SwitchReason::ReferenceFailed,
+            None => SwitchReason::CandidateQualified,
+        };
+
+        let qualified = self.candidates.iter()
+            .copied()
+            .filter(|&c| self.reference != Some(TimingReference::Channel(c)))
+            .find(|&c| self.channels[c].qualified(now, Self::QUALIFY));
+        let to = match (qualified, reason) {
+            (Some(channel_index), _) => TimingReference::Channel(channel_index),
+            (None, SwitchReason::ReferenceFailed) => TimingReference::FreeRun,
+            (None, SwitchReason::CandidateQualified) => return None,
+        };
+        if Some(to) == self.reference {
+            return None;
+        }
+
+        let switch = ClockSwitch { timestamp: now, from: self.reference, to, reason };
+        self.reference = Some(to);
+        Some(switch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOS: Defects = Defects { los: true, oof: false, ais: false, rai: false };
+    const CLEAN: Defects = Defects { los: false, oof: false, ais: false, rai: false };
+
+    fn to(switch: Option<ClockSwitch>) -> Option<(TimingReference, SwitchReason)> {
+        switch.map(|s| (s.to, s.reason))
+    }
+
+    #[test]
+    fn failover() {
+        let t0 = Instant::now();
+        let s = |ms: u64| t0 + Duration::from_millis(ms);
+
+        let mut dut = ClockFailover::new(&[0, 1, 2]);
+        assert_eq!(dut.reference, None);
+        assert_eq!(dut.defects(0, CLEAN, t0), None);
+        assert_eq!(dut.defects(1, CLEAN, t0), None);
+        assert_eq!(dut.defects(2, LOS, t0), None);
+
+        // Nothing has been clean long enough yet, and channel 2 failing alone
+        // is no reason to leave the framer's own timing.
+        assert_eq!(dut.tick(s(9999)), None);
+        assert_eq!(dut.reference, None);
+        let switch = dut.tick(s(10000));
+        assert_eq!(switch.map(|s| s.from), Some(None));
+        assert_eq!(to(switch), Some((TimingReference::Channel(0), SwitchReason::CandidateQualified)));
+
+        // A brief loss of the recovered clock rides through...
+        assert_eq!(dut.recovered_clock(0, true, s(20000)), None);
+        assert_eq!(dut.recovered_clock(0, false, s(22000)), None);
+        assert_eq!(dut.tick(s(30000)), None);
+
+        // ...a longer one doesn't.
+        assert_eq!(dut.defects(0, LOS, s(40000)), None);
+        assert_eq!(to(dut.tick(s(42500))), Some((TimingReference::Channel(1), SwitchReason::ReferenceFailed)));
+
+        // Non-revertive: channel 0 coming back doesn't take over.
+        assert_eq!(dut.defects(0, CLEAN, s(43000)), None);
+        assert_eq!(dut.tick(s(60000)), None);
+
+        // Channel 2 never qualified, so channel 0 is next.
+        assert_eq!(dut.defects(1, LOS, s(70000)), None);
+        let switch = dut.tick(s(72500)).unwrap();
+        assert_eq!((switch.timestamp, switch.from, switch.to), (s(72500), Some(TimingReference::Channel(1)), TimingReference::Channel(0)));
+        assert_eq!(dut.reference, Some(TimingReference::Channel(0)));
+    }
+
+    #[test]
+    fn free_run() {
+        let t0 = Instant::now();
+        let s = |ms: u64| t0 + Duration::from_millis(ms);
+
+        let mut dut = ClockFailover::new(&[3, 4]);
+        dut.defects(3, CLEAN, t0);
+        assert_eq!(to(dut.tick(s(10000))), Some((TimingReference::Channel(3), SwitchReason::CandidateQualified)));
+
+        // Channel 4 has never reported, so there's nowhere to go.
+        dut.recovered_clock(3, true, s(11000));
+        assert_eq!(to(dut.tick(s(13500))), Some((TimingReference::FreeRun, SwitchReason::ReferenceFailed)));
+
+        // Spans that aren't candidates don't count.
+        dut.defects(5, CLEAN, s(13500));
+        dut.defects(4, CLEAN, s(14000));
+        assert_eq!(dut.tick(s(23999)), None);
+        assert_eq!(to(dut.tick(s(24000))), Some((TimingReference::Channel(4), SwitchReason::CandidateQualified)));
+    }
+
+    #[test]
+    fn all_candidates_failed() {
+        let t0 = Instant::now();
+        let s = |ms: u64| t0 + Duration::from_millis(ms);
+
+        let mut dut = ClockFailover::new(&[0, 1]);
+        dut.defects(0, LOS, t0);
+        assert_eq!(dut.tick(s(2500)), None);
+        dut.defects(1, LOS, s(1000));
+        assert_eq!(dut.tick(s(3499)), None);
+        assert_eq!(to(dut.tick(s(3500))), Some((TimingReference::FreeRun, SwitchReason::ReferenceFailed)));
+    }
+}
@@ -0,0 +1,399 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use modular_bitfield_msb::Specifier;
+use rusb::constants::{LIBUSB_ENDPOINT_IN, LIBUSB_ENDPOINT_OUT};
+use thiserror::Error;
+
+use super::device::open_device;
+use super::register::{ClockSource, T1Framing};
+use super::usb::{EndpointNumber, InterfaceNumber, DESCRIPTOR_VERSION, FRAMER_CONTROL_BYTES_MAX};
+
+// Register read (0x00) and write (0x01) aren't used: see `FramerControl`.
+// TODO: Keep synchronized with `HostRequestCommand` in `tedium-soc`.
+#[derive(Copy, Clone, Debug)]
+#[repr(u8)]
+enum HostRequestCommand {
+    HdlcTransmit = 0x02,
+    GetInfo = 0x03,
+    Resync = 0x04,
+    InterruptEnable = 0x05,
+    ChannelEnable = 0x06,
+    TimingReference = 0x07,
+}
+
+/// Failures the firmware reports in a response's status byte, plus the ways
+/// a response can fail to make sense.
+///
+// TODO: Keep synchronized with `HostRequestError` in `tedium-soc`.
+#[derive(Error, Debug)]
+pub enum FramerControlError {
+    #[error(transparent)]
+    Usb(#[from] rusb::Error),
+    #[error("firmware: request too short for its command")]
+    ShortPacket,
+    #[error("firmware: request too long for its command")]
+    LongPacket,
+    #[error("firmware: invalid command or arguments")]
+    InvalidCommand,
+    #[error("firmware: framer register access failed")]
+    RegisterAccess,
+    #[error("firmware: unknown status {0:#04x}")]
+    Status(u8),
+    #[error("response of {0} bytes is too short")]
+    ShortResponse(usize),
+    #[error("firmware doesn't support command {0:#04x}")]
+    Unsupported(u8),
+    #[error("incompatible firmware: {0}")]
+    Incompatible(String),
+}
+
+impl FramerControlError {
+    fn from_status(status: u8) -> Option<Self> {
+        match status {
+            0 => None,
+            2 => Some(Self::ShortPacket),
+            3 => Some(Self::LongPacket),
+            4 => Some(Self::InvalidCommand),
+            5 => Some(Self::RegisterAccess),
+            n => Some(Self::Status(n)),
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, FramerControlError>;
+
+/// Largest frame the firmware will accept: one LAPD buffer. The framer appends the FCS.
+pub const HDLC_TRANSMIT_BYTES_MAX: usize = 96;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum HdlcTransmitStatus {
+    /// The frame was handed to the HDLC controller.
+    Queued,
+    /// The controller hasn't finished the previous frame. Try again after TxEOT.
+    Busy,
+}
+
+/// Interrupt enable registers the firmware will set for us.
+///
+// TODO: Keep synchronized with `INTERRUPT_ENABLE_OFFSETS` in `tedium-soc`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum InterruptEnable {
+    Bier,
+    Fier,
+    Aeier,
+    /// HDLC controller 0-2.
+    Dlier(usize),
+    Sbier,
+    /// Receive loopback code detector 0-7.
+    Rlcier(usize),
+}
+
+impl InterruptEnable {
+    pub const ALL: [Self; 15] = [
+        Self::Bier, Self::Fier, Self::Aeier,
+        Self::Dlier(0), Self::Dlier(1), Self::Dlier(2),
+        Self::Sbier,
+        Self::Rlcier(0), Self::Rlcier(1), Self::Rlcier(2), Self::Rlcier(3),
+        Self::Rlcier(4), Self::Rlcier(5), Self::Rlcier(6), Self::Rlcier(7),
+    ];
+
+    fn source(&self) -> u8 {
+        match *self {
+            Self::Bier      => 0,
+            Self::Fier      => 1,
+            Self::Aeier     => 2,
+            Self::Dlier(n)  => 3 + n as u8,
+            Self::Sbier     => 6,
+            Self::Rlcier(n) => 7 + n as u8,
+        }
+    }
+
+    /// The register's name in the register map (`dlier1` for HDLC controller 0).
+    pub fn name(&self) -> String {
+        match *self {
+            Self::Bier      => "bier".into(),
+            Self::Fier      => "fier".into(),
+            Self::Aeier     => "aeier".into(),
+            Self::Dlier(n)  => format!("dlier{}", n + 1),
+            Self::Sbier     => "sbier".into(),
+            Self::Rlcier(n) => format!("rlcier{n}"),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|e| e.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// Where the transmitters get their timing.
+///
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum TimingReference {
+    /// Loop-time to a span's recovered clock.
+    Channel(usize),
+    /// Run from the MCLK PLL.
+    FreeRun,
+}
+
+impl TimingReference {
+    // TODO: Keep synchronized with `TIMING_REFERENCE_FREE_RUN` in `tedium-soc`.
+    const FREE_RUN: u8 = 0xff;
+
+    fn to_byte(self) -> u8 {
+        match self {
+            Self::Channel(channel) => channel as u8,
+            Self::FreeRun => Self::FREE_RUN,
+        }
+    }
+}
+
+impl std::fmt::Display for TimingReference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Channel(channel) => write!(f, "channel {channel}"),
+            Self::FreeRun => write!(f, "free-run"),
+        }
+    }
+}
+
+/// What the firmware says about itself, from `GetInfo`.
+///
+// TODO: Keep synchronized with `info()` in `tedium-soc`.
+#[derive(Clone, Debug)]
+pub struct FirmwareInfo {
+    pub version: (u8, u8, u8),
+    pub git_hash: String,
+    /// USB descriptor version (bcdDevice) the firmware was built against.
+    pub descriptor_version: rusb::Version,
+    /// Mask of `HostRequestCommand` codes.
+    commands: u32,
+    pub channels: usize,
+    /// How the firmware configures every channel at start-up.
+    pub framing: Option<T1Framing>,
+    pub clock_source: Option<ClockSource>,
+}
+
+impl FirmwareInfo {
+    const BYTES: usize = 28;
+
+    fn from_bytes(info: &[u8]) -> Result<Self> {
+        if info.len() < Self::BYTES {
+            return Err(FramerControlError::ShortResponse(info.len()));
+        }
+        Ok(Self {
+            version: (info[0], info[1], info[2]),
+            git_hash: info[3..19].iter().take_while(|&&c| c != 0).map(|&c| c as char).collect(),
+            descriptor_version: rusb::Version::from_bcd(u16::from_le_bytes([info[19], info[20]])),
+            commands: u32::from_le_bytes([info[21], info[22], info[23], info[24]]),
+            channels: info[25] as usize,
+            framing: T1Framing::from_bytes(info[26]).ok(),
+            clock_source: ClockSource::from_bytes(info[27]).ok(),
+        })
+    }
+
+    fn supports(&self, command: HostRequestCommand) -> bool {
+        self.commands & (1 << command as u8) != 0
+    }
+}
+
+/// Commands handled by the SoC firmware, over the framer control bulk endpoints.
+///
+/// Register access goes through vendor control requests on `Device`, which the
+/// gateware services directly. Anything that needs the firmware comes through here.
+///
+/// `open` asks the firmware what it is, and refuses to go on if it doesn't
+/// agree with the gateware and this tool about the USB interface.
+///
+/// Only one handle can claim the interface, so threads share one. Requests
+/// are serialized, so one thread can't take another's response.
+///
+pub struct FramerControl {
+    handle: rusb::DeviceHandle<rusb::Context>,
+    timeout: Duration,
+    sequence: Mutex<u8>,
+    info: Option<FirmwareInfo>,
+}
+
+impl FramerControl {
+    pub fn open(context: &mut rusb::Context) -> Result<Self> {
+        let handle = open_device(context)?;
+
+        handle.claim_interface(InterfaceNumber::FramerControl as u8)?;
+        handle.set_alternate_setting(InterfaceNumber::FramerControl as u8, 0)?;
+
+        let mut control = Self {
+            handle,
+            timeout: Duration::from_secs(1),
+            sequence: Mutex::new(0),
+            info: None,
+        };
+
+        let info = control.info()?;
+        let gateware_version = control.handle.device().device_descriptor()?.device_version();
+        let expected_version = rusb::Version::from_bcd(DESCRIPTOR_VERSION);
+        if gateware_version != expected_version {
+            return Err(FramerControlError::Incompatible(format!("gateware descriptors are version {gateware_version}, expected {expected_version}")));
+        }
+        if info.descriptor_version != gateware_version {
+            return Err(FramerControlError::Incompatible(format!("firmware built for descriptors version {}, gateware has {gateware_version}", info.descriptor_version)));
+        }
+        if info.channels != 8 {
+            return Err(FramerControlError::Incompatible(format!("firmware has {} channels", info.channels)));
+        }
+        control.info = Some(info);
+
+        Ok(control)
+    }
+
+    /// Ask the firmware what it is.
+    pub fn info(&self) -> Result<FirmwareInfo> {
+        FirmwareInfo::from_bytes(&self.execute(HostRequestCommand::GetInfo, &[])?)
+    }
+
+    /// Send `command` and its arguments, and return the response payload.
+    fn execute(&self, command: HostRequestCommand, arguments: &[u8]) -> Result<Vec<u8>> {
+        let endpoint_out = LIBUSB_ENDPOINT_OUT | EndpointNumber::FramerControl as u8;
+        let endpoint_in = LIBUSB_ENDPOINT_IN | EndpointNumber::FramerControl as u8;
+
+        if let Some(info) = &self.info {
+            if !info.supports(command) {
+                return Err(FramerControlError::Unsupported(command as u8));
+            }
+        }
+
+        let mut last_sequence = self.sequence.lock().unwrap();
+        let sequence = last_sequence.wrapping_add(1);
+        *last_sequence = sequence;
+
+        let mut request = vec![command as u8, sequence];
+        request.extend_from_slice(arguments);
+        self.handle.write_bulk(endpoint_out, &request, self.timeout)?;
+
+        let mut response = [0u8; FRAMER_CONTROL_BYTES_MAX];
+        loop {
+            let length = self.handle.read_bulk(endpoint_in, &mut response, self.timeout)?;
+            let payload = parse_response(&response[0..length], sequence)?;
+            if let Some(payload) = payload {
+                return Ok(payload.to_vec());
+            }
+            // A late response to a request that already timed out; ours is next.
+        }
+    }
+
+    /// Have the firmware report every channel's interrupt status as if all
+    /// of it had just changed, so the host can rebuild its view of the framer.
+    pub fn resync(&self) -> Result<()> {
+        self.execute(HostRequestCommand::Resync, &[])?;
+        Ok(())
+    }
+
+    /// Set one of a channel's interrupt enable registers. The firmware keeps
+    /// BIER HDLC and DLIER TxEOT set, as it paces `hdlc_transmit` with them.
+    pub fn interrupt_enable(&self, channel: usize, register: InterruptEnable, value: u8) -> Result<()> {
+        if channel >= 8 {
+            return Err(rusb::Error::InvalidParam.into());
+        }
+        self.execute(HostRequestCommand::InterruptEnable, &[channel as u8, register.source(), value])?;
+        Ok(())
+    }
+
+    /// Have the firmware stop (or resume) polling a channel for interrupts.
+    pub fn channel_enable(&self, channel: usize, enable: bool) -> Result<()> {
+        if channel >= 8 {
+            return Err(rusb::Error::InvalidParam.into());
+        }
+        self.execute(HostRequestCommand::ChannelEnable, &[channel as u8, enable as u8])?;
+        Ok(())
+    }
+
+    /// Choose the span the transmitters are timed from.
+    pub fn timing_reference(&self, reference: TimingReference) -> Result<()> {
+        if matches!(reference, TimingReference::Channel(channel) if channel >= 8) {
+            return Err(rusb::Error::InvalidParam.into());
+        }
+        self.execute(HostRequestCommand::TimingReference, &[reference.to_byte()])?;
+        Ok(())
+    }
+
+    /// Hand a frame (without FCS) to one of a channel's three HDLC controllers.
+    pub fn hdlc_transmit(&self, channel: usize, hdlc_index: usize, frame: &[u8]) -> Result<HdlcTransmitStatus> {
+        if channel >= 8 || hdlc_index >= 3 || frame.is_empty() || frame.len() > HDLC_TRANSMIT_BYTES_MAX {
+            return Err(rusb::Error::InvalidParam.into());
+        }
+
+        let mut arguments = vec![channel as u8, hdlc_index as u8, frame.len() as u8];
+        arguments.extend_from_slice(frame);
+
+        match self.execute(HostRequestCommand::HdlcTransmit, &arguments)?[..] {
+            [0] => Ok(HdlcTransmitStatus::Queued),
+            [_] => Ok(HdlcTransmitStatus::Busy),
+            ref payload => Err(FramerControlError::ShortResponse(payload.len())),
+        }
+    }
+}
+
+/// Check a response's status and sequence number, and return its payload.
+/// `None` if it answers some other request.
+fn parse_response(response: &[u8], sequence: u8) -> Result<Option<&[u8]>> {
+    match response {
+        [status, response_sequence, payload @ ..] => {
+            if *response_sequence != sequence {
+                return Ok(None);
+            }
+            match FramerControlError::from_status(*status) {
+                Some(e) => Err(e),
+                None => Ok(Some(payload)),
+            }
+        },
+        _ => Err(FramerControlError::ShortResponse(response.len())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn responses() {
+        assert_eq!(parse_response(&[0x00, 0x07, 0x42], 0x07).unwrap(), Some(&[0x42][..]));
+        assert_eq!(parse_response(&[0x00, 0x06, 0x42], 0x07).unwrap(), None);
+        assert!(matches!(parse_response(&[0x04, 0x07], 0x07), Err(FramerControlError::InvalidCommand)));
+        assert!(matches!(parse_response(&[0x05, 0x07], 0x07), Err(FramerControlError::RegisterAccess)));
+        assert!(matches!(parse_response(&[0x99, 0x07], 0x07), Err(FramerControlError::Status(0x99))));
+        assert!(matches!(parse_response(&[0x00], 0x07), Err(FramerControlError::ShortResponse(1))));
+    }
+
+    #[test]
+    fn firmware_info() {
+        let info = FirmwareInfo::from_bytes(&[
+            0, 1, 2,
+            b'0', b'1', b'2', b'3', b'a', b'b', b'c', b'd', b'-', b'd', b'i', b'r', b't', b'y', 0, 0,
+            0x01, 0x01,
+            0x0f, 0x00, 0x00, 0x00,
+            8,
+            0b000,
+            0b01,
+        ]).unwrap();
+        assert_eq!(info.version, (0, 1, 2));
+        assert_eq!(info.git_hash, "0123abcd-dirty");
+        assert_eq!(info.descriptor_version, rusb::Version(1, 0, 1));
+        assert!(info.supports(HostRequestCommand::GetInfo));
+        assert_eq!(info.channels, 8);
+        assert!(matches!(info.framing, Some(T1Framing::ExtendedSuperFrame)));
+        assert!(matches!(info.clock_source, Some(ClockSource::External)));
+
+        assert!(matches!(FirmwareInfo::from_bytes(&[0; 27]), Err(FramerControlError::ShortResponse(27))));
+    }
+
+    #[test]
+    fn interrupt_enable_names() {
+        for (source, register) in InterruptEnable::ALL.iter().enumerate() {
+            assert_eq!(register.source() as usize, source);
+            assert_eq!(InterruptEnable::from_name(&register.name()), Some(*register));
+            assert!(crate::framer::map::Register::by_name(&register.name()).is_some());
+        }
+        assert_eq!(InterruptEnable::from_name("DLIER3"), Some(InterruptEnable::Dlier(2)));
+        assert_eq!(InterruptEnable::from_name("rlcier"), None);
+    }
+}
@@ -0,0 +1,169 @@
+/// ESF CRC-6 (x^6 + x + 1), per ANSI T1.403.
+///
+/// Computed over all 4,632 bits of an extended superframe, with the 24
+/// F-bits replaced by ones. The result is transmitted in the C1-C6 bits
+/// of the *following* superframe, C1 being the most significant.
+///
+#[derive(Copy, Clone, Debug)]
+pub struct Crc6 {
+    remainder: u8,
+}
+
+impl Crc6 {
+    const POLYNOMIAL: u8 = 0b000011;
+
+    pub fn new() -> Self {
+        Self {
+            remainder: 0,
+        }
+    }
+
+    pub fn update_bit(&mut self, bit: bool) {
+        let feedback = ((self.remainder >> 5) & 1 != 0) ^ bit;
+        self.remainder = (self.remainder << 1) & 0x3f;
+        if feedback {
+            self.remainder ^= Self::POLYNOMIAL;
+        }
+    }
+
+    pub fn update_byte(&mut self, byte: u8) {
+        for n in (0..8).rev() {
+            self.update_bit((byte >> n) & 1 != 0);
+        }
+    }
+
+    pub fn value(&self) -> u8 {
+        self.remainder
+    }
+}
+
+/// Host-side CRC-6 error counters for one channel.
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Crc6Statistics {
+    pub superframes: u32,
+    pub crc_errors: u32,
+    pub errored_seconds: u32,
+}
+
+/// Compares each superframe's received CRC bits against the CRC computed over
+/// the superframe before it, and accumulates errored seconds.
+///
+#[derive(Copy, Clone, Debug)]
+pub struct Crc6Monitor {
+    previous: Option<(u32, u8)>,
+    second: Option<u32>,
+    second_errored: bool,
+    statistics: Crc6Statistics,
+}
+
+impl Crc6Monitor {
+    const FRAMES_PER_SUPERFRAME: u32 = 24;
+    const FRAMES_PER_SECOND: u32 = 8000;
+
+    pub fn new() -> Self {
+        Self {
+            previous: None,
+            second: None,
+            second_errored: false,
+            statistics: Crc6Statistics::default(),
+        }
+    }
+
+    /// Account for a complete superframe, starting at `frame_count`.
+    /// Returns the statistics at the end of each second.
+    pub fn superframe(&mut self, frame_count: u32, computed_crc: u8, received_crc: u8) -> Option<Crc6Statistics> {
+        let second = frame_count / Self::FRAMES_PER_SECOND;
+        let result = match self.second {
+            Some(current) if current != second => {
+                if self.second_errored {
+                    self.statistics.errored_seconds += 1;
+                }
+                Some(self.statistics)
+            },
+            _ => None,
+        };
+        if self.second != Some(second) {
+            self.second = Some(second);
+            self.second_errored = false;
+        }
+
+        // Only check against the previous superframe if there was no gap between
+        // the two, otherwise we're comparing against the wrong CRC.
+        if let Some((previous_frame_count, previous_crc)) = self.previous {
+            if frame_count.wrapping_sub(previous_frame_count) == Self::FRAMES_PER_SUPERFRAME {
+                self.statistics.superframes += 1;
+                if received_crc != previous_crc {
+                    self.statistics.crc_errors += 1;
+                    self.second_errored = true;
+                }
+            }
+        }
+        self.previous = Some((frame_count, computed_crc));
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remainder_of_codeword_is_zero() {
+        let message = [0x12u8, 0x34, 0x56, 0x78, 0x9a];
+
+        let mut crc = Crc6::new();
+        for &b in &message {
+            crc.update_byte(b);
+        }
+        let value = crc.value();
+
+        // Appending the CRC to the message yields a zero remainder.
+        for n in (0..6).rev() {
+            crc.update_bit((value >> n) & 1 != 0);
+        }
+        assert_eq!(crc.value(), 0);
+    }
+
+    #[test]
+    fn single_bit_errors_detected() {
+        let message = [0xffu8; 24];
+
+        let mut reference = Crc6::new();
+        for &b in &message {
+            reference.update_byte(b);
+        }
+
+        for bit in 0..message.len() * 8 {
+            let mut corrupted = message;
+            corrupted[bit / 8] ^= 0x80 >> (bit % 8);
+
+            let mut crc = Crc6::new();
+            for &b in &corrupted {
+                crc.update_byte(b);
+            }
+            assert_ne!(crc.value(), reference.value());
+        }
+    }
+
+    #[test]
+    fn monitor_counts_errors_and_errored_seconds() {
+        let mut dut = Crc6Monitor::new();
+
+        // First superframe has nothing to compare against.
+        assert_eq!(dut.superframe(0, 0x15, 0x00), None);
+        assert_eq!(dut.superframe(24, 0x2a, 0x15), None);
+        assert_eq!(dut.superframe(48, 0x00, 0x3f), None);
+
+        // Gap in superframes, CRC is not checked.
+        assert_eq!(dut.superframe(96, 0x11, 0x3f), None);
+
+        // Crossing into the next second reports the previous second.
+        let statistics = dut.superframe(8016, 0x00, 0x00).unwrap();
+        assert_eq!(statistics, Crc6Statistics { superframes: 2, crc_errors: 1, errored_seconds: 1 });
+
+        let statistics = dut.superframe(16008, 0x00, 0x00).unwrap();
+        assert_eq!(statistics, Crc6Statistics { superframes: 2, crc_errors: 1, errored_seconds: 1 });
+    }
+}
@@ -129,7 +129,7 @@ pub struct Timeslots<'a> {
 }
 
 impl<'a> Timeslots<'a> {
-    const COUNT: usize = 24;
+    pub(crate) const COUNT: usize = 24;
 
     fn new(device: &'a Device, channel: usize) -> Self {
         Self {
@@ -229,8 +229,8 @@ impl<'a> Channel<'a> {
     pub fn rscr1   (&self) -> Access<RSChR>    { self.rscr(1) }
     pub fn rscr2   (&self) -> Access<RSChR>    { self.rscr(2) }
     pub fn rifr    (&self) -> Access<RIFR>     { self.access_framer(0x112) }
-    pub fn dlcr1   (&self) -> Access<DLCR>     { self.access_framer(0x113) }
-    pub fn tdlbcr1 (&self) -> Access<TDLBCR>   { self.access_framer(0x114) }
+    pub fn dlcr1   (&self) -> Access<DLCR>     { self.dlcr(0) }
+    pub fn tdlbcr1 (&self) -> Access<TDLBCR>   { self.tdlbcr(0) }
     pub fn rdlbcr1 (&self) -> Access<RDLBCR>   { self.rdlbcr(0) }
     pub fn sbcr    (&self) -> Access<SBCR>     { self.access_framer(0x116) }
     pub fn fifolr  (&self) -> Access<FIFOLR>   { self.access_framer(0x117) }
@@ -258,8 +258,8 @@ impl<'a> Channel<'a> {
     pub fn tlcgs   (&self) -> Access<TLCGS>    { self.access_framer(0x140) }
     pub fn lcts    (&self) -> Access<LCTS>     { self.access_framer(0x141) }
     pub fn tsprmcr (&self) -> Access<TSPRMCR>  { self.access_framer(0x142) }
-    pub fn dlcr2   (&self) -> Access<DLCR>     { self.access_framer(0x143) }
-    pub fn tdlbcr2 (&self) -> Access<TDLBCR>   { self.access_framer(0x144) }
+    pub fn dlcr2   (&self) -> Access<DLCR>     { self.dlcr(1) }
+    pub fn tdlbcr2 (&self) -> Access<TDLBCR>   { self.tdlbcr(1) }
     pub fn rdlbcr2 (&self) -> Access<RDLBCR>   { self.rdlbcr(1) }
     pub fn lccr3   (&self) -> Access<LCCR>     { self.access_framer(0x146) }
     pub fn rlacr3  (&self) -> Access<RLACR>    { self.access_framer(0x147) }
@@ -273,8 +273,8 @@ impl<'a> Channel<'a> {
     pub fn lccr6   (&self) -> Access<LCCR>     { self.access_framer(0x14f) }
     pub fn rlacr6  (&self) -> Access<RLACR>    { self.access_framer(0x150) }
     pub fn rldcr6  (&self) -> Access<RLDCR>    { self.access_framer(0x151) }
-    pub fn dlcr3   (&self) -> Access<DLCR>     { self.access_framer(0x153) }
-    pub fn tdlbcr3 (&self) -> Access<TDLBCR>   { self.access_framer(0x154) }
+    pub fn dlcr3   (&self) -> Access<DLCR>     { self.dlcr(2) }
+    pub fn tdlbcr3 (&self) -> Access<TDLBCR>   { self.tdlbcr(2) }
     pub fn rdlbcr3 (&self) -> Access<RDLBCR>   { self.rdlbcr(2) }
     pub fn lccr7   (&self) -> Access<LCCR>     { self.access_framer(0x156) }
     pub fn rlacr7  (&self) -> Access<RLACR>    { self.access_framer(0x157) }
@@ -309,6 +309,18 @@ impl<'a> Channel<'a> {
         self.access_framer(MAP[index])
     }
 
+    pub fn dlcr(&self, index: usize) -> Access<DLCR> {
+        const MAP: [usize; 3] = [0x113, 0x143, 0x153];
+        assert!(index < MAP.len());
+        self.access_framer(MAP[index])
+    }
+
+    pub fn tdlbcr(&self, index: usize) -> Access<TDLBCR> {
+        const MAP: [usize; 3] = [0x114, 0x144, 0x154];
+        assert!(index < MAP.len());
+        self.access_framer(MAP[index])
+    }
+
     // Receive Signaling Array Registers
 
     pub fn rsar(&self, index: usize) -> Access<RSAR> {
@@ -358,7 +370,7 @@ impl<'a> Channel<'a> {
     pub fn fisr    (&self) -> Access<FISR>     { self.access_framer(0xb04) }
     pub fn fier    (&self) -> Access<FIER>     { self.access_framer(0xb05) }
     pub fn dlsr1   (&self) -> Access<DLSRx>    { self.dlsr(0) }
-    pub fn dlier1  (&self) -> Access<DLIERx>   { self.access_framer(0xb07) }
+    pub fn dlier1  (&self) -> Access<DLIERx>   { self.dlier(0) }
     pub fn sbisr   (&self) -> Access<SBISR>    { self.access_framer(0xb08) }
     pub fn sbier   (&self) -> Access<SBIER>    { self.access_framer(0xb09) }
     pub fn rlcisr0 (&self) -> Access<RLCISRx>  { self.access_framer(0xb0a) }
@@ -366,15 +378,15 @@ impl<'a> Channel<'a> {
     pub fn exzsr   (&self) -> Access<EXZSR>    { self.access_framer(0xb0e) }
     pub fn exzer   (&self) -> Access<EXZER>    { self.access_framer(0xb0f) }
     pub fn ss7sr1  (&self) -> Access<SS7SRx>   { self.ss7sr(0) }
-    pub fn ss7er1  (&self) -> Access<SS7ERx>   { self.access_framer(0xb11) }
+    pub fn ss7er1  (&self) -> Access<SS7ERx>   { self.ss7er(0) }
     pub fn rlcisr  (&self) -> Access<RLCISR>   { self.access_framer(0xb12) }
     pub fn rlcier  (&self) -> Access<RLCIER>   { self.access_framer(0xb13) }
     pub fn rlcisr1 (&self) -> Access<RLCISRx>  { self.access_framer(0xb14) }
     pub fn rlcier1 (&self) -> Access<RLCIERx>  { self.access_framer(0xb15) }
     pub fn dlsr2   (&self) -> Access<DLSRx>    { self.dlsr(1) }
-    pub fn dlier2  (&self) -> Access<DLIERx>   { self.access_framer(0xb17) }
+    pub fn dlier2  (&self) -> Access<DLIERx>   { self.dlier(1) }
     pub fn ss7sr2  (&self) -> Access<SS7SRx>   { self.ss7sr(1) }
-    pub fn ss7er2  (&self) -> Access<SS7ERx>   { self.access_framer(0xb19) }
+    pub fn ss7er2  (&self) -> Access<SS7ERx>   { self.ss7er(1) }
     pub fn rlcisr2 (&self) -> Access<RLCISRx>  { self.access_framer(0xb1a) }
     pub fn rlcier2 (&self) -> Access<RLCIERx>  { self.access_framer(0xb1b) }
     pub fn rlcisr3 (&self) -> Access<RLCISRx>  { self.access_framer(0xb1c) }
@@ -388,9 +400,9 @@ impl<'a> Channel<'a> {
     pub fn rlcisr7 (&self) -> Access<RLCISRx>  { self.access_framer(0xb24) }
     pub fn rlcier7 (&self) -> Access<RLCIERx>  { self.access_framer(0xb25) }
     pub fn dlsr3   (&self) -> Access<DLSRx>    { self.dlsr(2) }
-    pub fn dlier3  (&self) -> Access<DLIERx>   { self.access_framer(0xb27) }
+    pub fn dlier3  (&self) -> Access<DLIERx>   { self.dlier(2) }
     pub fn ss7sr3  (&self) -> Access<SS7SRx>   { self.ss7sr(2) }
-    pub fn ss7er3  (&self) -> Access<SS7ERx>   { self.access_framer(0xb29) }
+    pub fn ss7er3  (&self) -> Access<SS7ERx>   { self.ss7er(2) }
     pub fn ciasr   (&self) -> Access<CIASR>    { self.access_framer(0xb40) }
     pub fn ciaier  (&self) -> Access<CIAIER>   { self.access_framer(0xb41) }
     pub fn bocisr  (&self) -> Access<BOCISR>   { self.access_framer(0xb70) }
@@ -404,12 +416,24 @@ impl<'a> Channel<'a> {
         self.access_framer(MAP[index])
     }
 
+    pub fn dlier(&self, index: usize) -> Access<DLIERx> {
+        const MAP: [usize; 3] = [0xb07, 0xb17, 0xb27];
+        assert!(index < MAP.len());
+        self.access_framer(MAP[index])
+    }
+
     pub fn ss7sr(&self, index: usize) -> Access<SS7SRx> {
         const MAP: [usize; 3] = [0xb10, 0xb18, 0xb28];
         assert!(index < MAP.len());
         self.access_framer(MAP[index])
     }
 
+    pub fn ss7er(&self, index: usize) -> Access<SS7ERx> {
+        const MAP: [usize; 3] = [0xb11, 0xb19, 0xb29];
+        assert!(index < MAP.len());
+        self.access_framer(MAP[index])
+    }
+
     // LIU
 
     pub fn liuccr0 (&self) -> Access<LIUCCR0>  { self.access_liu(0x0) }
@@ -436,7 +460,7 @@ pub struct Channels<'a> {
 }
 
 impl<'a> Channels<'a> {
-    const COUNT: usize = 8;
+    pub(crate) const COUNT: usize = 8;
 
     fn new(device: &'a Device) -> Self {
         Self {
@@ -574,23 +598,23 @@ impl Device {
 
 /// XRT86VX38 "uP" interface memory map addressing abstraction
 /// 
-struct Addressing {}
+pub(crate) struct Addressing {}
 
 impl Addressing {
-    fn global(offset: usize) -> usize {
+    pub(crate) fn global(offset: usize) -> usize {
         assert!(offset < 0x10000);
         offset
     }
 
     /// Registers of the form 0xNxxx + offset
-    fn channel_nxxx(channel: usize, offset: usize) -> usize {
+    pub(crate) fn channel_nxxx(channel: usize, offset: usize) -> usize {
         assert!(channel < Channels::COUNT);
         assert!(offset < 0x1000);
         Self::global((channel << 12) | offset)
     }
 
     /// Registers of the form 0xNxxx where offset points to a series of timeslot registers.
-    fn channel_nxxx_timeslot(channel: usize, block_offset: usize, timeslot: usize) -> usize {
+    pub(crate) fn channel_nxxx_timeslot(channel: usize, block_offset: usize, timeslot: usize) -> usize {
         assert!(block_offset & 0xf == 0);
         assert!(timeslot < Timeslots::COUNT);
         let offset = block_offset + timeslot;
@@ -598,7 +622,7 @@ impl Addressing {
     }
 
     /// Registers of the form 0x0fNx
-    fn channel_0fnx(channel: usize, offset: usize) -> usize {
+    pub(crate) fn channel_0fnx(channel: usize, offset: usize) -> usize {
         assert!(channel < Channels::COUNT);
         assert!(offset < 16);
         let address = 0x0f00 | (channel << 4) | offset;
@@ -606,7 +630,7 @@ impl Addressing {
     }
 
     /// Registers of the form 0x0fex
-    fn global_0fex(offset: usize) -> usize {
+    pub(crate) fn global_0fex(offset: usize) -> usize {
         assert!(offset < 16);
         let address = 0x0fe0 | offset;
         Self::global(address)
@@ -614,7 +638,7 @@ impl Addressing {
 
     /// The RDS0MR register set is "special", in that it has a gap at 0xN163,
     /// and skips from 0xN16f to 0xN1c0
-    fn rds0mr(channel: usize, timeslot: usize) -> usize {
+    pub(crate) fn rds0mr(channel: usize, timeslot: usize) -> usize {
         const OFFSET_MAP: [usize; Timeslots::COUNT] = [
             0x15f, 0x160, 0x161, 0x162, 0x164, 0x165, 0x166, 0x167,
             0x168, 0x169, 0x16a, 0x16b, 0x16c, 0x16d, 0x16e, 0x16f,
@@ -1,4 +1,11 @@
+use std::collections::HashMap;
+
+use console::style;
+use serde_json::{json, Value};
+use thiserror::Error;
+
 use super::device::{Device, Channel, Result, Timeslot};
+use super::map::{register_instances, RegisterInstance, RegisterTarget};
 
 pub fn registers_dump_raw(device: &Device) -> Result<()> {
     for address in 0..=0xffff {
@@ -236,3 +243,140 @@ pub fn registers_dump_timeslot(timeslot: &Timeslot) -> Result<()> {
 
     Ok(())
 }
+
+#[derive(Error, Debug)]
+pub enum SnapshotError {
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("no `registers` array")]
+    Registers,
+    #[error("register entry {0} needs an `address` up to 0xffff and a `value` up to 0xff")]
+    Entry(usize),
+}
+
+/// What register values get compared against.
+///
+pub enum RegisterReference {
+    /// The power-up defaults `framer_defaults` programs.
+    Defaults,
+    /// Values saved earlier with `registers_dump_json`.
+    Snapshot(HashMap<u16, u8>),
+}
+
+impl RegisterReference {
+    pub fn from_snapshot(json: &str) -> std::result::Result<Self, SnapshotError> {
+        let snapshot: Value = serde_json::from_str(json)?;
+        let values = snapshot["registers"].as_array().ok_or(SnapshotError::Registers)?.iter()
+            .enumerate()
+            .map(|(i, r)| {
+                let address = r["address"].as_u64().and_then(|a| u16::try_from(a).ok());
+                let value = r["value"].as_u64().and_then(|v| u8::try_from(v).ok());
+                address.zip(value).ok_or(SnapshotError::Entry(i))
+            })
+            .collect::<std::result::Result<_, _>>()?;
+        Ok(Self::Snapshot(values))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Defaults    => "default",
+            Self::Snapshot(_) => "snapshot",
+        }
+    }
+
+    pub fn value(&self, address: u16) -> Option<u8> {
+        match self {
+            Self::Defaults => xrt86vx38_pac::default::default_value(address),
+            Self::Snapshot(values) => values.get(&address).copied(),
+        }
+    }
+}
+
+/// Read every register in the map, or just those of one channel plus the
+/// device-wide ones. Status registers and PMON counters clear when read, so
+/// they're skipped unless `clear_on_read` is set.
+pub fn registers_read(device: &Device, channel: Option<usize>, clear_on_read: bool) -> Result<Vec<(RegisterInstance, u8)>> {
+    register_instances(channel).into_iter()
+        .filter(|instance| clear_on_read || !instance.register.clear_on_read)
+        .map(|instance| Ok((instance, device.register_read(instance.address())?)))
+        .collect()
+}
+
+pub fn registers_dump_decoded(readings: &[(RegisterInstance, u8)], reference: &RegisterReference, changed_only: bool) {
+    for (instance, value) in readings {
+        let expected = reference.value(instance.address()).filter(|expected| expected != value);
+        if changed_only && expected.is_none() {
+            continue;
+        }
+
+        let expected_fields = expected.map(|expected| instance.fields(expected)).unwrap_or_default();
+        let fields = instance.fields(*value).into_iter()
+            .map(|(name, field)| {
+                let text = format!("{name}={field}");
+                let differs = expected_fields.iter().any(|(n, f)| *n == name && *f != field);
+                if differs { style(text).yellow().bold().to_string() } else { text }
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let address = instance.address();
+        let name = instance.to_string();
+        match expected {
+            Some(expected) => println!("{address:04x} {name:12} {} ({} {expected:02x})  {fields}", style(format!("{value:02x}")).red().bold(), reference.name()),
+            None           => println!("{address:04x} {name:12} {value:02x}  {fields}"),
+        }
+    }
+}
+
+pub fn registers_dump_json(readings: &[(RegisterInstance, u8)], reference: &RegisterReference) -> Value {
+    let registers = readings.iter()
+        .map(|(instance, value)| {
+            let fields: serde_json::Map<String, Value> = instance.fields(*value).into_iter()
+                .map(|(name, field)| (name, Value::String(field)))
+                .collect();
+            json!({
+                "name": instance.to_string(),
+                "address": instance.address(),
+                "value": value,
+                "reference": reference.value(instance.address()),
+                "fields": fields,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "reference": reference.name(),
+        "registers": registers,
+    })
+}
+
+pub fn register_dump(target: &RegisterTarget, value: u8) {
+    let name = target.instance.map(|instance| instance.to_string()).unwrap_or_default();
+    let fields = target.instance.map(|instance| instance.fields(value)).unwrap_or_default().into_iter()
+        .map(|(name, field)| format!("{name}={field}"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    println!("{:04x} {name:12} {value:02x}  {fields}", target.address);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_and_snapshots() {
+        assert_eq!(RegisterReference::Defaults.value(0x3100), Some(0x11));
+        assert_eq!(RegisterReference::Defaults.value(0x0f52), Some(0x00));
+        // Read-only status registers have no default.
+        assert_eq!(RegisterReference::Defaults.value(0x110d), None);
+
+        let snapshot = RegisterReference::from_snapshot(r#"{"registers":[{"name":"csr@3","address":12544,"value":17}]}"#).unwrap();
+        assert_eq!(snapshot.value(0x3100), Some(0x11));
+        assert_eq!(snapshot.value(0x3101), None);
+
+        assert!(matches!(RegisterReference::from_snapshot("{"), Err(SnapshotError::Json(_))));
+        assert!(matches!(RegisterReference::from_snapshot(r#"{"registers":{}}"#), Err(SnapshotError::Registers)));
+        assert!(matches!(RegisterReference::from_snapshot(r#"{"registers":[{"address":1,"value":2},{"address":1,"value":256}]}"#), Err(SnapshotError::Entry(1))));
+        assert!(matches!(RegisterReference::from_snapshot(r#"{"registers":[{"address":65536,"value":0}]}"#), Err(SnapshotError::Entry(0))));
+    }
+}
@@ -0,0 +1,225 @@
+/// ESF Facility Data Link (FDL) bit-oriented message codes, per ANSI T1.403.
+///
+/// A bit-oriented message is a 16-bit codeword of the form `0xxxxxx0 11111111`,
+/// transmitted rightmost bit first, and repeated for as long as the condition
+/// persists (alarms) or for a burst of repetitions (commands).
+///
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum BitOrientedCode {
+    YellowAlarm,
+    LineLoopbackActivate,
+    LineLoopbackDeactivate,
+    PayloadLoopbackActivate,
+    PayloadLoopbackDeactivate,
+    UniversalLoopbackDeactivate,
+    ProtectionSwitchLine(u8),
+    ProtectionSwitchAcknowledge,
+    ProtectionSwitchRelease,
+    Unknown(u8),
+}
+
+impl BitOrientedCode {
+    /// Decode the `0xxxxxx0` half of a codeword, as written in T1.403.
+    fn from_code(code: u8) -> Self {
+        match code {
+            0b0000_0000 => Self::YellowAlarm,
+            0b0000_1110 => Self::LineLoopbackActivate,
+            0b0011_1000 => Self::LineLoopbackDeactivate,
+            0b0001_0100 => Self::PayloadLoopbackActivate,
+            0b0011_0010 => Self::PayloadLoopbackDeactivate,
+            0b0010_0100 => Self::UniversalLoopbackDeactivate,
+            0b0001_1000 => Self::ProtectionSwitchAcknowledge,
+            0b0010_0110 => Self::ProtectionSwitchRelease,
+            _ => {
+                // Protection switch line 1 through 27: 01nnnnn0
+                let line = (code >> 1) & 0x1f;
+                if code & 0xc0 == 0x40 && (1..=27).contains(&line) {
+                    Self::ProtectionSwitchLine(line)
+                } else {
+                    Self::Unknown(code)
+                }
+            },
+        }
+    }
+
+    /// The `0xxxxxx0` half of the codeword, as written in T1.403.
+    pub fn code(&self) -> u8 {
+        match self {
+            Self::YellowAlarm                 => 0b0000_0000,
+            Self::LineLoopbackActivate        => 0b0000_1110,
+            Self::LineLoopbackDeactivate      => 0b0011_1000,
+            Self::PayloadLoopbackActivate     => 0b0001_0100,
+            Self::PayloadLoopbackDeactivate   => 0b0011_0010,
+            Self::UniversalLoopbackDeactivate => 0b0010_0100,
+            Self::ProtectionSwitchAcknowledge => 0b0001_1000,
+            Self::ProtectionSwitchRelease     => 0b0010_0110,
+            Self::ProtectionSwitchLine(line)  => 0x40 | ((line & 0x1f) << 1),
+            Self::Unknown(code)               => *code,
+        }
+    }
+
+    /// Codeword bits in transmission order.
+    #[cfg(test)]
+    pub fn bits(&self) -> impl Iterator<Item=bool> {
+        let codeword = ((self.code() as u16) << 8) | 0x00ff;
+        (0..16).map(move |n| (codeword >> n) & 1 != 0)
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum BitOrientedMessage {
+    Start(BitOrientedCode),
+    End(BitOrientedCode),
+}
+
+/// Recovers bit-oriented messages from the serial FDL bit stream.
+///
+/// A message starts when the same codeword is received several times
+/// back-to-back, and ends when it hasn't been seen for a while. The end
+/// criteria is generous because T1.403 permits a yellow alarm to be
+/// interrupted by performance report messages for up to 100 milliseconds.
+///
+#[derive(Copy, Clone, Debug)]
+pub struct BitOrientedMessageDecoder {
+    shift: u16,
+    bits_since_codeword: u32,
+    candidate: Option<BitOrientedCode>,
+    candidate_count: u32,
+    active: Option<BitOrientedCode>,
+}
+
+impl BitOrientedMessageDecoder {
+    const CODEWORD_BITS: u32 = 16;
+
+    /// Number of consecutive identical codewords required to declare a message.
+    const VALIDATION_COUNT: u32 = 4;
+
+    /// 128 milliseconds at the 4 kbit/s FDL rate.
+    const END_BITS: u32 = Self::CODEWORD_BITS * 32;
+
+    pub fn new() -> Self {
+        Self {
+            shift: 0,
+            bits_since_codeword: 0,
+            candidate: None,
+            candidate_count: 0,
+            active: None,
+        }
+    }
+
+    pub fn push_bit<F>(&mut self, bit: bool, mut message_fn: F)
+        where F: FnMut(BitOrientedMessage)
+    {
+        self.shift = (self.shift << 1) | (bit as u16);
+        self.bits_since_codeword = self.bits_since_codeword.saturating_add(1);
+
+        // Bits arrive rightmost-first, so reverse them to get the codeword as
+        // it's written in the standard. The run of eight ones can only occur
+        // at one alignment, so a match here is always codeword-aligned.
+        let codeword = self.shift.reverse_bits();
+        if codeword & 0x81ff == 0x00ff {
+            let code = BitOrientedCode::from_code((codeword >> 8) as u8);
+
+            let consecutive = self.bits_since_codeword == Self::CODEWORD_BITS && self.candidate == Some(code);
+            if consecutive {
+                self.candidate_count += 1;
+            } else {
+                self.candidate = Some(code);
+                self.candidate_count = 1;
+            }
+            self.bits_since_codeword = 0;
+
+            if self.candidate_count == Self::VALIDATION_COUNT && self.active != Some(code) {
+                if let Some(previous) = self.active.take() {
+                    message_fn(BitOrientedMessage::End(previous));
+                }
+                self.active = Some(code);
+                message_fn(BitOrientedMessage::Start(code));
+            }
+        } else if self.bits_since_codeword >= Self::END_BITS {
+            self.candidate = None;
+            self.candidate_count = 0;
+            if let Some(previous) = self.active.take() {
+                message_fn(BitOrientedMessage::End(previous));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(decoder: &mut BitOrientedMessageDecoder, bits: impl Iterator<Item=bool>) -> Vec<BitOrientedMessage> {
+        let mut messages = Vec::new();
+        for bit in bits {
+            decoder.push_bit(bit, |m| messages.push(m));
+        }
+        messages
+    }
+
+    fn repeat(code: BitOrientedCode, count: usize) -> impl Iterator<Item=bool> {
+        (0..count).flat_map(move |_| code.bits())
+    }
+
+    fn hdlc_flags(count: usize) -> impl Iterator<Item=bool> {
+        (0..count).flat_map(|_| (0..8).map(|n| (0x7eu8 >> n) & 1 != 0))
+    }
+
+    #[test]
+    fn code_round_trip() {
+        for code in 0..=255u8 {
+            if code & 0x81 == 0 {
+                assert_eq!(BitOrientedCode::from_code(code).code(), code);
+            }
+        }
+        assert_eq!(BitOrientedCode::from_code(0b0100_0010), BitOrientedCode::ProtectionSwitchLine(1));
+        assert_eq!(BitOrientedCode::from_code(0b0111_0110), BitOrientedCode::ProtectionSwitchLine(27));
+        assert_eq!(BitOrientedCode::from_code(0b0111_1000), BitOrientedCode::Unknown(0b0111_1000));
+    }
+
+    #[test]
+    fn validation_requires_consecutive_codewords() {
+        let mut dut = BitOrientedMessageDecoder::new();
+        let code = BitOrientedCode::LineLoopbackActivate;
+
+        let messages = run(&mut dut, repeat(code, 3));
+        assert!(messages.is_empty());
+
+        let messages = run(&mut dut, repeat(code, 1));
+        assert_eq!(messages, [BitOrientedMessage::Start(code)]);
+
+        let messages = run(&mut dut, repeat(code, 10));
+        assert!(messages.is_empty());
+    }
+
+    #[test]
+    fn message_ends_after_silence() {
+        let mut dut = BitOrientedMessageDecoder::new();
+        let code = BitOrientedCode::YellowAlarm;
+
+        let messages = run(&mut dut, hdlc_flags(4).chain(repeat(code, 10)));
+        assert_eq!(messages, [BitOrientedMessage::Start(code)]);
+
+        // Performance report interruption shorter than the end criteria.
+        let messages = run(&mut dut, hdlc_flags(50).chain(repeat(code, 10)));
+        assert!(messages.is_empty());
+
+        let messages = run(&mut dut, hdlc_flags(80));
+        assert_eq!(messages, [BitOrientedMessage::End(code)]);
+    }
+
+    #[test]
+    fn new_code_ends_previous() {
+        let mut dut = BitOrientedMessageDecoder::new();
+        let first = BitOrientedCode::PayloadLoopbackActivate;
+        let second = BitOrientedCode::PayloadLoopbackDeactivate;
+
+        let messages = run(&mut dut, repeat(first, 10).chain(repeat(second, 10)));
+        assert_eq!(messages, [
+            BitOrientedMessage::Start(first),
+            BitOrientedMessage::End(first),
+            BitOrientedMessage::Start(second),
+        ]);
+    }
+}
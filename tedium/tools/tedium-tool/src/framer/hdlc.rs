@@ -0,0 +1,245 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use super::control::HdlcTransmitStatus;
+use super::register::DLSRx;
+
+/// Largest frame either protocol sends, plus two FCS octets if the framer
+/// passes them along. An SS7 MSU (BSN, FSN and LI octets, SIO, and 272 SIF
+/// octets) outgrows a Q.921 frame (two address octets, two control octets, and
+/// N201 (260) information octets).
+///
+pub const FRAME_BYTES_MAX: usize = 3 + 1 + 272 + 2;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum HdlcReceiveError {
+    /// Data arrived without a start of frame, probably the tail of a frame that
+    /// began before we started listening.
+    MissingStart,
+    /// A new frame started before the previous one ended.
+    MissingEnd,
+    Aborted,
+    FcsError,
+    Overrun,
+}
+
+/// Reassembles frames received by one framer HDLC controller.
+///
+/// The framer hands over received data in buffer-sized chunks, which arrive
+/// across one or more interrupts. The DLSR flags latched alongside each chunk
+/// say whether the chunk starts a frame, ends a frame, or ends it badly.
+///
+pub struct HdlcReceiver {
+    buffer: Vec<u8>,
+    in_progress: bool,
+}
+
+impl HdlcReceiver {
+    pub fn new() -> Self {
+        Self {
+            buffer: Vec::with_capacity(FRAME_BYTES_MAX),
+            in_progress: false,
+        }
+    }
+
+    pub fn push_chunk<F>(&mut self, dlsr: DLSRx, data: &[u8], mut frame_fn: F)
+        where F: FnMut(Result<&[u8], HdlcReceiveError>)
+    {
+        if dlsr.RxSOT() != 0 {
+            if self.in_progress && !self.buffer.is_empty() {
+                frame_fn(Err(HdlcReceiveError::MissingEnd));
+            }
+            self.reset();
+            self.in_progress = true;
+        }
+
+        if !self.in_progress {
+            if !data.is_empty() {
+                frame_fn(Err(HdlcReceiveError::MissingStart));
+            }
+            return;
+        }
+
+        if self.buffer.len() + data.len() > FRAME_BYTES_MAX {
+            self.reset();
+            frame_fn(Err(HdlcReceiveError::Overrun));
+            return;
+        }
+        self.buffer.extend_from_slice(data);
+
+        if dlsr.RxABORT() != 0 {
+            self.reset();
+            frame_fn(Err(HdlcReceiveError::Aborted));
+        } else if dlsr.RxEOT() != 0 {
+            if dlsr.FCS_ERR() != 0 {
+                frame_fn(Err(HdlcReceiveError::FcsError));
+            } else if !self.buffer.is_empty() {
+                frame_fn(Ok(&self.buffer));
+            }
+            self.reset();
+        }
+    }
+
+    fn reset(&mut self) {
+        self.buffer.clear();
+        self.in_progress = false;
+    }
+}
+
+/// Paces frames queued for one framer HDLC controller.
+///
+/// The firmware holds one frame per controller, so the next frame is only
+/// offered once the controller reports TxEOT for the previous one. A frame
+/// the firmware turned away as busy is offered again on the next poll.
+///
+pub struct HdlcTransmitter {
+    queue: VecDeque<Vec<u8>>,
+    /// When the firmware accepted the frame being sent.
+    in_flight: Option<Instant>,
+}
+
+impl HdlcTransmitter {
+    /// A full frame takes about 12 ms at 64 kbit/s, so this only runs out
+    /// if the TxEOT interrupt was lost.
+    pub const TX_EOT_TIMEOUT: Duration = Duration::from_secs(1);
+
+    pub fn new() -> Self {
+        Self {
+            queue: VecDeque::new(),
+            in_flight: None,
+        }
+    }
+
+    pub fn push(&mut self, frame: &[u8]) {
+        self.queue.push_back(frame.to_vec());
+    }
+
+    /// Offer the next queued frame to `send_fn`, if the controller is free.
+    pub fn poll<F, E>(&mut self, now: Instant, mut send_fn: F) -> Result<(), E>
+        where F: FnMut(&[u8]) -> Result<HdlcTransmitStatus, E>
+    {
+        if let Some(sent) = self.in_flight {
+            if now.saturating_duration_since(sent) < Self::TX_EOT_TIMEOUT {
+                return Ok(());
+            }
+            self.in_flight = None;
+        }
+
+        if let Some(frame) = self.queue.front() {
+            // Busy means the controller is sending something of its own (like
+            // an automatic performance report), so leave the frame queued.
+            if send_fn(frame)? == HdlcTransmitStatus::Queued {
+                self.queue.pop_front();
+                self.in_flight = Some(now);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The controller reported TxEOT.
+    pub fn transmit_complete(&mut self) {
+        self.in_flight = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(dut: &mut HdlcReceiver, dlsr: DLSRx, data: &[u8]) -> Vec<Result<Vec<u8>, HdlcReceiveError>> {
+        let mut frames = Vec::new();
+        dut.push_chunk(dlsr, data, |f| frames.push(f.map(|f| f.to_vec())));
+        frames
+    }
+
+    fn start() -> DLSRx { DLSRx::new().with_RxSOT(1) }
+    fn middle() -> DLSRx { DLSRx::new() }
+    fn end() -> DLSRx { DLSRx::new().with_RxEOT(1) }
+
+    #[test]
+    fn single_chunk_frame() {
+        let mut dut = HdlcReceiver::new();
+        let frames = run(&mut dut, start().with_RxEOT(1), &[1, 2, 3]);
+        assert_eq!(frames, [Ok(vec![1, 2, 3])]);
+    }
+
+    #[test]
+    fn frame_spans_interrupts() {
+        let mut dut = HdlcReceiver::new();
+        assert!(run(&mut dut, start(), &[1, 2]).is_empty());
+        assert!(run(&mut dut, middle(), &[3, 4]).is_empty());
+        assert_eq!(run(&mut dut, end(), &[5]), [Ok(vec![1, 2, 3, 4, 5])]);
+    }
+
+    #[test]
+    fn bad_frames_discarded() {
+        let mut dut = HdlcReceiver::new();
+
+        assert_eq!(run(&mut dut, middle(), &[9, 9]), [Err(HdlcReceiveError::MissingStart)]);
+        assert!(run(&mut dut, end(), &[]).is_empty());
+
+        run(&mut dut, start(), &[1, 2]);
+        assert_eq!(run(&mut dut, end().with_FCS_ERR(1), &[3]), [Err(HdlcReceiveError::FcsError)]);
+
+        run(&mut dut, start(), &[1, 2]);
+        assert_eq!(run(&mut dut, middle().with_RxABORT(1), &[]), [Err(HdlcReceiveError::Aborted)]);
+
+        run(&mut dut, start(), &[1, 2]);
+        assert_eq!(run(&mut dut, start(), &[4]), [Err(HdlcReceiveError::MissingEnd)]);
+        assert_eq!(run(&mut dut, end(), &[5]), [Ok(vec![4, 5])]);
+    }
+
+    #[test]
+    fn max_length_frame() {
+        let mut dut = HdlcReceiver::new();
+        let frame: Vec<u8> = (0..FRAME_BYTES_MAX).map(|i| i as u8).collect();
+        let (first, rest) = frame.split_at(96);
+        let (second, last) = rest.split_at(96);
+        assert!(run(&mut dut, start(), first).is_empty());
+        assert!(run(&mut dut, middle(), second).is_empty());
+        assert_eq!(run(&mut dut, end(), last), [Ok(frame.clone())]);
+    }
+
+    #[test]
+    fn overrun_discarded() {
+        let mut dut = HdlcReceiver::new();
+        let chunk = [0u8; 96];
+        run(&mut dut, start(), &chunk);
+        run(&mut dut, middle(), &chunk);
+        assert_eq!(run(&mut dut, middle(), &chunk), [Err(HdlcReceiveError::Overrun)]);
+        assert_eq!(run(&mut dut, end(), &chunk), [Err(HdlcReceiveError::MissingStart)]);
+    }
+
+    #[test]
+    fn transmitter_waits_for_tx_eot() {
+        let t0 = Instant::now();
+        let mut dut = HdlcTransmitter::new();
+        dut.push(&[1]);
+        dut.push(&[2]);
+        dut.push(&[3]);
+
+        let mut sent = Vec::new();
+        let mut poll = |dut: &mut HdlcTransmitter, now, status| {
+            dut.poll(now, |f| -> Result<_, ()> { sent.push(f.to_vec()); Ok(status) }).unwrap();
+        };
+
+        poll(&mut dut, t0, HdlcTransmitStatus::Queued);
+        poll(&mut dut, t0, HdlcTransmitStatus::Queued);
+
+        // Firmware was busy, so the frame stays queued and is retried.
+        dut.transmit_complete();
+        poll(&mut dut, t0, HdlcTransmitStatus::Busy);
+        poll(&mut dut, t0, HdlcTransmitStatus::Queued);
+
+        // No TxEOT, so wait it out.
+        poll(&mut dut, t0 + Duration::from_millis(999), HdlcTransmitStatus::Queued);
+        poll(&mut dut, t0 + HdlcTransmitter::TX_EOT_TIMEOUT, HdlcTransmitStatus::Queued);
+
+        // Nothing left to send.
+        dut.transmit_complete();
+        poll(&mut dut, t0 + HdlcTransmitter::TX_EOT_TIMEOUT, HdlcTransmitStatus::Queued);
+
+        assert_eq!(sent, [vec![1], vec![2], vec![2], vec![3]]);
+    }
+}
@@ -1,268 +1,154 @@
-use std::{io::{Read, Cursor, self}, slice, sync::{Arc, Mutex}, time::Instant};
+use std::{slice, sync::{Arc, Mutex}, thread, time::Instant};
 
-use crossbeam::channel::Sender;
+use crossbeam::channel::{bounded, Sender};
 
 use console::{style, Color};
 use rusb::{ffi, constants::*, UsbContext};
 
-use crate::framer::{register::*, device::{open_device}, usb::{EndpointNumber, InterfaceNumber, Transfer, CallbackWrapper, from_libusb}};
+use crate::framer::{register::*, device::{open_device}, usb::{EndpointNumber, InterfaceNumber, Transfer, CallbackInWrapper, from_libusb}};
 
-use super::{FramerEvent, usb::{TransferHandler, INTERRUPT_BYTES_MAX}, device};
+use super::{FramerEvent, usb::{CallbackIn, INTERRUPT_BYTES_MAX}, device, control::{FramerControl, FramerControlError}, hdlc::{HdlcReceiver, FRAME_BYTES_MAX}};
+use super::{alarm::Defects, audio::TimeslotAddress};
 
-pub struct LoopbackCodeStatus {
-    pub rlcisrs: [RLCISRx; 8],
-}
-
-pub struct HDLCControllerStatus {
-    pub dlsr: DLSRx,
-    pub rdlbcr: RDLBCR,
-    pub data: Vec<u8>,
-    pub ss7sr: SS7SRx,
-}
-
-pub struct HDLCStatus {
-    pub controller: [HDLCControllerStatus; 3],
-}
-
-pub struct SlipStatus {
-    pub sbisr: SBISR,
-}
-
-pub struct AlarmStatus {
-    pub aeisr: AEISR,
-    pub exzsr: EXZSR,
-    pub ciasr: CIASR,
-}
-
-pub struct T1FrameStatus {
-    pub fisr: FISR,
-    pub sig: Option<ReceiveSignalingStatus>,
-}
-
-pub struct ReceiveSignalingStatus {
-    pub rsars: [RSAR; 24],
-}
-
-pub struct FramerInterruptStatus {
-    pub channel_index: usize,
-    pub bisr: BISR,
-    pub lbcode: Option<LoopbackCodeStatus>,
-    pub hdlc: Option<HDLCStatus>,
-    pub slip: Option<SlipStatus>,
-    pub alarm: Option<AlarmStatus>,
-    pub t1frame: Option<T1FrameStatus>,
-}
+pub use super::interrupt_status::*;
 
 ///////////////////////////////////////////////////////////////////////
 
-impl FramerInterruptStatus {
-    pub fn from_slice(b: &[u8]) -> io::Result<Self> {
-        let mut r = Cursor::new(b);
-        let result = Self::from_read(&mut r);
-        assert_eq!(b.len() as u64, r.position());
-        result
-    }
-
-    fn from_read(r: &mut impl Read) -> io::Result<Self> {
-        let mut channel_index = [0u8; 1];
-        r.read(&mut channel_index)?;
-        let channel_index = channel_index[0] as usize;
-
-        let mut bisr = [0u8; 1];
-        r.read(&mut bisr)?;
-        let bisr = BISR::from(bisr[0]);
-
-        let lbcode = if bisr.LBCODE() != 0 {
-            Some(LoopbackCodeStatus::from_read(r)?)
-        } else {
-            None
-        };
-
-        if bisr.RxClkLOS() != 0 {
-
-        }
-    
-        if bisr.ONESEC() != 0 {
-    
-        }
-    
-        let hdlc = if bisr.HDLC() != 0 {
-            Some(HDLCStatus::from_read(r)?)
-        } else {
-            None
-        };
-
-        let slip = if bisr.SLIP() != 0 {
-            Some(SlipStatus::from_read(r)?)
-        } else {
-            None
-        };
-
-        let alarm = if bisr.ALARM() != 0 {
-            Some(AlarmStatus::from_read(r)?)
-        } else {
-            None
-        };
-
-        let t1frame = if bisr.T1FRAME() != 0 {
-            Some(T1FrameStatus::from_read(r)?)
-        } else {
-            None
-        };
-
-        Ok(Self {
-            channel_index,
-            bisr,
-            lbcode,
-            hdlc,
-            slip,
-            alarm,
-            t1frame,
-        })
-    }
+struct FramerInterruptHandler {
+    sender: Sender<FramerEvent>,
+    hdlc_receivers: [[HdlcReceiver; 3]; 8],
+    /// Last reported ABCD bits of each timeslot, so only changes are sent on.
+    signaling: [[Option<u8>; 24]; 8],
+    /// Last reported defects of each channel, so only changes are sent on.
+    defects: [Option<Defects>; 8],
+    /// Wakes the resync thread. It holds one request, which covers any more
+    /// made before the thread gets to it.
+    resync: Sender<()>,
 }
 
-impl LoopbackCodeStatus {
-    fn from_read(r: &mut impl Read) -> io::Result<Self> {
-        let mut rlcisrs = [0u8; 8];
-        r.read(&mut rlcisrs)?;
-        let rlcisrs = rlcisrs.map(|v| RLCISRx::from(v));
-
-        Ok(Self {
-            rlcisrs,
-        })
+impl FramerInterruptHandler {
+    fn new(sender: Sender<FramerEvent>, resync: Sender<()>) -> Self {
+        Self {
+            sender,
+            hdlc_receivers: [(); 8].map(|_| [(); 3].map(|_| HdlcReceiver::new())),
+            signaling: [[None; 24]; 8],
+            defects: [None; 8],
+            resync,
+        }
     }
-}
 
-impl HDLCStatus {
-    fn from_read(r: &mut impl Read) -> io::Result<Self> {
-        Ok(Self {
-            controller: [
-                HDLCControllerStatus::from_read(r)?,
-                HDLCControllerStatus::from_read(r)?,
-                HDLCControllerStatus::from_read(r)?,
-            ],
-        })
+    /// Interrupts may have been lost, so ask the firmware for all of them again.
+    fn request_resync(&self) {
+        let _ = self.resync.try_send(());
     }
-}
 
-impl HDLCControllerStatus {
-    fn from_read(r: &mut impl Read) -> io::Result<Self> {
-        let mut dlsr = [0u8; 1];
-        r.read(&mut dlsr)?;
-        let dlsr = DLSRx::from(dlsr[0]);
-
-        let mut rdlbcr = [0u8; 1];
-        r.read(&mut rdlbcr)?;
-        let rdlbcr = RDLBCR::from(rdlbcr[0]);
-
-        let rdlbc = rdlbcr.RDLBC().try_into().unwrap();
-        let mut data = Vec::with_capacity(rdlbc);
-        data.resize(rdlbc, 0);
-        r.read(data.as_mut_slice())?;
-
-        let mut ss7sr = [0u8; 1];
-        r.read(&mut ss7sr)?;
-        let ss7sr = SS7SRx::from(ss7sr[0]);
-
-        Ok(Self {
-            dlsr,
-            rdlbcr,
-            data,
-            ss7sr,
-        })
+    fn send_event(&self, event: FramerEvent) {
+        if let Err(e) = self.sender.send(event) {
+            eprint!("error: data.sender.send: {:?}", e);
+        }
     }
-}
-
-impl SlipStatus {
-    fn from_read(r: &mut impl Read) -> io::Result<Self> {
-        let mut sbisr = [0u8; 1];
-        r.read(&mut sbisr)?;
-        let sbisr = SBISR::from(sbisr[0]);
 
-        Ok(Self {
-            sbisr,
-        })
-    }
-}
+    /// Turn one interrupt's worth of status into events, in the order the
+    /// firmware reported them.
+    fn receive(&mut self, status: &FramerInterruptStatus, timestamp: Instant) {
+        let channel_index = status.channel_index;
 
-impl AlarmStatus {
-    fn from_read(r: &mut impl Read) -> io::Result<Self> {
-        let mut buffer = [0u8; 3];
-        r.read(&mut buffer)?;
-        
-        let aeisr = AEISR::from(buffer[0]);
-        let exzsr = EXZSR::from(buffer[1]);
-        let ciasr = CIASR::from(buffer[2]);
-
-        Ok(Self{
-            aeisr,
-            exzsr,
-            ciasr,
-        })
-    }
-}
+        if let Some(lbcode) = status.lbcode {
+            self.send_event(FramerEvent::LoopbackCode { timestamp, channel_index, status: lbcode });
+        }
 
-impl T1FrameStatus {
-    fn from_read(r: &mut impl Read) -> io::Result<Self> {
-        let mut fisr = [0u8; 1];
-        r.read(&mut fisr)?;
-        let fisr = FISR::from(fisr[0]);
+        if let Some(clock) = &status.clock {
+            self.send_event(FramerEvent::RecoveredClock { channel_index, lost: clock.lost });
+        }
+        if status.bisr.ONESEC() != 0 {
+            self.send_event(FramerEvent::OneSecond { channel_index });
+        }
 
-        let sig = if fisr.SIG() != 0 {
-            Some(ReceiveSignalingStatus::from_read(r)?)
-        } else {
-            None
-        };
+        self.receive_hdlc(status);
 
-        Ok(Self {
-            fisr,
-            sig,
-        })
-    }
-}
+        if let Some(slip) = &status.slip {
+            self.send_event(FramerEvent::Slip { timestamp, channel_index, sbisr: slip.sbisr });
+        }
 
-impl ReceiveSignalingStatus {
-    fn from_read(r: &mut impl Read) -> io::Result<Self> {
-        let mut buffer = [0u8; 12];
-        r.read(&mut buffer)?;
-
-        let mut rsars = [RSAR::new(); 24];
-        for (i, &v) in buffer.iter().enumerate() {
-            let even = RSAR::from(v >> 4);
-            let odd = RSAR::from(v & 15);
-            rsars[i * 2 + 0] = even;
-            rsars[i * 2 + 1] = odd;
+        if let Some(alarm) = &status.alarm {
+            let defects = Defects::from_status(alarm);
+            if self.defects[channel_index].replace(defects) != Some(defects) {
+                self.send_event(FramerEvent::Alarm { timestamp, channel_index, defects, status: *alarm });
+            }
         }
 
-        Ok(Self {
-            rsars,
-        })
+        if let Some(t1frame) = &status.t1frame {
+            self.send_event(FramerEvent::Framing { channel_index, fisr: t1frame.fisr });
+
+            if let Some(sig) = &t1frame.sig {
+                for (timeslot_index, rsar) in sig.rsars.iter().enumerate() {
+                    let new_abcd = (rsar.A() << 3) | (rsar.B() << 2) | (rsar.C() << 1) | rsar.D();
+                    let old_abcd = self.signaling[channel_index][timeslot_index].replace(new_abcd);
+                    if old_abcd != Some(new_abcd) {
+                        let address = TimeslotAddress::new(channel_index, timeslot_index);
+                        self.send_event(FramerEvent::Signaling { timestamp, address, old_abcd, new_abcd });
+                    }
+                }
+            }
+        }
     }
-}
 
-///////////////////////////////////////////////////////////////////////
+    fn receive_hdlc(&mut self, status: &FramerInterruptStatus) {
+        let channel_index = status.channel_index;
 
-struct FramerInterruptHandler {
-    sender: Sender<FramerEvent>,
-}
+        let Some(hdlc) = &status.hdlc else {
+            return;
+        };
 
-impl FramerInterruptHandler {
-    fn new(sender: Sender<FramerEvent>) -> Self {
-        Self {
-            sender,
+        let mut events = Vec::new();
+        for (controller_index, (controller, receiver)) in hdlc.controller.iter().zip(self.hdlc_receivers[channel_index].iter_mut()).enumerate() {
+            let dlsr_u8: u8 = controller.dlsr.into();
+            if dlsr_u8 != 0 || !controller.data.is_empty() {
+                let mut data = [0u8; HDLC_CHUNK_BYTES_MAX];
+                data[0..controller.data.len()].copy_from_slice(&controller.data);
+                events.push(FramerEvent::HdlcChunk {
+                    channel_index,
+                    controller_index,
+                    dlsr: controller.dlsr,
+                    buffer_index: controller.rdlbcr.RBUFPTR() as usize,
+                    data,
+                    length: controller.data.len(),
+                });
+            }
+            if controller.dlsr.TxEOT() != 0 {
+                events.push(FramerEvent::HdlcTransmitComplete { channel_index, controller_index });
+            }
+            if controller.ss7sr.SS7_STATUS() != 0 {
+                events.push(FramerEvent::Ss7Status { channel_index, controller_index });
+            }
+
+            // HDLC frames span interrupts, so they're reassembled here, where
+            // every interrupt is seen in order, rather than by each consumer.
+            receiver.push_chunk(controller.dlsr, &controller.data, |frame| {
+                match frame {
+                    Ok(frame) => {
+                        let mut data = [0u8; FRAME_BYTES_MAX];
+                        data[0..frame.len()].copy_from_slice(frame);
+                        events.push(FramerEvent::HdlcFrame {
+                            channel_index,
+                            controller_index,
+                            data,
+                            length: frame.len(),
+                        });
+                    },
+                    Err(e) => eprintln!("framer: hdlc: {channel_index}.{controller_index}: {e:?}"),
+                }
+            });
         }
-    }
 
-    fn send_event(&self, event: FramerEvent) {
-        if let Err(e) = self.sender.send(event) {
-            eprint!("error: data.sender.send: {:?}", e);
+        for event in events {
+            self.send_event(event);
         }
     }
 }
 
-impl TransferHandler for FramerInterruptHandler {
-    fn callback(&self, transfer: *mut ffi::libusb_transfer) {
+impl CallbackIn for FramerInterruptHandler {
+    fn callback_in(&mut self, transfer: *mut ffi::libusb_transfer) {
         let status = unsafe { (*transfer).status };
         let actual_length = unsafe { (*transfer).actual_length }.try_into().unwrap();
 
@@ -281,17 +167,13 @@ impl TransferHandler for FramerInterruptHandler {
                 slice::from_raw_parts_mut(buffer, actual_length)
             };
 
-            let mut message = FramerEvent::Interrupt {
-                timestamp: Instant::now(),
-                data: [0u8; INTERRUPT_BYTES_MAX],
-                length: actual_length
-            };
-            if let FramerEvent::Interrupt { timestamp: _, ref mut data, length } = message {
-                data[0..length].copy_from_slice(buffer);
-            }
-            
-            Some(message)
+            let mut data = [0u8; INTERRUPT_BYTES_MAX];
+            data[0..actual_length].copy_from_slice(buffer);
+            Some((Instant::now(), data, actual_length))
         } else {
+            if status != LIBUSB_TRANSFER_COMPLETED {
+                self.request_resync();
+            }
             None
         };
 
@@ -300,11 +182,17 @@ impl TransferHandler for FramerInterruptHandler {
         };
         match result {
             LIBUSB_SUCCESS => {},
-            e => eprintln!("IN: libusb_submit_transfer error: {e}"),
+            e => {
+                eprintln!("IN: libusb_submit_transfer error: {e}");
+                self.request_resync();
+            },
         }
 
-        if let Some(message) = message {
-            self.send_event(message);
+        if let Some((timestamp, data, length)) = message {
+            match FramerInterruptStatus::from_slice(&data[0..length]) {
+                Ok(status) => self.receive(&status, timestamp),
+                Err(error) => self.send_event(FramerEvent::InterruptError { error, data, length }),
+            }
         }
     }
 }
@@ -314,10 +202,13 @@ pub struct FramerInterruptThread {
 }
 
 impl FramerInterruptThread {
-    pub fn run(sender: Sender<FramerEvent>) -> device::Result<()> {
+    /// Pump interrupts from the firmware into `sender`. Whenever interrupts
+    /// may have been missed, including at the start, `control` is used to
+    /// ask the firmware for all the current interrupt state.
+    pub fn run(sender: Sender<FramerEvent>, control: Arc<FramerControl>) -> device::Result<()> {
         let mut context = rusb::Context::new()?;
 
-        let mut device = open_device(&mut context)?;
+        let device = open_device(&mut context)?;
 
         let endpoint = LIBUSB_ENDPOINT_IN | EndpointNumber::Interrupt as u8;
 
@@ -331,8 +222,28 @@ impl FramerInterruptThread {
         const TRANSFERS_COUNT: usize = 4;
 
         let mut transfers: Vec<Transfer> = Vec::new();
-        
-        let handler = Arc::new(Mutex::new(FramerInterruptHandler::new(sender)));
+
+        // The resync request is a synchronous transfer with a long timeout,
+        // so it gets a thread of its own rather than holding up the event
+        // loop, which has to keep reaping the interrupts it produces.
+        let (resync_sender, resync_receiver) = bounded(1);
+        let (resync_error_sender, resync_error_receiver) = bounded(1);
+        thread::Builder::new()
+            .name("fr_resync".to_string())
+            .spawn(move || {
+                for () in resync_receiver.iter() {
+                    match control.resync() {
+                        Ok(()) => {},
+                        Err(FramerControlError::Usb(e)) => {
+                            let _ = resync_error_sender.send(e);
+                            break;
+                        },
+                        Err(e) => eprintln!("error: framer interrupt resync: {e}"),
+                    }
+                }
+            }).unwrap();
+
+        let handler = Arc::new(Mutex::new(FramerInterruptHandler::new(sender, resync_sender)));
         
         for _ in 0..TRANSFERS_COUNT {
             let transfer = Transfer::new_interrupt_transfer(
@@ -340,7 +251,7 @@ impl FramerInterruptThread {
                 endpoint,
                 INTERRUPT_BYTES_MAX,
                 0,
-                Box::new(CallbackWrapper::new(handler.clone())),
+                Box::new(CallbackInWrapper::new(handler.clone())),
             );
 
             transfer.submit();
@@ -349,6 +260,9 @@ impl FramerInterruptThread {
 
         let context = device.context();
 
+        // Nothing has been heard yet.
+        handler.lock().unwrap().request_resync();
+
         loop {
             let result = unsafe {
                 ffi::libusb_handle_events(context.as_raw())
@@ -360,129 +274,135 @@ impl FramerInterruptThread {
                 // is private to the rusb crate.
                 return Err(from_libusb(result));
             }
+
+            if let Ok(e) = resync_error_receiver.try_recv() {
+                return Err(e);
+            }
         }
     }
 }
 
 ///////////////////////////////////////////////////////////////////////
 
-pub fn print_framer_interrupt_status(s: &FramerInterruptStatus) {
+fn bits(names: &[(&str, u8)]) -> String {
     let color = |v| if v != 0 { Color::Red } else { Color::Green };
+    names.iter().map(|&(name, v)| format!("[{}]", style(name).fg(color(v)))).collect()
+}
 
-    let bisr = s.bisr;
-    eprint!("CH{} BISR:[{}][{}][{}][{}][{}][{}][{}]",
-        s.channel_index,
-        style("LBCODE").fg(color(bisr.LBCODE())),
-        style("RXCLOS").fg(color(bisr.RxClkLOS())),
-        style("ONESEC").fg(color(bisr.ONESEC())),
-        style("HDLC").fg(color(bisr.HDLC())),
-        style("SLIP").fg(color(bisr.SLIP())),
-        style("ALRM").fg(color(bisr.ALARM())),
-        style("T1FRM").fg(color(bisr.T1FRAME())),
-    );
-
-    if let Some(lbcode) = &s.lbcode {
-        eprint!(" RLCISR=[");
-        for rlcisr in lbcode.rlcisrs {
-            let rlcisr_u8: u8 = rlcisr.into();
-            eprint!(" {rlcisr_u8:02x}");
-        }
-        eprint!("]");
-    }
+pub fn rlcisr_bytes(status: &LoopbackCodeStatus) -> String {
+    let bytes: Vec<_> = status.rlcisrs.iter().map(|&rlcisr| format!("{:02x}", u8::from(rlcisr))).collect();
+    format!("RLCISR=[{}]", bytes.join(" "))
+}
 
-    if let Some(hdlc) = &s.hdlc {
-        for (hdlc_index, controller) in hdlc.controller.iter().enumerate() {
-            let dlsr = controller.dlsr;
-            let dlsr_u8: u8 = dlsr.into();
-
-            if dlsr_u8 != 0 {
-                print!(" DLSR{}:[{}][{}][{}][{}][{}][{}][{}][{}]",
-                    hdlc_index,
-                    style("MOS").fg(color(dlsr.MSG_TYPE())),
-                    style("TxSOT").fg(color(dlsr.TxSOT())),
-                    style("RxSOT").fg(color(dlsr.RxSOT())),
-                    style("TxEOT").fg(color(dlsr.TxEOT())),
-                    style("RxEOT").fg(color(dlsr.RxEOT())),
-                    style("FCS").fg(color(dlsr.FCS_ERR())),
-                    style("RxABT").fg(color(dlsr.RxABORT())),
-                    style("RxIDL").fg(color(dlsr.RxIDLE())),
-                );
-
-                let rdlbcr = controller.rdlbcr;
-                print!(" LAPDBCR{}:[", rdlbcr.RBUFPTR());
-                for data in &controller.data {
-                    print!("{data:02x}");
-                }
-                print!("]");
+pub fn dlsr_bits(dlsr: DLSRx) -> String {
+    format!("DLSR:{}", bits(&[
+        ("MOS", dlsr.MSG_TYPE()),
+        ("TxSOT", dlsr.TxSOT()),
+        ("RxSOT", dlsr.RxSOT()),
+        ("TxEOT", dlsr.TxEOT()),
+        ("RxEOT", dlsr.RxEOT()),
+        ("FCS", dlsr.FCS_ERR()),
+        ("RxABT", dlsr.RxABORT()),
+        ("RxIDL", dlsr.RxIDLE()),
+    ]))
+}
 
-                if dlsr.FCS_ERR() != 0 {
-                    print!(" FCS_ERR");
-                }
-            }
-        }
-    }
+pub fn alarm_bits(alarm: &AlarmStatus) -> String {
+    let (aeisr, exzsr, ciasr) = (alarm.aeisr, alarm.exzsr, alarm.ciasr);
+    format!("AEISR:{} EXZSR:{} CIASR:{}",
+        bits(&[
+            ("RXOOF", aeisr.RxOOF_State()),
+            ("RXAIS", aeisr.RxAIS_State()),
+            ("RXYEL", aeisr.RxYEL_State()),
+            ("LOS", aeisr.LOS_State()),
+            ("LCV", aeisr.LCVInt_Status()),
+            ("RXOOFX", aeisr.RxOOF_State_Change()),
+            ("RXAISX", aeisr.RxAIS_State_Change()),
+            ("RXYELX", aeisr.RxYEL_State_Change()),
+        ]),
+        bits(&[
+            ("EXZ", exzsr.EXZ_STATUS()),
+        ]),
+        bits(&[
+            ("RAISCI", ciasr.RxAIS_CI_state()),
+            ("RRAICI", ciasr.RxRAI_CI_state()),
+        ]),
+    )
+}
 
-    if let Some(slip) = &s.slip {
-        let sbisr = slip.sbisr;
-        print!(" SBISR:[{}][{}][{}][{}][{}][{}][{}][{}]",
-            style("TSBF").fg(color(sbisr.TxSB_FULL())),
-            style("TSBE").fg(color(sbisr.TxSB_EMPT())),
-            style("TSBS").fg(color(sbisr.TxSB_SLIP())),
-            style("RSBF").fg(color(sbisr.RxSB_FULL())),
-            style("RSBE").fg(color(sbisr.RxSB_EMPT())),
-            style("RSBS").fg(color(sbisr.RxSB_SLIP())),
-            style("SLC96LOCK").fg(color(sbisr.SLC96_LOCK())),
-            style("MFLOCK").fg(color(sbisr.Multiframe_LOCK())),
-        );
-    }
+pub fn liuccsr_bits(liuccsr: LIUCCSR) -> String {
+    format!("LIUCCSR:{}", bits(&[
+        ("DMO", liuccsr.DMO_n()),
+        ("FLS", liuccsr.FLS_n()),
+        ("LCV", liuccsr.LCV_n()),
+        ("NLCD", liuccsr.NLCD_n()),
+        ("AISD", liuccsr.AISD_n()),
+        ("RLOS", liuccsr.RLOS_n()),
+        ("QRPD", liuccsr.QRPD_n()),
+    ]))
+}
 
-    if let Some(alarm) = &s.alarm {
-        let aeisr = alarm.aeisr;
-        print!(" AEISR:[{}][{}][{}][{}][{}][{}][{}][{}]",
-            style("RXOOF").fg(color(aeisr.RxOOF_State())),
-            style("RXAIS").fg(color(aeisr.RxAIS_State())),
-            style("RXYEL").fg(color(aeisr.RxYEL_State())),
-            style("LOS").fg(color(aeisr.LOS_State())),
-            style("LCV").fg(color(aeisr.LCVInt_Status())),
-            style("RXOOFX").fg(color(aeisr.RxOOF_State_Change())),
-            style("RXAISX").fg(color(aeisr.RxAIS_State_Change())),
-            style("RXYELX").fg(color(aeisr.RxYEL_State_Change())),
-        );
-
-        let exzsr = alarm.exzsr;
-        print!(" EXZSR:[{}]",
-            style("EXZ").fg(color(exzsr.EXZ_STATUS())),
-        );
-
-        let ciasr = alarm.ciasr;
-        print!(" CIASR:[{}][{}]",
-            style("RAISCI").fg(color(ciasr.RxAIS_CI_state())),
-            style("RRAICI").fg(color(ciasr.RxRAI_CI_state())),
-        );
-    }
+pub fn sbisr_bits(sbisr: SBISR) -> String {
+    format!("SBISR:{}", bits(&[
+        ("TSBF", sbisr.TxSB_FULL()),
+        ("TSBE", sbisr.TxSB_EMPT()),
+        ("TSBS", sbisr.TxSB_SLIP()),
+        ("RSBF", sbisr.RxSB_FULL()),
+        ("RSBE", sbisr.RxSB_EMPT()),
+        ("RSBS", sbisr.RxSB_SLIP()),
+        ("SLC96LOCK", sbisr.SLC96_LOCK()),
+        ("MFLOCK", sbisr.Multiframe_LOCK()),
+    ]))
+}
 
-    if let Some(t1frame) = &s.t1frame {
-        let fisr = t1frame.fisr;
-        print!(" FISR:[{}][{}][{}][{}][{}][{}][{}][{}]",
-            style("DS0X").fg(color(fisr.DS0_Change())),
-            style("DS0S").fg(color(fisr.DS0_Status())),
-            style("SIG").fg(color(fisr.SIG())),
-            style("COFA").fg(color(fisr.COFA())),
-            style("OOFX").fg(color(fisr.OOF_Status())),
-            style("FMD").fg(color(fisr.FMD())),
-            style("SE").fg(color(fisr.SE())),
-            style("FE").fg(color(fisr.FE())),
-        );
-
-        if let Some(sig) = &t1frame.sig {
-            print!(" RSAR:[");
-            for rsar in sig.rsars {
-                let rsar_u8: u8 = rsar.into();
-                print!("{rsar_u8:1x}");
-            }
-            print!("]");
+pub fn fisr_bits(fisr: FISR) -> String {
+    format!("FISR:{}", bits(&[
+        ("DS0X", fisr.DS0_Change()),
+        ("DS0S", fisr.DS0_Status()),
+        ("SIG", fisr.SIG()),
+        ("COFA", fisr.COFA()),
+        ("OOFX", fisr.OOF_Status()),
+        ("FMD", fisr.FMD()),
+        ("SE", fisr.SE()),
+        ("FE", fisr.FE()),
+    ]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changes_only() {
+        let (sender, receiver) = crossbeam::channel::unbounded();
+        let mut handler = FramerInterruptHandler::new(sender, bounded(1).0);
+        let timestamp = Instant::now();
+
+        let mut packet = |rsar_0: u8| {
+            let bisr: u8 = BISR::new().with_ALARM(1).with_T1FRAME(1).into();
+            let fisr: u8 = FISR::new().with_SIG(1).into();
+            let mut packet = vec![2, bisr, 0x10, 0, 0, fisr, rsar_0 << 4];
+            packet.extend([0x55; 11]);
+            let status = FramerInterruptStatus::from_slice(&packet).unwrap();
+            handler.receive(&status, timestamp);
+            receiver.try_iter().collect::<Vec<_>>()
+        };
+
+        // Everything is news the first time around.
+        let events = packet(0b0101);
+        assert!(matches!(events[0], FramerEvent::Alarm { channel_index: 2, defects: Defects { los: true, .. }, .. }));
+        assert!(matches!(events[1], FramerEvent::Framing { channel_index: 2, .. }));
+        assert_eq!(events.len(), 2 + 24);
+
+        let events = packet(0b1111);
+        assert_eq!(events.len(), 2);
+        match events[1] {
+            FramerEvent::Signaling { address, old_abcd, new_abcd, .. } => {
+                assert_eq!(address, TimeslotAddress::new(2, 0));
+                assert_eq!(old_abcd, Some(0b0101));
+                assert_eq!(new_abcd, 0b1111);
+            },
+            e => panic!("unexpected {e:?}"),
         }
     }
-
-    println!();
 }
@@ -0,0 +1,481 @@
+//! Interrupt endpoint packets, as sent by the tedium-soc firmware main loop.
+//!
+//! A packet is the channel index, the channel's BISR, then the status of each
+//! source flagged in the BISR, in BISR bit order. The packet comes straight
+//! off the wire, so parsing must fail gracefully on anything at all.
+//!
+//! This module only depends on `register`, so the fuzz target can borrow it.
+
+use thiserror::Error;
+
+use super::register::*;
+
+/// Number of channels the firmware reports on.
+const CHANNELS_COUNT: usize = 8;
+
+/// Most received data link bytes the framer hands over at once (`RDLBC` is seven bits).
+pub const HDLC_CHUNK_BYTES_MAX: usize = 1 << 7;
+
+#[derive(Error, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum InterruptError {
+    #[error("packet ends in the middle of the status")]
+    Truncated,
+    #[error("{0} bytes left over after the status")]
+    TrailingBytes(usize),
+    #[error("channel {0} doesn't exist")]
+    Channel(u8),
+    #[error("recovered clock status {0:#04x} is neither lost nor restored")]
+    RecoveredClock(u8),
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct LoopbackCodeStatus {
+    pub rlcisrs: [RLCISRx; 8],
+}
+
+#[derive(Debug)]
+pub struct RecoveredClockStatus {
+    /// The receive framer has lost the recovered clock. Cleared when it returns.
+    pub lost: bool,
+}
+
+#[derive(Debug)]
+pub struct HDLCControllerStatus {
+    pub dlsr: DLSRx,
+    pub rdlbcr: RDLBCR,
+    pub data: Vec<u8>,
+    pub ss7sr: SS7SRx,
+}
+
+#[derive(Debug)]
+pub struct HDLCStatus {
+    pub controller: [HDLCControllerStatus; 3],
+}
+
+#[derive(Debug)]
+pub struct SlipStatus {
+    pub sbisr: SBISR,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct AlarmStatus {
+    pub aeisr: AEISR,
+    pub exzsr: EXZSR,
+    pub ciasr: CIASR,
+}
+
+#[derive(Debug)]
+pub struct T1FrameStatus {
+    pub fisr: FISR,
+    pub sig: Option<ReceiveSignalingStatus>,
+}
+
+#[derive(Debug)]
+pub struct ReceiveSignalingStatus {
+    pub rsars: [RSAR; 24],
+}
+
+#[derive(Debug)]
+pub struct FramerInterruptStatus {
+    pub channel_index: usize,
+    pub bisr: BISR,
+    pub lbcode: Option<LoopbackCodeStatus>,
+    pub clock: Option<RecoveredClockStatus>,
+    pub hdlc: Option<HDLCStatus>,
+    pub slip: Option<SlipStatus>,
+    pub alarm: Option<AlarmStatus>,
+    pub t1frame: Option<T1FrameStatus>,
+}
+
+///////////////////////////////////////////////////////////////////////
+
+type Result<T> = std::result::Result<T, InterruptError>;
+
+/// Hands out bytes from the front of a packet, refusing to go past the end.
+struct PacketReader<'a> {
+    remaining: &'a [u8],
+}
+
+impl<'a> PacketReader<'a> {
+    fn new(packet: &'a [u8]) -> Self {
+        Self {
+            remaining: packet,
+        }
+    }
+
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8]> {
+        if n > self.remaining.len() {
+            return Err(InterruptError::Truncated);
+        }
+        let (bytes, remaining) = self.remaining.split_at(n);
+        self.remaining = remaining;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.bytes(N)?.try_into().unwrap())
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        let [v] = self.array()?;
+        Ok(v)
+    }
+}
+
+impl FramerInterruptStatus {
+    pub fn from_slice(b: &[u8]) -> Result<Self> {
+        let mut r = PacketReader::new(b);
+        let result = Self::from_reader(&mut r)?;
+        match r.remaining.len() {
+            0 => Ok(result),
+            n => Err(InterruptError::TrailingBytes(n)),
+        }
+    }
+
+    fn from_reader(r: &mut PacketReader) -> Result<Self> {
+        let channel_index = r.u8()?;
+        if channel_index as usize >= CHANNELS_COUNT {
+            return Err(InterruptError::Channel(channel_index));
+        }
+        let channel_index = channel_index as usize;
+
+        let bisr = BISR::from(r.u8()?);
+
+        let lbcode = if bisr.LBCODE() != 0 {
+            Some(LoopbackCodeStatus::from_reader(r)?)
+        } else {
+            None
+        };
+
+        let clock = if bisr.RxClkLOS() != 0 {
+            Some(RecoveredClockStatus::from_reader(r)?)
+        } else {
+            None
+        };
+
+        // ONESEC carries no status, the BISR bit is all there is.
+
+        let hdlc = if bisr.HDLC() != 0 {
+            Some(HDLCStatus::from_reader(r)?)
+        } else {
+            None
+        };
+
+        let slip = if bisr.SLIP() != 0 {
+            Some(SlipStatus::from_reader(r)?)
+        } else {
+            None
+        };
+
+        let alarm = if bisr.ALARM() != 0 {
+            Some(AlarmStatus::from_reader(r)?)
+        } else {
+            None
+        };
+
+        let t1frame = if bisr.T1FRAME() != 0 {
+            Some(T1FrameStatus::from_reader(r)?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            channel_index,
+            bisr,
+            lbcode,
+            clock,
+            hdlc,
+            slip,
+            alarm,
+            t1frame,
+        })
+    }
+}
+
+impl LoopbackCodeStatus {
+    fn from_reader(r: &mut PacketReader) -> Result<Self> {
+        let rlcisrs = r.array::<8>()?.map(RLCISRx::from);
+
+        Ok(Self {
+            rlcisrs,
+        })
+    }
+}
+
+impl RecoveredClockStatus {
+    fn from_reader(r: &mut PacketReader) -> Result<Self> {
+        let lost = match r.u8()? {
+            0 => false,
+            1 => true,
+            v => return Err(InterruptError::RecoveredClock(v)),
+        };
+
+        Ok(Self {
+            lost,
+        })
+    }
+}
+
+impl HDLCStatus {
+    fn from_reader(r: &mut PacketReader) -> Result<Self> {
+        Ok(Self {
+            controller: [
+                HDLCControllerStatus::from_reader(r)?,
+                HDLCControllerStatus::from_reader(r)?,
+                HDLCControllerStatus::from_reader(r)?,
+            ],
+        })
+    }
+}
+
+impl HDLCControllerStatus {
+    fn from_reader(r: &mut PacketReader) -> Result<Self> {
+        let dlsr = DLSRx::from(r.u8()?);
+        let rdlbcr = RDLBCR::from(r.u8()?);
+
+        // The count came over the wire, so the reader has the final say on
+        // how many bytes there really are.
+        let data = r.bytes(rdlbcr.RDLBC() as usize)?.to_vec();
+
+        let ss7sr = SS7SRx::from(r.u8()?);
+
+        Ok(Self {
+            dlsr,
+            rdlbcr,
+            data,
+            ss7sr,
+        })
+    }
+}
+
+impl SlipStatus {
+    fn from_reader(r: &mut PacketReader) -> Result<Self> {
+        let sbisr = SBISR::from(r.u8()?);
+
+        Ok(Self {
+            sbisr,
+        })
+    }
+}
+
+impl AlarmStatus {
+    fn from_reader(r: &mut PacketReader) -> Result<Self> {
+        let [aeisr, exzsr, ciasr] = r.array()?;
+
+        Ok(Self {
+            aeisr: AEISR::from(aeisr),
+            exzsr: EXZSR::from(exzsr),
+            ciasr: CIASR::from(ciasr),
+        })
+    }
+}
+
+impl T1FrameStatus {
+    fn from_reader(r: &mut PacketReader) -> Result<Self> {
+        let fisr = FISR::from(r.u8()?);
+
+        let sig = if fisr.SIG() != 0 {
+            Some(ReceiveSignalingStatus::from_reader(r)?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            fisr,
+            sig,
+        })
+    }
+}
+
+impl ReceiveSignalingStatus {
+    fn from_reader(r: &mut PacketReader) -> Result<Self> {
+        let buffer = r.array::<12>()?;
+
+        let mut rsars = [RSAR::new(); 24];
+        for (i, &v) in buffer.iter().enumerate() {
+            let even = RSAR::from(v >> 4);
+            let odd = RSAR::from(v & 15);
+            rsars[i * 2] = even;
+            rsars[i * 2 + 1] = odd;
+        }
+
+        Ok(Self {
+            rsars,
+        })
+    }
+}
+
+///////////////////////////////////////////////////////////////////////
+
+/// Reference encoder, following the firmware's `usb_in_int.write_fifo` calls.
+#[cfg(any(test, fuzzing))]
+impl FramerInterruptStatus {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut w = vec![self.channel_index as u8, self.bisr.into()];
+
+        if let Some(lbcode) = &self.lbcode {
+            w.extend(lbcode.rlcisrs.map(u8::from));
+        }
+
+        if let Some(clock) = &self.clock {
+            w.push(clock.lost as u8);
+        }
+
+        if let Some(hdlc) = &self.hdlc {
+            for controller in &hdlc.controller {
+                w.push(controller.dlsr.into());
+                w.push(controller.rdlbcr.into());
+                w.extend_from_slice(&controller.data);
+                w.push(controller.ss7sr.into());
+            }
+        }
+
+        if let Some(slip) = &self.slip {
+            w.push(slip.sbisr.into());
+        }
+
+        if let Some(alarm) = &self.alarm {
+            w.extend_from_slice(&[alarm.aeisr.into(), alarm.exzsr.into(), alarm.ciasr.into()]);
+        }
+
+        if let Some(t1frame) = &self.t1frame {
+            w.push(t1frame.fisr.into());
+            if let Some(sig) = &t1frame.sig {
+                for pair in sig.rsars.chunks(2) {
+                    let even: u8 = pair[0].into();
+                    let odd: u8 = pair[1].into();
+                    w.push((even << 4) | (odd & 15));
+                }
+            }
+        }
+
+        w
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use proptest::array::uniform24;
+    use proptest::collection::vec;
+    use proptest::option;
+
+    use super::*;
+
+    #[test]
+    fn recovered_clock() {
+        let bisr: u8 = BISR::new().with_RxClkLOS(1).with_ONESEC(1).into();
+        let status = FramerInterruptStatus::from_slice(&[3, bisr, 1]).unwrap();
+        assert_eq!(status.channel_index, 3);
+        assert!(status.clock.unwrap().lost);
+        assert_eq!(status.bisr.ONESEC(), 1);
+
+        let bisr: u8 = BISR::new().with_RxClkLOS(1).with_SLIP(1).into();
+        let status = FramerInterruptStatus::from_slice(&[0, bisr, 0, 0x01]).unwrap();
+        assert!(!status.clock.unwrap().lost);
+        assert_eq!(status.slip.unwrap().sbisr.RxSB_SLIP(), 1);
+
+        let bisr: u8 = BISR::new().with_ONESEC(1).into();
+        let status = FramerInterruptStatus::from_slice(&[7, bisr]).unwrap();
+        assert!(status.clock.is_none());
+    }
+
+    #[test]
+    fn malformed() {
+        let bisr: u8 = BISR::new().with_SLIP(1).into();
+        assert_eq!(FramerInterruptStatus::from_slice(&[0, bisr, 0, 0xff]).unwrap_err(), InterruptError::TrailingBytes(1));
+        assert_eq!(FramerInterruptStatus::from_slice(&[8, bisr, 0]).unwrap_err(), InterruptError::Channel(8));
+        assert_eq!(FramerInterruptStatus::from_slice(&[]).unwrap_err(), InterruptError::Truncated);
+
+        // A byte count claiming more than the packet holds.
+        let bisr: u8 = BISR::new().with_HDLC(1).into();
+        let rdlbcr: u8 = RDLBCR::new().with_RDLBC(127).into();
+        assert_eq!(FramerInterruptStatus::from_slice(&[0, bisr, 0, rdlbcr, 0x7e]).unwrap_err(), InterruptError::Truncated);
+    }
+
+    fn register<T: From<u8> + std::fmt::Debug>() -> impl Strategy<Value = T> {
+        any::<u8>().prop_map(T::from)
+    }
+
+    fn hdlc_controller() -> impl Strategy<Value = HDLCControllerStatus> {
+        (register(), any::<bool>(), vec(any::<u8>(), 0..HDLC_CHUNK_BYTES_MAX), register())
+            .prop_map(|(dlsr, rbufptr, data, ss7sr)| HDLCControllerStatus {
+                dlsr,
+                rdlbcr: RDLBCR::new().with_RBUFPTR(rbufptr as u8).with_RDLBC(data.len() as u8),
+                data,
+                ss7sr,
+            })
+    }
+
+    fn t1frame() -> impl Strategy<Value = T1FrameStatus> {
+        (register::<FISR>(), uniform24(0u8..16))
+            .prop_map(|(fisr, abcds)| T1FrameStatus {
+                fisr,
+                sig: (fisr.SIG() != 0).then(|| ReceiveSignalingStatus { rsars: abcds.map(RSAR::from) }),
+            })
+    }
+
+    prop_compose! {
+        fn status()(
+            channel_index in 0..CHANNELS_COUNT,
+            onesec in any::<bool>(),
+            lbcode in option::of([any::<u8>(); 8]),
+            clock in option::of(any::<bool>()),
+            hdlc in option::of([hdlc_controller(), hdlc_controller(), hdlc_controller()]),
+            slip in option::of(register()),
+            alarm in option::of((register(), register(), register())),
+            t1frame in option::of(t1frame()),
+        ) -> FramerInterruptStatus {
+            let bisr = BISR::new()
+                .with_LBCODE(lbcode.is_some() as u8)
+                .with_RxClkLOS(clock.is_some() as u8)
+                .with_ONESEC(onesec as u8)
+                .with_HDLC(hdlc.is_some() as u8)
+                .with_SLIP(slip.is_some() as u8)
+                .with_ALARM(alarm.is_some() as u8)
+                .with_T1FRAME(t1frame.is_some() as u8);
+
+            FramerInterruptStatus {
+                channel_index,
+                bisr,
+                lbcode: lbcode.map(|rlcisrs| LoopbackCodeStatus { rlcisrs: rlcisrs.map(RLCISRx::from) }),
+                clock: clock.map(|lost| RecoveredClockStatus { lost }),
+                hdlc: hdlc.map(|controller| HDLCStatus { controller }),
+                slip: slip.map(|sbisr| SlipStatus { sbisr }),
+                alarm: alarm.map(|(aeisr, exzsr, ciasr)| AlarmStatus { aeisr, exzsr, ciasr }),
+                t1frame,
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn round_trip(status in status()) {
+            let packet = status.to_bytes();
+            let parsed = FramerInterruptStatus::from_slice(&packet).unwrap();
+            prop_assert_eq!(parsed.to_bytes(), packet);
+        }
+
+        #[test]
+        fn truncated(status in status()) {
+            let packet = status.to_bytes();
+            for length in 0..packet.len() {
+                prop_assert_eq!(FramerInterruptStatus::from_slice(&packet[0..length]).unwrap_err(), InterruptError::Truncated);
+            }
+        }
+
+        #[test]
+        fn trailing(status in status(), extra in vec(any::<u8>(), 1..16)) {
+            let mut packet = status.to_bytes();
+            packet.extend(&extra);
+            prop_assert_eq!(FramerInterruptStatus::from_slice(&packet).unwrap_err(), InterruptError::TrailingBytes(extra.len()));
+        }
+
+        #[test]
+        fn anything(packet in vec(any::<u8>(), 0..256)) {
+            if let Ok(status) = FramerInterruptStatus::from_slice(&packet) {
+                prop_assert_eq!(status.to_bytes(), packet);
+            }
+        }
+    }
+}
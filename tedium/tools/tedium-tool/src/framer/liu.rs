@@ -0,0 +1,273 @@
+use std::fmt;
+
+use super::device::*;
+use super::register::*;
+
+use xrt86vx38_pac::default;
+
+/// DSX-1 cable length from the LIU to the cross-connect, in the
+/// equalizer's 133 foot steps.
+///
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Dsx1Length {
+    Feet0To133,
+    Feet133To266,
+    Feet266To399,
+    Feet399To533,
+    Feet533To655,
+}
+
+/// Attenuation added to the transmit pulse for a CSU on a long-haul span,
+/// so it arrives at the network's next repeater at the expected level.
+///
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum CsuBuildOut {
+    Db0,
+    Db7_5,
+    Db15,
+    Db22_5,
+}
+
+/// The transmit pulse shape and receive equalizer, from LIUCCR0 EQC.
+///
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum LineBuildOut {
+    /// Short haul, to a DSX-1 cross-connect.
+    Dsx1(Dsx1Length),
+    /// Long haul, with the 36 dB receive equalizer.
+    Csu(CsuBuildOut),
+}
+
+impl LineBuildOut {
+    pub const ALL: [Self; 9] = [
+        Self::Dsx1(Dsx1Length::Feet0To133),
+        Self::Dsx1(Dsx1Length::Feet133To266),
+        Self::Dsx1(Dsx1Length::Feet266To399),
+        Self::Dsx1(Dsx1Length::Feet399To533),
+        Self::Dsx1(Dsx1Length::Feet533To655),
+        Self::Csu(CsuBuildOut::Db0),
+        Self::Csu(CsuBuildOut::Db7_5),
+        Self::Csu(CsuBuildOut::Db15),
+        Self::Csu(CsuBuildOut::Db22_5),
+    ];
+
+    fn eqc(&self) -> u8 {
+        match *self {
+            Self::Csu(CsuBuildOut::Db0)             => 0x00,
+            Self::Csu(CsuBuildOut::Db7_5)           => 0x01,
+            Self::Csu(CsuBuildOut::Db15)            => 0x02,
+            Self::Csu(CsuBuildOut::Db22_5)          => 0x03,
+            Self::Dsx1(Dsx1Length::Feet0To133)      => 0x08,
+            Self::Dsx1(Dsx1Length::Feet133To266)    => 0x09,
+            Self::Dsx1(Dsx1Length::Feet266To399)    => 0x0a,
+            Self::Dsx1(Dsx1Length::Feet399To533)    => 0x0b,
+            Self::Dsx1(Dsx1Length::Feet533To655)    => 0x0c,
+        }
+    }
+
+    /// `None` for the EQC codes we don't set: E1, 45 dB receive, arbitrary pulse and so on.
+    fn from_eqc(eqc: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|l| l.eqc() == eqc)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Self::Dsx1(Dsx1Length::Feet0To133)      => "dsx-133",
+            Self::Dsx1(Dsx1Length::Feet133To266)    => "dsx-266",
+            Self::Dsx1(Dsx1Length::Feet266To399)    => "dsx-399",
+            Self::Dsx1(Dsx1Length::Feet399To533)    => "dsx-533",
+            Self::Dsx1(Dsx1Length::Feet533To655)    => "dsx-655",
+            Self::Csu(CsuBuildOut::Db0)             => "csu-0",
+            Self::Csu(CsuBuildOut::Db7_5)           => "csu-7.5",
+            Self::Csu(CsuBuildOut::Db15)            => "csu-15",
+            Self::Csu(CsuBuildOut::Db22_5)          => "csu-22.5",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|l| l.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// Which path the jitter attenuator sits in.
+///
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum JitterAttenuator {
+    Disabled,
+    /// Smooths the recovered clock, for loop timing from a noisy span.
+    Receive,
+    /// Smooths TxSERCLK, for a jittery transmit clock.
+    Transmit,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum JitterAttenuatorBandwidth {
+    Wide,
+    /// Attenuates more jitter, but follows wander more slowly.
+    Narrow,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum JitterAttenuatorFifo {
+    Bits32,
+    Bits64,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct LiuTermination {
+    pub receive: Termination,
+    pub transmit: Termination,
+    pub impedance: TerminationImpedance,
+}
+
+/// A channel's line interface setup, from LIUCCR0 and LIUCCR1.
+///
+/// The default is what `configure_channel` in `tedium-soc` sets at startup,
+/// from the LIU defaults in `xrt86vx38_pac::default`: DSX-1 up to 133 feet,
+/// receive jitter attenuator, 100 ohms internal termination.
+///
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct LiuConfig {
+    pub line_build_out: LineBuildOut,
+    pub jitter_attenuator: JitterAttenuator,
+    pub jitter_attenuator_bandwidth: JitterAttenuatorBandwidth,
+    pub jitter_attenuator_fifo: JitterAttenuatorFifo,
+    pub termination: LiuTermination,
+}
+
+impl Default for LiuConfig {
+    fn default() -> Self {
+        let liuccr0 = LIUCCR0::new().with_EQC(default::LIU_EQC);
+        let liuccr1 = LIUCCR1::from(default::LIU_LIUCCR1);
+        Self::from_registers(liuccr0, liuccr1).expect("LIU defaults")
+    }
+}
+
+impl LiuConfig {
+    /// `None` if EQC holds something `LineBuildOut` doesn't cover.
+    fn from_registers(liuccr0: LIUCCR0, liuccr1: LIUCCR1) -> Option<Self> {
+        // With both selects set, the receive path wins.
+        let jitter_attenuator = match (liuccr1.RxJASEL_n(), liuccr1.TxJASEL_n()) {
+            (0, 0) => JitterAttenuator::Disabled,
+            (0, _) => JitterAttenuator::Transmit,
+            _ => JitterAttenuator::Receive,
+        };
+
+        Some(Self {
+            line_build_out: LineBuildOut::from_eqc(liuccr0.EQC())?,
+            jitter_attenuator,
+            jitter_attenuator_bandwidth: if liuccr1.JABW_n() != 0 { JitterAttenuatorBandwidth::Narrow } else { JitterAttenuatorBandwidth::Wide },
+            jitter_attenuator_fifo: if liuccr1.FIFOS_n() != 0 { JitterAttenuatorFifo::Bits64 } else { JitterAttenuatorFifo::Bits32 },
+            termination: LiuTermination {
+                receive: liuccr1.RXTSEL_n(),
+                transmit: liuccr1.TXTSEL_n(),
+                impedance: liuccr1.TERSEL(),
+            },
+        })
+    }
+
+    fn liuccr1(&self) -> LIUCCR1 {
+        // Receive sets both selects, as `configure_channel` always has.
+        let (rx, tx) = match self.jitter_attenuator {
+            JitterAttenuator::Disabled => (0, 0),
+            JitterAttenuator::Receive => (1, 1),
+            JitterAttenuator::Transmit => (0, 1),
+        };
+
+        LIUCCR1::new()
+            .with_RXTSEL_n(self.termination.receive)
+            .with_TXTSEL_n(self.termination.transmit)
+            .with_TERSEL(self.termination.impedance)
+            .with_RxJASEL_n(rx)
+            .with_TxJASEL_n(tx)
+            .with_JABW_n((self.jitter_attenuator_bandwidth == JitterAttenuatorBandwidth::Narrow) as u8)
+            .with_FIFOS_n((self.jitter_attenuator_fifo == JitterAttenuatorFifo::Bits64) as u8)
+    }
+}
+
+impl fmt::Display for LiuConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let termination = &self.termination;
+        write!(f, "{} ja={:?}/{:?}/{:?} rx={:?} tx={:?} {:?}",
+            self.line_build_out.name(),
+            self.jitter_attenuator, self.jitter_attenuator_bandwidth, self.jitter_attenuator_fifo,
+            termination.receive, termination.transmit, termination.impedance,
+        )
+    }
+}
+
+/// Change a channel's line interface setup, leaving PRBS, test and
+/// loopback settings alone.
+pub fn liu_configure(channel: &Channel, config: &LiuConfig) -> Result<()> {
+    channel.liuccr0().modify(|m| m
+        .with_EQC(config.line_build_out.eqc())
+    )?;
+    channel.liuccr1().write(|_| config.liuccr1())
+}
+
+pub struct LiuStatus {
+    /// `None` if the LIU is set up in a way `LiuConfig` can't describe.
+    pub config: Option<LiuConfig>,
+    pub eqc: u8,
+    pub receiver_on: bool,
+    pub transmitter_on: bool,
+    pub liuccsr: LIUCCSR,
+    /// Receive cable loss, in dB.
+    pub cable_loss: u8,
+}
+
+pub fn liu_status(channel: &Channel) -> Result<LiuStatus> {
+    let liuccr0 = channel.liuccr0().read()?;
+    let liuccr1 = channel.liuccr1().read()?;
+    Ok(LiuStatus {
+        config: LiuConfig::from_registers(liuccr0, liuccr1),
+        eqc: liuccr0.EQC(),
+        receiver_on: liuccr0.RXON_n() != 0,
+        transmitter_on: channel.liuccr2().read()?.TXON_n() != 0,
+        liuccsr: channel.liuccsr().read()?,
+        cable_loss: channel.liuccccr().read()?.CLOS(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_build_out() {
+        for lbo in LineBuildOut::ALL {
+            assert_eq!(LineBuildOut::from_eqc(lbo.eqc()), Some(lbo));
+            assert_eq!(LineBuildOut::from_name(lbo.name()), Some(lbo));
+        }
+        // 45 dB receive equalizer.
+        assert_eq!(LineBuildOut::from_eqc(0x04), None);
+    }
+
+    #[test]
+    fn registers() {
+        // What `configure_channel` writes.
+        let liuccr0 = LIUCCR0::new().with_RXON_n(1).with_EQC(0x08);
+        let liuccr1 = LIUCCR1::new()
+            .with_RXTSEL_n(Termination::Internal)
+            .with_TXTSEL_n(Termination::Internal)
+            .with_TERSEL(TerminationImpedance::Ohms100)
+            .with_RxJASEL_n(1)
+            .with_TxJASEL_n(1);
+        assert_eq!(LiuConfig::from_registers(liuccr0, liuccr1), Some(LiuConfig::default()));
+        assert_eq!(u8::from(LiuConfig::default().liuccr1()), u8::from(liuccr1));
+
+        let config = LiuConfig {
+            line_build_out: LineBuildOut::Csu(CsuBuildOut::Db15),
+            jitter_attenuator: JitterAttenuator::Transmit,
+            jitter_attenuator_bandwidth: JitterAttenuatorBandwidth::Narrow,
+            jitter_attenuator_fifo: JitterAttenuatorFifo::Bits64,
+            termination: LiuTermination {
+                receive: Termination::HighImpedance,
+                transmit: Termination::Internal,
+                impedance: TerminationImpedance::Ohms110,
+            },
+        };
+        let liuccr0 = LIUCCR0::new().with_EQC(config.line_build_out.eqc());
+        assert_eq!(LiuConfig::from_registers(liuccr0, config.liuccr1()), Some(config));
+    }
+}
@@ -0,0 +1,357 @@
+use std::time::{Duration, Instant};
+
+use super::device::{Channel, Result};
+use super::fdl::BitOrientedCode;
+use super::interrupt::LoopbackCodeStatus;
+use super::register::*;
+
+/// In-band loopback codes, per ANSI T1.403: a short pattern repeated in
+/// place of the payload for as long as the request is being made.
+///
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum InbandLoopbackCode {
+    /// Repeating `00001`.
+    LoopUp,
+    /// Repeating `001`.
+    LoopDown,
+}
+
+impl InbandLoopbackCode {
+    /// The framer's code registers hold four to seven bits, left-justified.
+    /// `001` is too short, so it goes in doubled.
+    fn pattern(&self) -> (u8, u8) {
+        match self {
+            Self::LoopUp   => (0b000_0100, 5),
+            Self::LoopDown => (0b001_0010, 6),
+        }
+    }
+
+    /// Code length, as the LCCR length fields encode it.
+    fn length_code(&self) -> u8 {
+        self.pattern().1 - 4
+    }
+}
+
+/// Bit-oriented loopback commands are sent as a burst of at least ten codewords.
+pub const FDL_COMMAND_REPETITIONS: u8 = 10;
+
+/// Start sending an in-band loopback code. The code replaces the payload, but
+/// leaves the framing alone, until `inband_code_stop`.
+pub fn inband_code_start(channel: &Channel, code: InbandLoopbackCode) -> Result<()> {
+    channel.lccr0().modify(|m| m
+        .with_TXLBCLEN(code.length_code())
+        .with_FRAMED(1)
+    )?;
+    channel.tlcr().write(|w| w
+        .with_TXLBC(code.pattern().0)
+        .with_TXLBCENB(1)
+    )
+}
+
+pub fn inband_code_stop(channel: &Channel) -> Result<()> {
+    channel.tlcr().modify(|m| m
+        .with_TXLBCENB(0)
+    )
+}
+
+/// Send a bit-oriented message on the ESF facility data link, `repetitions`
+/// times. The framer sends it between HDLC frames.
+pub fn fdl_code_send(channel: &Channel, code: BitOrientedCode, repetitions: u8) -> Result<()> {
+    channel.tfdlr().write(|w| w
+        .with_TBOC(code.code() >> 1)
+    )?;
+    channel.tbcr().write(|w| w
+        .with_TBCR(repetitions)
+    )?;
+    channel.boccr().modify(|m| m
+        .with_SBOC(1)
+    )
+}
+
+/// Watch for in-band loop-up and loop-down codes on receive loopback code
+/// detector 0. The framer is left to detect the codes but not to act on
+/// them; `LoopbackResponder` decides what to do.
+pub fn configure_loopback_code_detection(channel: &Channel) -> Result<()> {
+    let up = InbandLoopbackCode::LoopUp;
+    let down = InbandLoopbackCode::LoopDown;
+
+    channel.lccr0().modify(|m| m
+        .with_RXLBCALEN(up.length_code())
+        .with_RXLBCDLEN(down.length_code())
+        .with_AUTOENB(0)
+    )?;
+    channel.rlacr0().write(|w| w
+        .with_RXLBAC(up.pattern().0)
+        .with_RXLBACENB(1)
+    )?;
+    channel.rldcr0().write(|w| w
+        .with_RXLBDC(down.pattern().0)
+        .with_RXLBDCENB(1)
+    )
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Loopback {
+    /// Everything received is sent back, framing and all.
+    Line,
+    /// The received payload is sent back, in freshly generated framing.
+    Payload,
+}
+
+/// Registers loopbacks and test modes change, as found beforehand, so the
+/// channel can be put back the way it was.
+///
+#[derive(Copy, Clone, Debug)]
+pub struct LoopbackSavedState {
+    licr: LICR,
+    csr: CSR,
+    liuccr2: LIUCCR2,
+    tscrs: [TSCR; 24],
+}
+
+pub fn loopback_save(channel: &Channel) -> Result<LoopbackSavedState> {
+    let mut tscrs = [TSCR::new(); 24];
+    for (tscr, timeslot) in tscrs.iter_mut().zip(channel.timeslots()) {
+        *tscr = timeslot.tscr().read()?;
+    }
+
+    Ok(LoopbackSavedState {
+        licr: channel.licr().read()?,
+        csr: channel.csr().read()?,
+        liuccr2: channel.liuccr2().read()?,
+        tscrs,
+    })
+}
+
+pub fn loopback_restore(channel: &Channel, saved: &LoopbackSavedState) -> Result<()> {
+    channel.liuccr2().write(|_| saved.liuccr2)?;
+    channel.licr().write(|_| saved.licr)?;
+    channel.csr().write(|_| saved.csr)?;
+    for (tscr, timeslot) in saved.tscrs.iter().zip(channel.timeslots()) {
+        timeslot.tscr().write(|_| *tscr)?;
+    }
+    Ok(())
+}
+
+pub fn loopback_enter(channel: &Channel, loopback: Loopback) -> Result<LoopbackSavedState> {
+    let saved = loopback_save(channel)?;
+
+    match loopback {
+        Loopback::Line => {
+            channel.licr().modify(|m| m
+                .with_LB(FramerLoopback::FarEndRemoteLine)
+            )?;
+        },
+        Loopback::Payload => {
+            channel.licr().modify(|m| m
+                .with_LB(FramerLoopback::Payload)
+            )?;
+
+            // Transmit timing has to follow the received payload.
+            channel.csr().modify(|m| m
+                .with_CSS(ClockSource::Loop)
+            )?;
+
+            // Send back the received robbed bits rather than our own.
+            for timeslot in channel.timeslots() {
+                timeslot.tscr().modify(|m| m
+                    .with_Rob_Enb(0)
+                    .with_TxSIGSRC(ChannelSignalingSource::PCMData)
+                )?;
+            }
+        },
+    }
+
+    Ok(saved)
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum LoopbackAction {
+    Enter(Loopback),
+    Leave,
+}
+
+/// Which far-end loopback requests to honor.
+///
+#[derive(Copy, Clone, Debug)]
+pub struct LoopbackPolicy {
+    pub line: bool,
+    pub payload: bool,
+    /// Leave a loopback by ourselves after this long, in case the far end
+    /// never sends the loop-down.
+    pub timeout: Option<Duration>,
+}
+
+impl Default for LoopbackPolicy {
+    fn default() -> Self {
+        Self {
+            line: true,
+            payload: true,
+            timeout: None,
+        }
+    }
+}
+
+/// Decides when to enter and leave loopback in response to codes from the
+/// far end, the way network interface units do.
+///
+/// In-band codes must be present for five seconds before they're acted on,
+/// since payload can contain them by chance. FDL bit-oriented messages have
+/// already been validated by the time they get here, so they take effect
+/// right away.
+///
+pub struct LoopbackResponder {
+    policy: LoopbackPolicy,
+    active: Option<(Loopback, Instant)>,
+    inband_activate: Option<Instant>,
+    inband_deactivate: Option<Instant>,
+}
+
+impl LoopbackResponder {
+    const INBAND_VALIDATION: Duration = Duration::from_secs(5);
+
+    pub fn new(policy: LoopbackPolicy) -> Self {
+        Self {
+            policy,
+            active: None,
+            inband_activate: None,
+            inband_deactivate: None,
+        }
+    }
+
+    pub fn active(&self) -> Option<Loopback> {
+        self.active.map(|(loopback, _)| loopback)
+    }
+
+    /// Account for a change in the receive loopback code detector status.
+    pub fn inband_status(&mut self, status: &LoopbackCodeStatus, now: Instant) -> Option<LoopbackAction> {
+        let rlcisr = &status.rlcisrs[0];
+        let update = |since: &mut Option<Instant>, present: bool| {
+            *since = match (present, *since) {
+                (true, None) => Some(now),
+                (true, since) => since,
+                (false, _) => None,
+            };
+        };
+        update(&mut self.inband_activate, rlcisr.RXASTAT() != 0);
+        update(&mut self.inband_deactivate, rlcisr.RXDSTAT() != 0);
+
+        self.tick(now)
+    }
+
+    pub fn bit_oriented_message(&mut self, code: BitOrientedCode, now: Instant) -> Option<LoopbackAction> {
+        match code {
+            BitOrientedCode::LineLoopbackActivate        => self.activate(Loopback::Line, now),
+            BitOrientedCode::PayloadLoopbackActivate     => self.activate(Loopback::Payload, now),
+            BitOrientedCode::LineLoopbackDeactivate      => self.deactivate(Some(Loopback::Line)),
+            BitOrientedCode::PayloadLoopbackDeactivate   => self.deactivate(Some(Loopback::Payload)),
+            BitOrientedCode::UniversalLoopbackDeactivate => self.deactivate(None),
+            _ => None,
+        }
+    }
+
+    pub fn tick(&mut self, now: Instant) -> Option<LoopbackAction> {
+        let validated = |since: &mut Option<Instant>| {
+            let validated = since.is_some_and(|since| now.duration_since(since) >= Self::INBAND_VALIDATION);
+            if validated {
+                // Act once per appearance of the code.
+                *since = None;
+            }
+            validated
+        };
+
+        if validated(&mut self.inband_deactivate) {
+            if let Some(action) = self.deactivate(Some(Loopback::Line)) {
+                return Some(action);
+            }
+        }
+        if validated(&mut self.inband_activate) {
+            if let Some(action) = self.activate(Loopback::Line, now) {
+                return Some(action);
+            }
+        }
+
+        if let (Some((_, entered)), Some(timeout)) = (self.active, self.policy.timeout) {
+            if now.duration_since(entered) >= timeout {
+                return self.deactivate(None);
+            }
+        }
+
+        None
+    }
+
+    fn activate(&mut self, loopback: Loopback, now: Instant) -> Option<LoopbackAction> {
+        let allowed = match loopback {
+            Loopback::Line    => self.policy.line,
+            Loopback::Payload => self.policy.payload,
+        };
+        if !allowed || self.active() == Some(loopback) {
+            return None;
+        }
+        // A different loopback replaces the current one; the caller leaves
+        // the old one as part of entering the new one.
+        self.active = Some((loopback, now));
+        Some(LoopbackAction::Enter(loopback))
+    }
+
+    /// Leave `loopback`, or whatever loopback is active if `None`.
+    fn deactivate(&mut self, loopback: Option<Loopback>) -> Option<LoopbackAction> {
+        match self.active() {
+            Some(active) if loopback.is_none_or(|l| l == active) => {
+                self.active = None;
+                Some(LoopbackAction::Leave)
+            },
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(activate: bool, deactivate: bool) -> LoopbackCodeStatus {
+        let mut rlcisrs = [RLCISRx::new(); 8];
+        rlcisrs[0] = RLCISRx::new()
+            .with_RXASTAT(activate as u8)
+            .with_RXDSTAT(deactivate as u8);
+        LoopbackCodeStatus { rlcisrs }
+    }
+
+    #[test]
+    fn inband_codes_need_five_seconds() {
+        let mut dut = LoopbackResponder::new(LoopbackPolicy::default());
+        let t0 = Instant::now();
+        let s = |n| t0 + Duration::from_secs(n);
+
+        assert_eq!(dut.inband_status(&status(true, false), t0), None);
+        assert_eq!(dut.tick(s(4)), None);
+        // A brief dropout restarts validation.
+        assert_eq!(dut.inband_status(&status(false, false), s(4)), None);
+        assert_eq!(dut.inband_status(&status(true, false), s(5)), None);
+        assert_eq!(dut.tick(s(9)), None);
+        assert_eq!(dut.tick(s(10)), Some(LoopbackAction::Enter(Loopback::Line)));
+        assert_eq!(dut.tick(s(20)), None);
+
+        assert_eq!(dut.inband_status(&status(false, true), s(30)), None);
+        assert_eq!(dut.tick(s(35)), Some(LoopbackAction::Leave));
+        assert_eq!(dut.active(), None);
+    }
+
+    #[test]
+    fn fdl_commands_and_policy() {
+        let policy = LoopbackPolicy { payload: false, timeout: Some(Duration::from_secs(60)), ..Default::default() };
+        let mut dut = LoopbackResponder::new(policy);
+        let t0 = Instant::now();
+
+        assert_eq!(dut.bit_oriented_message(BitOrientedCode::PayloadLoopbackActivate, t0), None);
+        assert_eq!(dut.bit_oriented_message(BitOrientedCode::LineLoopbackActivate, t0), Some(LoopbackAction::Enter(Loopback::Line)));
+        // Deactivating a loopback we're not in does nothing.
+        assert_eq!(dut.bit_oriented_message(BitOrientedCode::PayloadLoopbackDeactivate, t0), None);
+        assert_eq!(dut.bit_oriented_message(BitOrientedCode::UniversalLoopbackDeactivate, t0), Some(LoopbackAction::Leave));
+
+        assert_eq!(dut.bit_oriented_message(BitOrientedCode::LineLoopbackActivate, t0), Some(LoopbackAction::Enter(Loopback::Line)));
+        assert_eq!(dut.tick(t0 + Duration::from_secs(59)), None);
+        assert_eq!(dut.tick(t0 + Duration::from_secs(60)), Some(LoopbackAction::Leave));
+    }
+}
@@ -0,0 +1,611 @@
+use std::fmt;
+use std::str::FromStr;
+
+use modular_bitfield_msb::prelude::*;
+use thiserror::Error;
+
+use super::device::{Addressing, Channels, Timeslots};
+use super::register::*;
+
+/// Where a register lives in the "uP" interface memory map, and so how many
+/// instances of it there are.
+///
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Scope {
+    /// One per device, at a fixed address.
+    Global(usize),
+    /// One per device, at 0x0fex.
+    LiuGlobal(usize),
+    /// One per channel, at 0xNxxx.
+    Channel(usize),
+    /// One per channel, at 0x0fNx.
+    LiuChannel(usize),
+    /// One per timeslot, in a block at 0xNxxx.
+    Timeslot(usize),
+    /// One per timeslot, with the RDS0MR gap.
+    Rds0mr,
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum RegisterMapError {
+    #[error("no register named {0:?}")]
+    UnknownRegister(String),
+    #[error("{0:?} isn't `name`, `name@channel` or `name@channel.timeslot`")]
+    Malformed(String),
+    #[error("{register} takes {expected}")]
+    Instance { register: &'static str, expected: &'static str },
+    #[error("no register definition at {0:#06x}")]
+    UnknownAddress(u16),
+    #[error("{register} has no field {field:?}")]
+    UnknownField { register: &'static str, field: String },
+    #[error("{field} can't be {value:?}")]
+    FieldValue { field: String, value: String },
+}
+
+impl Scope {
+    pub fn per_channel(&self) -> bool {
+        !matches!(self, Self::Global(_) | Self::LiuGlobal(_))
+    }
+
+    pub fn per_timeslot(&self) -> bool {
+        matches!(self, Self::Timeslot(_) | Self::Rds0mr)
+    }
+}
+
+/// One bitfield of a register definition.
+///
+pub struct Field {
+    /// `None` for reserved bits.
+    name: Option<&'static str>,
+    bits: usize,
+    decode: fn(u8) -> String,
+}
+
+impl Field {
+    const fn new<T>(name: Option<&'static str>) -> Self
+        where T: Specifier<Bytes = u8>, T::InOut: fmt::Debug
+    {
+        Self { name, bits: T::BITS, decode: decode::<T> }
+    }
+}
+
+/// A field's raw value as its specifier names it: a number, or an enum variant.
+fn decode<T>(raw: u8) -> String
+    where T: Specifier<Bytes = u8>, T::InOut: fmt::Debug
+{
+    match T::from_bytes(raw) {
+        Ok(value) => format!("{value:?}"),
+        Err(_) => format!("Invalid({raw})"),
+    }
+}
+
+/// The fields of a register definition, most significant first. `build.rs`
+/// implements this for every bitfield in `register.rs`.
+trait RegisterFields {
+    const FIELDS: &'static [Field];
+}
+
+include!(concat!(env!("OUT_DIR"), "/register_fields.rs"));
+
+/// Registers that are a plain count have no fields.
+impl RegisterFields for u8 {
+    const FIELDS: &'static [Field] = &[];
+}
+
+pub struct Register {
+    pub name: &'static str,
+    pub scope: Scope,
+    /// Status registers and PMON counters that reset when read.
+    pub clear_on_read: bool,
+    fields: &'static [Field],
+}
+
+impl Register {
+    pub fn by_name(name: &str) -> Option<&'static Register> {
+        REGISTERS.iter().find(|r| r.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Named fields with their shift and mask within the register.
+    fn layout(&self) -> impl Iterator<Item = (&'static str, &'static Field, u32, u8)> {
+        let mut shift = 8;
+        self.fields.iter().filter_map(move |field| {
+            shift -= field.bits as u32;
+            let mask = (((1u16 << field.bits) - 1) as u8) << shift;
+            field.name.map(|name| (name, field, shift, mask))
+        })
+    }
+
+    /// Bitfields of `value`, by name, as the register definition decodes
+    /// them. Registers that are a plain count have no fields.
+    pub fn fields(&self, value: u8) -> Vec<(String, String)> {
+        self.layout()
+            .map(|(name, field, shift, mask)| (name.to_string(), (field.decode)((value & mask) >> shift)))
+            .collect()
+    }
+
+    /// `value` with `field` set to `text`: a number, or the name of one of
+    /// the field's enumerated values.
+    pub fn with_field(&self, value: u8, field: &str, text: &str) -> Result<u8, RegisterMapError> {
+        let (_, definition, shift, mask) = self.layout()
+            .find(|(name, ..)| name.eq_ignore_ascii_case(field))
+            .ok_or_else(|| RegisterMapError::UnknownField { register: self.name, field: field.into() })?;
+        let max = mask >> shift;
+
+        let raw = match parse_number(text) {
+            Some(n) if n <= max as u32 => Some(n as u8),
+            Some(_) => None,
+            None => (0..=max).find(|&raw| (definition.decode)(raw).eq_ignore_ascii_case(text)),
+        };
+
+        raw.map(|raw| (value & !mask) | (raw << shift))
+            .ok_or_else(|| RegisterMapError::FieldValue { field: field.into(), value: text.into() })
+    }
+}
+
+impl fmt::Debug for Register {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Register")
+            .field("name", &self.name)
+            .field("scope", &self.scope)
+            .finish()
+    }
+}
+
+macro_rules! registers {
+    (@clear_on_read) => { false };
+    (@clear_on_read clear_on_read) => { true };
+    ($($name:ident: $scope:ident $(($offset:expr))? => $type:ty $([$clear:ident])?,)*) => {
+        /// Every register `Device`, `Channel` and `Timeslot` give access to,
+        /// except the LAPD buffers.
+        pub static REGISTERS: &[Register] = &[
+            $(Register {
+                name: stringify!($name),
+                scope: Scope::$scope $(($offset))?,
+                clear_on_read: registers!(@clear_on_read $($clear)?),
+                fields: <$type as RegisterFields>::FIELDS,
+            },)*
+        ];
+    };
+}
+
+registers! {
+    // Timeslot
+    rds0mr:   Rds0mr            => RDS0MR,
+    tds0mr:   Timeslot(0x1d0)   => TDS0MR,
+    tccr:     Timeslot(0x300)   => TCCR,
+    tucr:     Timeslot(0x320)   => TUCR,
+    tscr:     Timeslot(0x340)   => TSCR,
+    rccr:     Timeslot(0x360)   => RCCR,
+    rucr:     Timeslot(0x380)   => RUCR,
+    rscr:     Timeslot(0x3a0)   => RSCtR,
+    rssr:     Timeslot(0x3c0)   => RSSR,
+    rsar:     Timeslot(0x500)   => RSAR,
+
+    // Framer
+    csr:      Channel(0x100)    => CSR,
+    licr:     Channel(0x101)    => LICR,
+    fsr:      Channel(0x107)    => FSR,
+    agr:      Channel(0x108)    => AGR,
+    smr:      Channel(0x109)    => SMR,
+    tsdlsr:   Channel(0x10a)    => TSDLSR,
+    fcr:      Channel(0x10b)    => FCR,
+    rsdlsr:   Channel(0x10c)    => RSDLSR,
+    rscr0:    Channel(0x10d)    => RSChR,
+    rscr1:    Channel(0x10e)    => RSChR,
+    rscr2:    Channel(0x10f)    => RSChR,
+    rifr:     Channel(0x112)    => RIFR,
+    dlcr1:    Channel(0x113)    => DLCR,
+    tdlbcr1:  Channel(0x114)    => TDLBCR,
+    rdlbcr1:  Channel(0x115)    => RDLBCR,
+    sbcr:     Channel(0x116)    => SBCR,
+    fifolr:   Channel(0x117)    => FIFOLR,
+    icr:      Channel(0x11a)    => ICR,
+    lapdsr:   Channel(0x11b)    => LAPDSR,
+    ciagr:    Channel(0x11c)    => CIAGR,
+    prcr:     Channel(0x11d)    => PRCR,
+    gccr:     Channel(0x11e)    => GCCR,
+    ticr:     Channel(0x120)    => TICR,
+    bertcsr0: Channel(0x121)    => BERTCSR0,
+    ricr:     Channel(0x122)    => RICR,
+    bertcsr1: Channel(0x123)    => BERTCSR1,
+    lccr0:    Channel(0x124)    => LCCR0,
+    tlcr:     Channel(0x125)    => TLCR,
+    rlacr0:   Channel(0x126)    => RLACR,
+    rldcr0:   Channel(0x127)    => RLDCR,
+    rlcds:    Channel(0x128)    => RLCDS,
+    dder:     Channel(0x129)    => DDER,
+    lccr1:    Channel(0x12a)    => LCCR,
+    rlacr1:   Channel(0x12b)    => RLACR,
+    rldcr1:   Channel(0x12c)    => RLDCR,
+    lccr2:    Channel(0x12d)    => LCCR,
+    rlacr2:   Channel(0x12e)    => RLACR,
+    rldcr2:   Channel(0x12f)    => RLDCR,
+    tlcgs:    Channel(0x140)    => TLCGS,
+    lcts:     Channel(0x141)    => LCTS,
+    tsprmcr:  Channel(0x142)    => TSPRMCR,
+    dlcr2:    Channel(0x143)    => DLCR,
+    tdlbcr2:  Channel(0x144)    => TDLBCR,
+    rdlbcr2:  Channel(0x145)    => RDLBCR,
+    lccr3:    Channel(0x146)    => LCCR,
+    rlacr3:   Channel(0x147)    => RLACR,
+    rldcr3:   Channel(0x148)    => RLDCR,
+    lccr4:    Channel(0x149)    => LCCR,
+    rlacr4:   Channel(0x14a)    => RLACR,
+    rldcr4:   Channel(0x14b)    => RLDCR,
+    lccr5:    Channel(0x14c)    => LCCR,
+    rlacr5:   Channel(0x14d)    => RLACR,
+    rldcr5:   Channel(0x14e)    => RLDCR,
+    lccr6:    Channel(0x14f)    => LCCR,
+    rlacr6:   Channel(0x150)    => RLACR,
+    rldcr6:   Channel(0x151)    => RLDCR,
+    dlcr3:    Channel(0x153)    => DLCR,
+    tdlbcr3:  Channel(0x154)    => TDLBCR,
+    rdlbcr3:  Channel(0x155)    => RDLBCR,
+    lccr7:    Channel(0x156)    => LCCR,
+    rlacr7:   Channel(0x157)    => RLACR,
+    rldcr7:   Channel(0x158)    => RLDCR,
+    bcr:      Channel(0x163)    => BCR,
+    boccr:    Channel(0x170)    => BOCCR,
+    rfdlr:    Channel(0x171)    => RFDLR,
+    rfdlmr1:  Channel(0x172)    => RFDLMR,
+    rfdlmr2:  Channel(0x173)    => RFDLMR,
+    rfdlmr3:  Channel(0x174)    => RFDLMR,
+    tfdlr:    Channel(0x175)    => TFDLR,
+    tbcr:     Channel(0x176)    => TBCR,
+
+    // Performance monitoring
+    rlcvcu:   Channel(0x900)    => RLCVCU [clear_on_read],
+    rlcvcl:   Channel(0x901)    => RLCVCL [clear_on_read],
+    rfaecu:   Channel(0x902)    => RFAECU [clear_on_read],
+    rfaecl:   Channel(0x903)    => RFAECL [clear_on_read],
+    rsefc:    Channel(0x904)    => RSEFC [clear_on_read],
+    rsbbecu:  Channel(0x905)    => RSBBECU [clear_on_read],
+    rsbbecl:  Channel(0x906)    => RSBBECL [clear_on_read],
+    rsc:      Channel(0x909)    => RSC [clear_on_read],
+    rlfc:     Channel(0x90a)    => RLFC [clear_on_read],
+    rcfac:    Channel(0x90b)    => RCFAC [clear_on_read],
+    lfcsec1:  Channel(0x90c)    => LFCSEC1 [clear_on_read],
+    pbecu:    Channel(0x90d)    => PBECU [clear_on_read],
+    pbecl:    Channel(0x90e)    => PBECL [clear_on_read],
+    tsc:      Channel(0x90f)    => TSC [clear_on_read],
+    ezvcu:    Channel(0x910)    => EZVCU [clear_on_read],
+    ezvcl:    Channel(0x911)    => EZVCL [clear_on_read],
+    lfcsec2:  Channel(0x91c)    => LFCSEC2 [clear_on_read],
+    lfcsec3:  Channel(0x92c)    => LFCSEC3 [clear_on_read],
+
+    // Interrupt
+    bisr:     Channel(0xb00)    => BISR [clear_on_read],
+    bier:     Channel(0xb01)    => BIER,
+    aeisr:    Channel(0xb02)    => AEISR [clear_on_read],
+    aeier:    Channel(0xb03)    => AEIER,
+    fisr:     Channel(0xb04)    => FISR [clear_on_read],
+    fier:     Channel(0xb05)    => FIER,
+    dlsr1:    Channel(0xb06)    => DLSRx [clear_on_read],
+    dlier1:   Channel(0xb07)    => DLIERx,
+    sbisr:    Channel(0xb08)    => SBISR [clear_on_read],
+    sbier:    Channel(0xb09)    => SBIER,
+    rlcisr0:  Channel(0xb0a)    => RLCISRx [clear_on_read],
+    rlcier0:  Channel(0xb0b)    => RLCIERx,
+    exzsr:    Channel(0xb0e)    => EXZSR,
+    exzer:    Channel(0xb0f)    => EXZER,
+    ss7sr1:   Channel(0xb10)    => SS7SRx [clear_on_read],
+    ss7er1:   Channel(0xb11)    => SS7ERx,
+    rlcisr:   Channel(0xb12)    => RLCISR [clear_on_read],
+    rlcier:   Channel(0xb13)    => RLCIER,
+    rlcisr1:  Channel(0xb14)    => RLCISRx [clear_on_read],
+    rlcier1:  Channel(0xb15)    => RLCIERx,
+    dlsr2:    Channel(0xb16)    => DLSRx [clear_on_read],
+    dlier2:   Channel(0xb17)    => DLIERx,
+    ss7sr2:   Channel(0xb18)    => SS7SRx [clear_on_read],
+    ss7er2:   Channel(0xb19)    => SS7ERx,
+    rlcisr2:  Channel(0xb1a)    => RLCISRx [clear_on_read],
+    rlcier2:  Channel(0xb1b)    => RLCIERx,
+    rlcisr3:  Channel(0xb1c)    => RLCISRx [clear_on_read],
+    rlcier3:  Channel(0xb1d)    => RLCIERx,
+    rlcisr4:  Channel(0xb1e)    => RLCISRx [clear_on_read],
+    rlcier4:  Channel(0xb1f)    => RLCIERx,
+    rlcisr5:  Channel(0xb20)    => RLCISRx [clear_on_read],
+    rlcier5:  Channel(0xb21)    => RLCIERx,
+    rlcisr6:  Channel(0xb22)    => RLCISRx [clear_on_read],
+    rlcier6:  Channel(0xb23)    => RLCIERx,
+    rlcisr7:  Channel(0xb24)    => RLCISRx [clear_on_read],
+    rlcier7:  Channel(0xb25)    => RLCIERx,
+    dlsr3:    Channel(0xb26)    => DLSRx [clear_on_read],
+    dlier3:   Channel(0xb27)    => DLIERx,
+    ss7sr3:   Channel(0xb28)    => SS7SRx [clear_on_read],
+    ss7er3:   Channel(0xb29)    => SS7ERx,
+    ciasr:    Channel(0xb40)    => CIASR [clear_on_read],
+    ciaier:   Channel(0xb41)    => CIAIER,
+    bocisr:   Channel(0xb70)    => BOCISR [clear_on_read],
+    bocier:   Channel(0xb71)    => BOCIER,
+    bocuisr:  Channel(0xb74)    => BOCUISR,
+    bocuier:  Channel(0xb75)    => BOCUIER,
+
+    // LIU channel
+    liuccr0:  LiuChannel(0x0)   => LIUCCR0,
+    liuccr1:  LiuChannel(0x1)   => LIUCCR1,
+    liuccr2:  LiuChannel(0x2)   => LIUCCR2,
+    liuccr3:  LiuChannel(0x3)   => LIUCCR3,
+    liuccier: LiuChannel(0x4)   => LIUCCIER,
+    liuccsr:  LiuChannel(0x5)   => LIUCCSR,
+    liuccisr: LiuChannel(0x6)   => LIUCCISR [clear_on_read],
+    liuccccr: LiuChannel(0x7)   => LIUCCCCR,
+    liuccar1: LiuChannel(0x8)   => LIUCCAR,
+    liuccar2: LiuChannel(0x9)   => LIUCCAR,
+    liuccar3: LiuChannel(0xa)   => LIUCCAR,
+    liuccar4: LiuChannel(0xb)   => LIUCCAR,
+    liuccar5: LiuChannel(0xc)   => LIUCCAR,
+    liuccar6: LiuChannel(0xd)   => LIUCCAR,
+    liuccar7: LiuChannel(0xe)   => LIUCCAR,
+    liuccar8: LiuChannel(0xf)   => LIUCCAR,
+
+    // LIU global
+    liugcr0:  LiuGlobal(0x0)    => LIUGCR0,
+    liugcr1:  LiuGlobal(0x1)    => LIUGCR1,
+    liugcr2:  LiuGlobal(0x2)    => LIUGCR2,
+    liugcr3:  LiuGlobal(0x4)    => LIUGCR3,
+    liugcr4:  LiuGlobal(0x9)    => LIUGCR4,
+    liugcr5:  LiuGlobal(0xa)    => LIUGCR5,
+
+    // Device identification
+    devid:    Global(0x01fe)    => DEVID,
+    revid:    Global(0x01ff)    => REVID,
+}
+
+/// One instance of a register: a particular channel's, or timeslot's.
+///
+#[derive(Copy, Clone, Debug)]
+pub struct RegisterInstance {
+    pub register: &'static Register,
+    pub channel: Option<usize>,
+    pub timeslot: Option<usize>,
+}
+
+impl RegisterInstance {
+    pub fn address(&self) -> u16 {
+        let channel = self.channel.unwrap_or(0);
+        let timeslot = self.timeslot.unwrap_or(0);
+
+        let address = match self.register.scope {
+            Scope::Global(offset)          => Addressing::global(offset),
+            Scope::LiuGlobal(offset)       => Addressing::global_0fex(offset),
+            Scope::Channel(offset)         => Addressing::channel_nxxx(channel, offset),
+            Scope::LiuChannel(offset)      => Addressing::channel_0fnx(channel, offset),
+            Scope::Timeslot(block_offset)  => Addressing::channel_nxxx_timeslot(channel, block_offset, timeslot),
+            Scope::Rds0mr                  => Addressing::rds0mr(channel, timeslot),
+        };
+
+        address as u16
+    }
+
+    pub fn fields(&self, value: u8) -> Vec<(String, String)> {
+        self.register.fields(value)
+    }
+}
+
+impl FromStr for RegisterInstance {
+    type Err = RegisterMapError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let malformed = || RegisterMapError::Malformed(s.into());
+        let (name, location) = match s.split_once('@') {
+            Some((name, location)) => (name, Some(location)),
+            None => (s, None),
+        };
+        let register = Register::by_name(name)
+            .ok_or_else(|| RegisterMapError::UnknownRegister(name.into()))?;
+
+        let (channel, timeslot) = match location.map(|l| l.split_once('.')) {
+            None => (None, None),
+            Some(None) => (location, None),
+            Some(Some((channel, timeslot))) => (Some(channel), Some(timeslot)),
+        };
+        let channel = channel.map(|c| c.parse::<usize>().map_err(|_| malformed())).transpose()?;
+        let timeslot = timeslot.map(|t| t.parse::<usize>().map_err(|_| malformed())).transpose()?;
+
+        let scope = register.scope;
+        let expected = match (scope.per_channel(), scope.per_timeslot()) {
+            (false, _)    => "no channel",
+            (true, false) => "a channel, 0-7",
+            (true, true)  => "a channel and timeslot, 0-7.0-23",
+        };
+        let valid = channel.is_none_or(|c| c < Channels::COUNT)
+            && timeslot.is_none_or(|t| t < Timeslots::COUNT)
+            && channel.is_some() == scope.per_channel()
+            && timeslot.is_some() == scope.per_timeslot();
+        if !valid {
+            return Err(RegisterMapError::Instance { register: register.name, expected });
+        }
+
+        Ok(Self { register, channel, timeslot })
+    }
+}
+
+/// Names instances the way the command line does: `csr@3` for channel 3's
+/// CSR, `tccr@2.17` for timeslot 17 of channel 2.
+impl fmt::Display for RegisterInstance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.register.name)?;
+        if let Some(channel) = self.channel {
+            write!(f, "@{channel}")?;
+        }
+        if let Some(timeslot) = self.timeslot {
+            write!(f, ".{timeslot}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Every register instance in the map, device-wide ones first, then each
+/// channel's in address order. `channel` limits it to one channel (and the
+/// device-wide registers).
+pub fn register_instances(channel: Option<usize>) -> Vec<RegisterInstance> {
+    let mut instances = Vec::new();
+
+    for register in REGISTERS.iter().filter(|r| !r.scope.per_channel()) {
+        instances.push(RegisterInstance { register, channel: None, timeslot: None });
+    }
+
+    let channels = match channel {
+        Some(channel) => channel..channel + 1,
+        None => 0..Channels::COUNT,
+    };
+    for channel in channels {
+        let start = instances.len();
+        for register in REGISTERS.iter().filter(|r| r.scope.per_channel()) {
+            if register.scope.per_timeslot() {
+                for timeslot in 0..Timeslots::COUNT {
+                    instances.push(RegisterInstance { register, channel: Some(channel), timeslot: Some(timeslot) });
+                }
+            } else {
+                instances.push(RegisterInstance { register, channel: Some(channel), timeslot: None });
+            }
+        }
+        instances[start..].sort_by_key(|i| i.address());
+    }
+
+    instances
+}
+
+/// A register as given on the command line: an address, or a name that
+/// `RegisterInstance` parses. Addresses in the map still get decoded.
+///
+#[derive(Copy, Clone, Debug)]
+pub struct RegisterTarget {
+    pub address: u16,
+    pub instance: Option<RegisterInstance>,
+}
+
+impl RegisterTarget {
+    pub fn with_fields(&self, value: u8, assignments: &[(String, String)]) -> Result<u8, RegisterMapError> {
+        let instance = self.instance.ok_or(RegisterMapError::UnknownAddress(self.address))?;
+        assignments.iter().try_fold(value, |value, (field, text)| {
+            instance.register.with_field(value, field, text)
+        })
+    }
+}
+
+impl FromStr for RegisterTarget {
+    type Err = RegisterMapError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match parse_number(s) {
+            Some(address) => {
+                let address = u16::try_from(address).map_err(|_| RegisterMapError::Malformed(s.into()))?;
+                let instance = register_instances(None).into_iter().find(|i| i.address() == address);
+                Ok(Self { address, instance })
+            },
+            None => {
+                let instance: RegisterInstance = s.parse()?;
+                Ok(Self { address: instance.address(), instance: Some(instance) })
+            },
+        }
+    }
+}
+
+/// Decimal, or hexadecimal and binary with `0x` and `0b` prefixes.
+pub fn parse_number(s: &str) -> Option<u32> {
+    if let Some(hex) = s.strip_prefix("0x") {
+        u32::from_str_radix(hex, 16).ok()
+    } else if let Some(binary) = s.strip_prefix("0b") {
+        u32::from_str_radix(binary, 2).ok()
+    } else {
+        s.parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn instance_addresses() {
+        let instance = |name, channel, timeslot| RegisterInstance {
+            register: Register::by_name(name).unwrap(),
+            channel,
+            timeslot,
+        };
+
+        assert_eq!(instance("revid", None, None).address(), 0x01ff);
+        assert_eq!(instance("liugcr3", None, None).address(), 0x0fe4);
+        assert_eq!(instance("csr", Some(3), None).address(), 0x3100);
+        assert_eq!(instance("liuccr2", Some(5), None).address(), 0x0f52);
+        assert_eq!(instance("tccr", Some(2), Some(17)).address(), 0x2311);
+        assert_eq!(instance("rds0mr", Some(1), Some(4)).address(), 0x1164);
+        assert_eq!(instance("tccr", Some(2), Some(17)).to_string(), "tccr@2.17");
+    }
+
+    #[test]
+    fn parse_names() {
+        let target: RegisterTarget = "tccr@2.17".parse().unwrap();
+        assert_eq!(target.address, 0x2311);
+        let target: RegisterTarget = "RDS0MR@0.4".parse().unwrap();
+        assert_eq!(target.address, 0x0164);
+        let target: RegisterTarget = "devid".parse().unwrap();
+        assert_eq!(target.address, 0x01fe);
+
+        let target: RegisterTarget = "0x3100".parse().unwrap();
+        assert_eq!(target.instance.unwrap().to_string(), "csr@3");
+        assert!("0x3130".parse::<RegisterTarget>().unwrap().instance.is_none());
+
+        assert!(matches!("csr".parse::<RegisterTarget>(), Err(RegisterMapError::Instance { .. })));
+        assert!(matches!("csr@8".parse::<RegisterTarget>(), Err(RegisterMapError::Instance { .. })));
+        assert!(matches!("tccr@2".parse::<RegisterTarget>(), Err(RegisterMapError::Instance { .. })));
+        assert!(matches!("devid@0".parse::<RegisterTarget>(), Err(RegisterMapError::Instance { .. })));
+        assert!(matches!("csr@x".parse::<RegisterTarget>(), Err(RegisterMapError::Malformed(_))));
+        assert!(matches!("nope@1".parse::<RegisterTarget>(), Err(RegisterMapError::UnknownRegister(_))));
+    }
+
+    #[test]
+    fn set_fields() {
+        let target: RegisterTarget = "licr@1".parse().unwrap();
+        let assign = |field: &str, value: &str| vec![(field.to_string(), value.to_string())];
+
+        let value = target.with_fields(0xff, &assign("LB", "Payload")).unwrap();
+        assert_eq!(LICR::from(value).LB() as u8, FramerLoopback::Payload as u8);
+        // The other fields are left alone.
+        assert_eq!(value | 0b0011_0000, 0xff);
+
+        let value = target.with_fields(0x00, &assign("encode_b8zs", "1")).unwrap();
+        assert_eq!(LICR::from(value).Encode_B8ZS(), 1);
+
+        assert!(matches!(target.with_fields(0, &assign("LB", "Sideways")), Err(RegisterMapError::FieldValue { .. })));
+        assert!(matches!(target.with_fields(0, &assign("Encode_B8ZS", "2")), Err(RegisterMapError::FieldValue { .. })));
+        assert!(matches!(target.with_fields(0, &assign("XYZZY", "1")), Err(RegisterMapError::UnknownField { .. })));
+    }
+
+    #[test]
+    fn instances_are_distinct() {
+        let mut addresses: Vec<u16> = register_instances(None).iter().map(|i| i.address()).collect();
+        let count = addresses.len();
+        addresses.sort();
+        addresses.dedup();
+        assert_eq!(addresses.len(), count);
+    }
+
+    #[test]
+    fn fields_from_definitions() {
+        let register = Register::by_name("licr").unwrap();
+        let value = LICR::new()
+            .with_LB(FramerLoopback::Payload)
+            .into();
+        let fields = register.fields(value);
+        assert!(fields.contains(&("LB".to_string(), "Payload".to_string())));
+        assert!(fields.iter().all(|(name, _)| !name.contains(' ')));
+
+        // Every bit is a field or reserved.
+        for register in REGISTERS {
+            let bits: usize = register.fields.iter().map(|f| f.bits).sum();
+            assert!(bits == 8 || register.fields.is_empty(), "{}", register.name);
+        }
+
+        // Reserved bits aren't reported.
+        let bisr = Register::by_name("bisr").unwrap();
+        assert_eq!(bisr.fields(0xff).len(), 7);
+        assert_eq!(bisr.fields(0x40)[0], ("LBCODE".to_string(), "1".to_string()));
+
+        assert!(Register::by_name("pbecu").unwrap().fields(0x12).is_empty());
+        assert!(Register::by_name("pbecu").unwrap().clear_on_read);
+        assert!(!Register::by_name("bier").unwrap().clear_on_read);
+    }
+}
@@ -2,19 +2,57 @@ use std::time::Instant;
 
 use crate::detector::DetectionEvent;
 
-use self::audio::TimeslotAddress;
+use self::audio::{SoftBertStatistics, TimeslotAddress};
+use self::crc6::Crc6Statistics;
+use self::fdl::BitOrientedMessage;
 
+pub mod alarm;
 pub mod audio;
+pub mod bert;
+pub mod clock;
+pub mod control;
+pub mod crc6;
 pub mod device;
 pub mod dump;
+pub mod fdl;
+pub mod hdlc;
 pub mod interrupt;
+mod interrupt_status;
+pub mod liu;
+pub mod loopback;
+pub mod map;
+pub mod mtp2;
+pub mod mtp3;
+pub mod pmon;
+pub mod pri;
+pub mod prm;
+pub mod q921;
+pub mod q931;
 pub mod register;
+pub mod slip;
 pub mod test;
 mod usb;
 
 #[derive(Copy, Clone, Debug)]
 pub enum FramerEvent {
-    Interrupt { timestamp: Instant, data: [u8; usb::INTERRUPT_BYTES_MAX], length: usize },
+    InterruptError { error: interrupt::InterruptError, data: [u8; usb::INTERRUPT_BYTES_MAX], length: usize },
+    LoopbackCode { timestamp: Instant, channel_index: usize, status: interrupt::LoopbackCodeStatus },
+    Slip { timestamp: Instant, channel_index: usize, sbisr: register::SBISR },
+    Alarm { timestamp: Instant, channel_index: usize, defects: alarm::Defects, status: interrupt::AlarmStatus },
+    Framing { channel_index: usize, fisr: register::FISR },
+    /// `old_abcd` is `None` the first time a timeslot's signaling is heard.
+    Signaling { timestamp: Instant, address: TimeslotAddress, old_abcd: Option<u8>, new_abcd: u8 },
     Digit(TimeslotAddress, DetectionEvent),
     RobbedBitState(u32, TimeslotAddress, u8),
+    BitOrientedMessage(u32, usize, BitOrientedMessage),
+    Crc6Statistics(u32, usize, Crc6Statistics),
+    HdlcChunk { channel_index: usize, controller_index: usize, dlsr: register::DLSRx, buffer_index: usize, data: [u8; interrupt::HDLC_CHUNK_BYTES_MAX], length: usize },
+    HdlcFrame { channel_index: usize, controller_index: usize, data: [u8; hdlc::FRAME_BYTES_MAX], length: usize },
+    HdlcTransmitComplete { channel_index: usize, controller_index: usize },
+    Ss7Status { channel_index: usize, controller_index: usize },
+    RecoveredClock { channel_index: usize, lost: bool },
+    OneSecond { channel_index: usize },
+    SoftBert(u32, usize, SoftBertStatistics),
+    /// The gateware's cumulative USB FIFO error counters changed.
+    UsbFifoErrors { timestamp: Instant, rx_underflow_count: u16, tx_overflow_count: u16 },
 }
@@ -0,0 +1,376 @@
+use thiserror::Error;
+
+use super::device::{Channel, Result};
+use super::register::*;
+
+/// Route a receive timeslot to one of the channel's LAPD controllers, for
+/// listening to a 64 kbit/s signalling link. Transmit is left alone, since a
+/// monitor must never drive the tapped link.
+///
+pub fn configure_signalling_link_monitor(channel: &Channel, timeslot_index: usize, hdlc_index: usize) -> Result<()> {
+    let source = match hdlc_index {
+        0 => ReceiveLAPDSource::LAPDController1,
+        1 => ReceiveLAPDSource::LAPDController2,
+        2 => ReceiveLAPDSource::LAPDController3,
+        _ => return Err(rusb::Error::InvalidParam),
+    };
+
+    channel.timeslot(timeslot_index).rccr().modify(|m| m
+        .with_LAPDcntl(source)
+    )?;
+
+    channel.lapdsr().modify(|m| match hdlc_index {
+        0 => m.with_HDLC1en(1),
+        1 => m.with_HDLC2en(1),
+        _ => m.with_HDLC3en(1),
+    })?;
+
+    channel.dlcr(hdlc_index).modify(|m| m
+        .with_MOS_BOSn(1)
+    )?;
+
+    channel.ss7er(hdlc_index).modify(|m| m
+        .with_SS7_ENB(1)
+    )
+}
+
+#[derive(Error, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Mtp2Error {
+    #[error("signal unit shorter than its header")]
+    Truncated,
+    #[error("length indicator {length_indicator} doesn't match {length} octets of payload")]
+    LengthIndicator { length_indicator: u8, length: usize },
+}
+
+/// LSSU status field (Q.703 11.1.3 / T1.111.3).
+///
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum LinkStatus {
+    /// SIO
+    OutOfAlignment,
+    /// SIN
+    NormalAlignment,
+    /// SIE
+    EmergencyAlignment,
+    /// SIOS
+    OutOfService,
+    /// SIPO
+    ProcessorOutage,
+    /// SIB
+    Busy,
+    Unknown(u8),
+}
+
+impl LinkStatus {
+    pub fn from_u8(v: u8) -> Self {
+        match v & 0x07 {
+            0 => Self::OutOfAlignment,
+            1 => Self::NormalAlignment,
+            2 => Self::EmergencyAlignment,
+            3 => Self::OutOfService,
+            4 => Self::ProcessorOutage,
+            5 => Self::Busy,
+            v => Self::Unknown(v),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SignalUnitKind<'a> {
+    /// Fill-in signal unit: keeps the link busy and carries acknowledgements.
+    Fisu,
+    /// Link status signal unit.
+    Lssu(LinkStatus),
+    /// Message signal unit, with the service information octet and field.
+    Msu { sio: u8, sif: &'a [u8] },
+}
+
+/// An MTP2 signal unit, as received by the framer's HDLC controller.
+///
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct SignalUnit<'a> {
+    /// Backward sequence number: the last FSN this end has accepted.
+    pub bsn: u8,
+    /// Backward indicator bit: inverted to request retransmission.
+    pub bib: bool,
+    /// Forward sequence number.
+    pub fsn: u8,
+    /// Forward indicator bit: inverted when retransmission starts.
+    pub fib: bool,
+    pub length_indicator: u8,
+    pub kind: SignalUnitKind<'a>,
+}
+
+impl<'a> SignalUnit<'a> {
+    const HEADER_LENGTH: usize = 3;
+
+    /// An LI of 63 means "62 octets or more".
+    const LENGTH_INDICATOR_MAX: u8 = 63;
+
+    /// Parse a signal unit, which may or may not still have the FCS attached.
+    pub fn from_slice(b: &'a [u8]) -> core::result::Result<Self, Mtp2Error> {
+        if b.len() < Self::HEADER_LENGTH {
+            return Err(Mtp2Error::Truncated);
+        }
+
+        let bsn = b[0] & 0x7f;
+        let bib = b[0] & 0x80 != 0;
+        let fsn = b[1] & 0x7f;
+        let fib = b[1] & 0x80 != 0;
+        let length_indicator = b[2] & 0x3f;
+
+        let mut payload = &b[Self::HEADER_LENGTH..];
+        if payload.len() >= 2 && fcs_valid(b) {
+            payload = &payload[..payload.len() - 2];
+        }
+
+        let length = payload.len();
+        let length_ok = match length_indicator {
+            Self::LENGTH_INDICATOR_MAX => length >= Self::LENGTH_INDICATOR_MAX as usize - 1,
+            li => length == li as usize,
+        };
+        if !length_ok {
+            return Err(Mtp2Error::LengthIndicator { length_indicator, length });
+        }
+
+        let kind = match length_indicator {
+            0 => SignalUnitKind::Fisu,
+            1 | 2 => SignalUnitKind::Lssu(LinkStatus::from_u8(payload[0])),
+            _ => SignalUnitKind::Msu { sio: payload[0], sif: &payload[1..] },
+        };
+
+        Ok(Self {
+            bsn,
+            bib,
+            fsn,
+            fib,
+            length_indicator,
+            kind,
+        })
+    }
+}
+
+/// HDLC FCS-16 register (ISO 3309), before the final inversion.
+fn fcs(b: &[u8]) -> u16 {
+    let mut crc = 0xffffu16;
+    for &octet in b {
+        crc ^= octet as u16;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0x8408 } else { crc >> 1 };
+        }
+    }
+    crc
+}
+
+/// Running the FCS over a frame and its own FCS leaves this residue.
+fn fcs_valid(b: &[u8]) -> bool {
+    fcs(b) == 0xf0b8
+}
+
+/// Link state, as inferred by a passive observer of one direction.
+///
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum LinkState {
+    /// Nothing heard yet.
+    Unknown,
+    /// Alignment in progress (SIO, SIN, or SIE).
+    Aligning,
+    /// FISUs and MSUs flowing.
+    InService,
+    OutOfService,
+    ProcessorOutage,
+    Busy,
+}
+
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub struct Mtp2Statistics {
+    pub fisus: u32,
+    pub lssus: u32,
+    pub msus: u32,
+    /// MSUs sent again with an FSN we'd already seen.
+    pub retransmissions: u32,
+    /// BIB inversions: the far end asking this end to retransmit.
+    pub negative_acknowledgements: u32,
+    /// Signal units that didn't decode.
+    pub errors: u32,
+    /// Times the framer flagged SS7 status for the controller.
+    pub ss7_status: u32,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Mtp2MonitorEvent<'a> {
+    LinkState(LinkState),
+    Msu { fsn: u8, retransmission: bool, sio: u8, sif: &'a [u8] },
+    Error(Mtp2Error),
+}
+
+/// Passive monitor for one direction of an MTP2 signalling link.
+///
+/// Tracks the link state from LSSUs, counts signal units, and spots
+/// retransmissions from the sequence numbers, without ever transmitting.
+///
+pub struct Mtp2Monitor {
+    state: LinkState,
+    last_msu_fsn: Option<u8>,
+    last_bib: Option<bool>,
+    statistics: Mtp2Statistics,
+}
+
+impl Mtp2Monitor {
+    pub fn new() -> Self {
+        Self {
+            state: LinkState::Unknown,
+            last_msu_fsn: None,
+            last_bib: None,
+            statistics: Mtp2Statistics::default(),
+        }
+    }
+
+    pub fn statistics(&self) -> &Mtp2Statistics {
+        &self.statistics
+    }
+
+    /// The framer latched SS7_STATUS for this controller.
+    pub fn ss7_status(&mut self) {
+        self.statistics.ss7_status += 1;
+    }
+
+    pub fn push<F>(&mut self, frame: &[u8], mut event_fn: F)
+        where F: FnMut(Mtp2MonitorEvent)
+    {
+        let su = match SignalUnit::from_slice(frame) {
+            Ok(su) => su,
+            Err(e) => {
+                self.statistics.errors += 1;
+                event_fn(Mtp2MonitorEvent::Error(e));
+                return;
+            },
+        };
+
+        if let Some(last_bib) = self.last_bib {
+            if su.bib != last_bib {
+                self.statistics.negative_acknowledgements += 1;
+            }
+        }
+        self.last_bib = Some(su.bib);
+
+        let state = match su.kind {
+            SignalUnitKind::Fisu => {
+                self.statistics.fisus += 1;
+                LinkState::InService
+            },
+            SignalUnitKind::Lssu(status) => {
+                self.statistics.lssus += 1;
+                match status {
+                    LinkStatus::OutOfAlignment |
+                    LinkStatus::NormalAlignment |
+                    LinkStatus::EmergencyAlignment => LinkState::Aligning,
+                    LinkStatus::OutOfService => LinkState::OutOfService,
+                    LinkStatus::ProcessorOutage => LinkState::ProcessorOutage,
+                    LinkStatus::Busy => LinkState::Busy,
+                    LinkStatus::Unknown(_) => self.state,
+                }
+            },
+            SignalUnitKind::Msu { sio, sif } => {
+                self.statistics.msus += 1;
+
+                // An FSN at or behind the last one (mod 128) has been seen before.
+                let retransmission = self.last_msu_fsn
+                    .map(|last| last.wrapping_sub(su.fsn) & 0x7f < 64)
+                    .unwrap_or(false);
+                if retransmission {
+                    self.statistics.retransmissions += 1;
+                } else {
+                    self.last_msu_fsn = Some(su.fsn);
+                }
+
+                event_fn(Mtp2MonitorEvent::Msu { fsn: su.fsn, retransmission, sio, sif });
+                LinkState::InService
+            },
+        };
+
+        if state != self.state {
+            self.state = state;
+            event_fn(Mtp2MonitorEvent::LinkState(state));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(bsn: u8, fsn: u8, li: u8) -> Vec<u8> {
+        vec![bsn, fsn, li]
+    }
+
+    fn with_fcs(mut b: Vec<u8>) -> Vec<u8> {
+        let fcs = !fcs(&b);
+        b.extend_from_slice(&fcs.to_le_bytes());
+        b
+    }
+
+    #[test]
+    fn decode_signal_units() {
+        let fisu = SignalUnit::from_slice(&[0x85, 0x03, 0x00]).unwrap();
+        assert_eq!((fisu.bsn, fisu.bib, fisu.fsn, fisu.fib), (5, true, 3, false));
+        assert_eq!(fisu.kind, SignalUnitKind::Fisu);
+
+        let lssu = with_fcs(vec![0x7f, 0xff, 0x01, 0x03]);
+        let lssu = SignalUnit::from_slice(&lssu).unwrap();
+        assert_eq!(lssu.kind, SignalUnitKind::Lssu(LinkStatus::OutOfService));
+
+        let mut msu = header(1, 2, 4);
+        msu.extend_from_slice(&[0x85, 0xaa, 0xbb, 0xcc]);
+        let msu = SignalUnit::from_slice(&msu).unwrap();
+        assert_eq!(msu.kind, SignalUnitKind::Msu { sio: 0x85, sif: &[0xaa, 0xbb, 0xcc] });
+
+        assert_eq!(SignalUnit::from_slice(&[0, 0]), Err(Mtp2Error::Truncated));
+        assert_eq!(SignalUnit::from_slice(&[0, 0, 5, 1]), Err(Mtp2Error::LengthIndicator { length_indicator: 5, length: 1 }));
+    }
+
+    #[test]
+    fn long_msu() {
+        let mut msu = header(0, 0, 63);
+        msu.extend_from_slice(&[0x05; 100]);
+        let msu = with_fcs(msu);
+        let su = SignalUnit::from_slice(&msu).unwrap();
+        assert!(matches!(su.kind, SignalUnitKind::Msu { sif, .. } if sif.len() == 99));
+    }
+
+    #[test]
+    fn monitor_tracks_alignment_and_retransmission() {
+        let mut dut = Mtp2Monitor::new();
+        let mut events = Vec::new();
+        let mut push = |dut: &mut Mtp2Monitor, b: Vec<u8>| {
+            dut.push(&b, |e| events.push(format!("{e:?}")));
+        };
+
+        push(&mut dut, vec![0xff, 0xff, 0x01, 0x03]);
+        push(&mut dut, vec![0xff, 0xff, 0x01, 0x01]);
+        push(&mut dut, vec![0xff, 0xff, 0x01, 0x01]);
+        push(&mut dut, vec![0xff, 0xff, 0x00]);
+
+        let msu = |fsn: u8, bib: u8| vec![0x7f | bib, 0x80 | fsn, 4, 0x85, 1, 2, 3];
+        push(&mut dut, msu(0, 0x80));
+        push(&mut dut, msu(1, 0x80));
+        // The far end asked for retransmission.
+        push(&mut dut, msu(1, 0x00));
+        push(&mut dut, msu(2, 0x00));
+
+        assert_eq!(events, [
+            "LinkState(OutOfService)",
+            "LinkState(Aligning)",
+            "LinkState(InService)",
+            "Msu { fsn: 0, retransmission: false, sio: 133, sif: [1, 2, 3] }",
+            "Msu { fsn: 1, retransmission: false, sio: 133, sif: [1, 2, 3] }",
+            "Msu { fsn: 1, retransmission: true, sio: 133, sif: [1, 2, 3] }",
+            "Msu { fsn: 2, retransmission: false, sio: 133, sif: [1, 2, 3] }",
+        ]);
+
+        let statistics = dut.statistics();
+        assert_eq!((statistics.fisus, statistics.lssus, statistics.msus), (1, 3, 4));
+        assert_eq!(statistics.retransmissions, 1);
+        assert_eq!(statistics.negative_acknowledgements, 1);
+    }
+}
@@ -0,0 +1,293 @@
+use std::fmt;
+
+/// Point code and CIC layout. T1 links are almost always ANSI.
+///
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Variant {
+    /// ANSI T1.111/T1.113: 24-bit point codes, 8-bit SLS, 14-bit CIC.
+    Ansi,
+    /// ITU-T Q.704/Q.763: 14-bit point codes, 4-bit SLS, 12-bit CIC.
+    Itu,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ServiceIndicator {
+    SignallingNetworkManagement,
+    SignallingNetworkTesting,
+    SignallingNetworkTestingSpecial,
+    Sccp,
+    Tup,
+    Isup,
+    Unknown(u8),
+}
+
+impl ServiceIndicator {
+    pub fn from_u8(v: u8) -> Self {
+        match v {
+            0 => Self::SignallingNetworkManagement,
+            1 => Self::SignallingNetworkTesting,
+            2 => Self::SignallingNetworkTestingSpecial,
+            3 => Self::Sccp,
+            4 => Self::Tup,
+            5 => Self::Isup,
+            v => Self::Unknown(v),
+        }
+    }
+}
+
+/// Service information octet (SIO), the first octet of every MSU.
+///
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ServiceInformationOctet {
+    pub service_indicator: ServiceIndicator,
+    /// 0 = international, 1 = spare, 2 = national, 3 = national spare.
+    pub network_indicator: u8,
+    /// ANSI message priority; spare in ITU.
+    pub priority: u8,
+}
+
+impl ServiceInformationOctet {
+    pub fn from_u8(v: u8) -> Self {
+        Self {
+            service_indicator: ServiceIndicator::from_u8(v & 0x0f),
+            network_indicator: (v >> 6) & 3,
+            priority: (v >> 4) & 3,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct PointCode {
+    pub value: u32,
+    pub variant: Variant,
+}
+
+impl fmt::Display for PointCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.variant {
+            // network-cluster-member
+            Variant::Ansi => write!(f, "{}-{}-{}", (self.value >> 16) & 0xff, (self.value >> 8) & 0xff, self.value & 0xff),
+            // zone-area-signalling point (3-8-3)
+            Variant::Itu => write!(f, "{}-{}-{}", (self.value >> 11) & 0x7, (self.value >> 3) & 0xff, self.value & 0x7),
+        }
+    }
+}
+
+/// MTP3 routing label, at the start of the signalling information field.
+///
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct RoutingLabel {
+    pub destination: PointCode,
+    pub origin: PointCode,
+    pub signalling_link_selection: u8,
+}
+
+impl RoutingLabel {
+    /// Returns the label and the rest of the signalling information field.
+    pub fn from_slice(b: &[u8], variant: Variant) -> Option<(Self, &[u8])> {
+        match variant {
+            Variant::Ansi => {
+                if b.len() < 7 {
+                    return None;
+                }
+                let pc = |o: usize| PointCode {
+                    value: u32::from_le_bytes([b[o], b[o + 1], b[o + 2], 0]),
+                    variant,
+                };
+                Some((Self {
+                    destination: pc(0),
+                    origin: pc(3),
+                    signalling_link_selection: b[6],
+                }, &b[7..]))
+            },
+            Variant::Itu => {
+                if b.len() < 4 {
+                    return None;
+                }
+                let v = u32::from_le_bytes([b[0], b[1], b[2], b[3]]);
+                let pc = |value: u32| PointCode { value: value & 0x3fff, variant };
+                Some((Self {
+                    destination: pc(v),
+                    origin: pc(v >> 14),
+                    signalling_link_selection: (v >> 28) as u8,
+                }, &b[4..]))
+            },
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum IsupMessageType {
+    InitialAddress,
+    SubsequentAddress,
+    Information,
+    InformationRequest,
+    Continuity,
+    AddressComplete,
+    Connect,
+    Answer,
+    Release,
+    Suspend,
+    Resume,
+    ReleaseComplete,
+    ContinuityCheckRequest,
+    Reset,
+    Blocking,
+    Unblocking,
+    BlockingAcknowledgement,
+    UnblockingAcknowledgement,
+    CircuitGroupReset,
+    CircuitGroupBlocking,
+    CircuitGroupUnblocking,
+    CircuitGroupBlockingAcknowledgement,
+    CircuitGroupUnblockingAcknowledgement,
+    CircuitGroupResetAcknowledgement,
+    CallProgress,
+    UnequippedCic,
+    Confusion,
+    Unknown(u8),
+}
+
+impl IsupMessageType {
+    pub fn from_u8(v: u8) -> Self {
+        match v {
+            0x01 => Self::InitialAddress,
+            0x02 => Self::SubsequentAddress,
+            0x03 => Self::InformationRequest,
+            0x04 => Self::Information,
+            0x05 => Self::Continuity,
+            0x06 => Self::AddressComplete,
+            0x07 => Self::Connect,
+            0x09 => Self::Answer,
+            0x0c => Self::Release,
+            0x0d => Self::Suspend,
+            0x0e => Self::Resume,
+            0x10 => Self::ReleaseComplete,
+            0x11 => Self::ContinuityCheckRequest,
+            0x12 => Self::Reset,
+            0x13 => Self::Blocking,
+            0x14 => Self::Unblocking,
+            0x15 => Self::BlockingAcknowledgement,
+            0x16 => Self::UnblockingAcknowledgement,
+            0x17 => Self::CircuitGroupReset,
+            0x18 => Self::CircuitGroupBlocking,
+            0x19 => Self::CircuitGroupUnblocking,
+            0x1a => Self::CircuitGroupBlockingAcknowledgement,
+            0x1b => Self::CircuitGroupUnblockingAcknowledgement,
+            0x29 => Self::CircuitGroupResetAcknowledgement,
+            0x2c => Self::CallProgress,
+            0x2e => Self::UnequippedCic,
+            0x2f => Self::Confusion,
+            v => Self::Unknown(v),
+        }
+    }
+}
+
+/// Circuit identification code and message type, common to every ISUP message.
+///
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct IsupHeader {
+    pub circuit_identification_code: u16,
+    pub message_type: IsupMessageType,
+}
+
+impl IsupHeader {
+    pub fn from_slice(b: &[u8], variant: Variant) -> Option<Self> {
+        if b.len() < 3 {
+            return None;
+        }
+
+        let cic = u16::from_le_bytes([b[0], b[1]]);
+        let cic_mask = match variant {
+            Variant::Ansi => 0x3fff,
+            Variant::Itu => 0x0fff,
+        };
+
+        Some(Self {
+            circuit_identification_code: cic & cic_mask,
+            message_type: IsupMessageType::from_u8(b[2]),
+        })
+    }
+}
+
+/// An MSU's SIO and SIF, decoded as far as the routing label and, for ISUP,
+/// the circuit and message type.
+///
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Mtp3Message {
+    pub sio: ServiceInformationOctet,
+    pub label: RoutingLabel,
+    pub isup: Option<IsupHeader>,
+}
+
+impl Mtp3Message {
+    pub fn from_slice(sio: u8, sif: &[u8], variant: Variant) -> Option<Self> {
+        let sio = ServiceInformationOctet::from_u8(sio);
+        let (label, payload) = RoutingLabel::from_slice(sif, variant)?;
+
+        let isup = match sio.service_indicator {
+            ServiceIndicator::Isup => IsupHeader::from_slice(payload, variant),
+            _ => None,
+        };
+
+        Some(Self {
+            sio,
+            label,
+            isup,
+        })
+    }
+}
+
+impl fmt::Display for Mtp3Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} NI={} OPC={} DPC={} SLS={}",
+            self.sio.service_indicator, self.sio.network_indicator,
+            self.label.origin, self.label.destination, self.label.signalling_link_selection,
+        )?;
+        if let Some(isup) = &self.isup {
+            write!(f, " CIC={} {:?}", isup.circuit_identification_code, isup.message_type)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ansi_isup_iam() {
+        // SIO: national, ISUP. DPC 1-2-3, OPC 4-5-6, SLS 7, CIC 0x1234, IAM.
+        let sif = [0x03, 0x02, 0x01, 0x06, 0x05, 0x04, 0x07, 0x34, 0x12, 0x01, 0x00];
+        let message = Mtp3Message::from_slice(0x85, &sif, Variant::Ansi).unwrap();
+
+        assert_eq!(message.sio.service_indicator, ServiceIndicator::Isup);
+        assert_eq!(message.sio.network_indicator, 2);
+        assert_eq!(message.label.destination.to_string(), "1-2-3");
+        assert_eq!(message.label.origin.to_string(), "4-5-6");
+        assert_eq!(message.label.signalling_link_selection, 7);
+        assert_eq!(message.isup, Some(IsupHeader {
+            circuit_identification_code: 0x1234,
+            message_type: IsupMessageType::InitialAddress,
+        }));
+    }
+
+    #[test]
+    fn itu_routing_label() {
+        // DPC 0x0123, OPC 0x0456, SLS 9, packed LSB first into 32 bits.
+        let v: u32 = 0x0123 | (0x0456 << 14) | (9 << 28);
+        let mut sif = v.to_le_bytes().to_vec();
+        sif.extend_from_slice(&[0x05, 0xf0, 0x10]);
+
+        let message = Mtp3Message::from_slice(0x05, &sif, Variant::Itu).unwrap();
+        assert_eq!(message.label.destination.value, 0x0123);
+        assert_eq!(message.label.origin.value, 0x0456);
+        assert_eq!(message.label.signalling_link_selection, 9);
+        assert_eq!(message.isup, Some(IsupHeader {
+            circuit_identification_code: 0x005,
+            message_type: IsupMessageType::ReleaseComplete,
+        }));
+
+        assert!(Mtp3Message::from_slice(0x05, &sif[0..3], Variant::Itu).is_none());
+    }
+}
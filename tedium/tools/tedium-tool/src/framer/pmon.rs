@@ -0,0 +1,311 @@
+use std::collections::VecDeque;
+use std::ops::AddAssign;
+
+use super::device::{Channel, Result};
+
+/// One second's worth of the framer's performance monitor (PMON) counters.
+///
+/// The PMON registers reset when read, so reading them once a second (on the
+/// framer's ONESEC interrupt) gives per-second counts. Sixteen-bit counters
+/// are read upper byte first.
+///
+/// PBEC is among them, so while this is running the BERT's bit errors end up
+/// here rather than with `bert_error_count`.
+///
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub struct PmonCounts {
+    /// RLCVC: bipolar and line code violations.
+    pub line_code_violations: u16,
+    /// RFAEC: framing alignment (F-bit) errors.
+    pub framing_bit_errors: u16,
+    /// RSEFC: severely errored framing events.
+    pub severely_errored_frames: u8,
+    /// RSBBEC: ESF CRC-6 block errors.
+    pub crc6_errors: u16,
+    /// RSC
+    pub receive_slips: u8,
+    /// RLFC
+    pub loss_of_frame: u8,
+    /// RCFAC
+    pub change_of_frame_alignment: u8,
+    /// TSC
+    pub transmit_slips: u8,
+    /// EZVC
+    pub excessive_zeros: u16,
+    /// PBEC
+    pub prbs_bit_errors: u16,
+    /// LFCSEC1..3
+    pub lapd_fcs_errors: [u8; 3],
+}
+
+impl PmonCounts {
+    pub fn read(channel: &Channel) -> Result<Self> {
+        let u16_from = |u: u8, l: u8| u16::from_be_bytes([u, l]);
+
+        Ok(Self {
+            line_code_violations: u16_from(channel.rlcvcu().read()?, channel.rlcvcl().read()?),
+            framing_bit_errors: u16_from(channel.rfaecu().read()?, channel.rfaecl().read()?),
+            severely_errored_frames: channel.rsefc().read()?,
+            crc6_errors: u16_from(channel.rsbbecu().read()?, channel.rsbbecl().read()?),
+            receive_slips: channel.rsc().read()?,
+            loss_of_frame: channel.rlfc().read()?,
+            change_of_frame_alignment: channel.rcfac().read()?,
+            transmit_slips: channel.tsc().read()?,
+            excessive_zeros: u16_from(channel.ezvcu().read()?, channel.ezvcl().read()?),
+            prbs_bit_errors: u16_from(channel.pbecu().read()?, channel.pbecl().read()?),
+            lapd_fcs_errors: [
+                channel.lfcsec1().read()?,
+                channel.lfcsec2().read()?,
+                channel.lfcsec3().read()?,
+            ],
+        })
+    }
+}
+
+/// ANSI T1.231 near-end path and line parameters.
+///
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub struct PerformanceCounters {
+    /// Errored seconds: any CRC-6 error, SEF, or AIS.
+    pub es: u32,
+    /// Severely errored seconds: 320 or more CRC-6 errors, SEF, or AIS.
+    pub ses: u32,
+    /// Unavailable seconds.
+    pub uas: u32,
+    /// Bursty errored seconds: 2 to 319 CRC-6 errors, no SEF or AIS.
+    pub bes: u32,
+    /// Line code violations.
+    pub lcv: u32,
+    /// Controlled slip seconds.
+    pub css: u32,
+}
+
+impl AddAssign for PerformanceCounters {
+    fn add_assign(&mut self, other: Self) {
+        self.es += other.es;
+        self.ses += other.ses;
+        self.uas += other.uas;
+        self.bes += other.bes;
+        self.lcv += other.lcv;
+        self.css += other.css;
+    }
+}
+
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub struct PerformanceInterval {
+    pub counters: PerformanceCounters,
+    /// Seconds accumulated so far; 900 for a complete interval.
+    pub seconds: u32,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct SecondClassification {
+    errored: bool,
+    severely_errored: bool,
+    bursty_errored: bool,
+    controlled_slip: bool,
+    line_code_violations: u32,
+    unavailable: bool,
+}
+
+impl SecondClassification {
+    const SES_CRC6_THRESHOLD: u16 = 320;
+
+    fn new(counts: &PmonCounts, ais: bool) -> Self {
+        let crc6 = counts.crc6_errors;
+        let sef = counts.severely_errored_frames > 0;
+
+        Self {
+            errored: crc6 > 0 || sef || ais,
+            severely_errored: crc6 >= Self::SES_CRC6_THRESHOLD || sef || ais,
+            bursty_errored: crc6 > 1 && crc6 < Self::SES_CRC6_THRESHOLD && !sef && !ais,
+            controlled_slip: counts.receive_slips > 0,
+            line_code_violations: counts.line_code_violations as u32,
+            unavailable: false,
+        }
+    }
+}
+
+/// Accumulates per-second PMON counts into T1.231 parameters for one channel,
+/// keeping the current 15-minute interval and the 96 before it (24 hours).
+///
+/// The path becomes unavailable at the start of ten consecutive SES, and
+/// available again at the start of ten consecutive non-SES. Seconds are held
+/// back for ten seconds so they can be counted on the right side of that line,
+/// which means the counters lag the line by ten seconds.
+///
+pub struct PerformanceMonitor {
+    pending: VecDeque<SecondClassification>,
+    unavailable: bool,
+    /// Consecutive seconds that disagree with `unavailable`.
+    run: usize,
+    current: PerformanceInterval,
+    intervals: VecDeque<PerformanceInterval>,
+}
+
+impl PerformanceMonitor {
+    const UNAVAILABLE_SECONDS: usize = 10;
+    pub const INTERVAL_SECONDS: u32 = 15 * 60;
+    pub const INTERVALS_MAX: usize = 96;
+
+    pub fn new() -> Self {
+        Self {
+            pending: VecDeque::with_capacity(Self::UNAVAILABLE_SECONDS + 1),
+            unavailable: false,
+            run: 0,
+            current: PerformanceInterval::default(),
+            intervals: VecDeque::with_capacity(Self::INTERVALS_MAX),
+        }
+    }
+
+    pub fn is_unavailable(&self) -> bool {
+        self.unavailable
+    }
+
+    pub fn current(&self) -> &PerformanceInterval {
+        &self.current
+    }
+
+    /// Completed intervals, most recent first.
+    pub fn intervals(&self) -> impl Iterator<Item=&PerformanceInterval> {
+        self.intervals.iter().rev()
+    }
+
+    /// Totals over the completed intervals, up to 24 hours' worth.
+    pub fn total(&self) -> PerformanceInterval {
+        let mut total = PerformanceInterval::default();
+        for interval in &self.intervals {
+            total.counters += interval.counters;
+            total.seconds += interval.seconds;
+        }
+        total
+    }
+
+    /// Account for one second. `ais` is whether an AIS defect was present.
+    /// Returns the interval just completed, if this second completed one.
+    pub fn update(&mut self, counts: &PmonCounts, ais: bool) -> Option<PerformanceInterval> {
+        let mut second = SecondClassification::new(counts, ais);
+
+        if second.severely_errored != self.unavailable {
+            self.run += 1;
+        } else {
+            self.run = 0;
+        }
+        second.unavailable = self.unavailable;
+        self.pending.push_back(second);
+
+        let oldest = if self.pending.len() > Self::UNAVAILABLE_SECONDS {
+            self.pending.pop_front()
+        } else {
+            None
+        };
+
+        // What's left in `pending` is exactly the run that caused the change.
+        if self.run == Self::UNAVAILABLE_SECONDS {
+            self.unavailable = !self.unavailable;
+            self.run = 0;
+            for second in self.pending.iter_mut() {
+                second.unavailable = self.unavailable;
+            }
+        }
+
+        oldest.and_then(|second| self.commit(&second))
+    }
+
+    fn commit(&mut self, second: &SecondClassification) -> Option<PerformanceInterval> {
+        let counters = &mut self.current.counters;
+
+        // Line parameters keep counting through unavailable time; path parameters don't.
+        counters.lcv += second.line_code_violations;
+        if second.unavailable {
+            counters.uas += 1;
+        } else {
+            counters.es += second.errored as u32;
+            counters.ses += second.severely_errored as u32;
+            counters.bes += second.bursty_errored as u32;
+            counters.css += second.controlled_slip as u32;
+        }
+
+        self.current.seconds += 1;
+        if self.current.seconds < Self::INTERVAL_SECONDS {
+            return None;
+        }
+
+        let completed = std::mem::take(&mut self.current);
+        if self.intervals.len() == Self::INTERVALS_MAX {
+            self.intervals.pop_front();
+        }
+        self.intervals.push_back(completed);
+        Some(completed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crc6(n: u16) -> PmonCounts {
+        PmonCounts { crc6_errors: n, ..Default::default() }
+    }
+
+    fn run(dut: &mut PerformanceMonitor, counts: PmonCounts, seconds: usize) {
+        for _ in 0..seconds {
+            dut.update(&counts, false);
+        }
+    }
+
+    #[test]
+    fn classify_seconds() {
+        let mut dut = PerformanceMonitor::new();
+        run(&mut dut, crc6(1), 1);
+        run(&mut dut, crc6(5), 1);
+        run(&mut dut, crc6(400), 1);
+        run(&mut dut, PmonCounts { receive_slips: 1, line_code_violations: 7, ..Default::default() }, 1);
+        run(&mut dut, PmonCounts { severely_errored_frames: 1, ..Default::default() }, 1);
+        dut.update(&PmonCounts::default(), true);
+        run(&mut dut, PmonCounts::default(), 10);
+
+        assert_eq!(dut.current().counters, PerformanceCounters {
+            es: 5, ses: 3, uas: 0, bes: 1, lcv: 7, css: 1,
+        });
+        assert_eq!(dut.current().seconds, 6);
+    }
+
+    #[test]
+    fn unavailable_time() {
+        let mut dut = PerformanceMonitor::new();
+
+        // Nine SES aren't enough...
+        run(&mut dut, crc6(400), 9);
+        run(&mut dut, crc6(0), 1);
+        // ...but ten are, and all ten count as unavailable.
+        run(&mut dut, crc6(400), 15);
+        assert!(dut.is_unavailable());
+
+        // Errored seconds during unavailable time aren't counted as such.
+        run(&mut dut, crc6(3), 5);
+        run(&mut dut, crc6(400), 1);
+        // Ten good seconds end it, and those ten count as available.
+        run(&mut dut, crc6(0), 10);
+        assert!(!dut.is_unavailable());
+        run(&mut dut, crc6(0), 10);
+
+        assert_eq!(dut.current().counters, PerformanceCounters {
+            es: 9, ses: 9, uas: 15 + 5 + 1, bes: 0, lcv: 0, css: 0,
+        });
+    }
+
+    #[test]
+    fn interval_history() {
+        let mut dut = PerformanceMonitor::new();
+        let seconds = PerformanceMonitor::INTERVAL_SECONDS as usize;
+
+        run(&mut dut, crc6(1), seconds * 100 + 10);
+        assert_eq!(dut.intervals().count(), PerformanceMonitor::INTERVALS_MAX);
+        assert_eq!(dut.current().seconds, 0);
+
+        let total = dut.total();
+        assert_eq!(total.seconds, PerformanceMonitor::INTERVAL_SECONDS * 96);
+        assert_eq!(total.counters.es, PerformanceMonitor::INTERVAL_SECONDS * 96);
+    }
+}
@@ -0,0 +1,137 @@
+use std::time::Instant;
+
+use super::FramerEvent;
+use super::control::{self, FramerControl};
+use super::device::{Channel, Result};
+use super::hdlc::HdlcTransmitter;
+use super::q921::{DataLink, DataLinkOutput, Side};
+use super::q931::{CallControl, CallControlOutput, CallEvent};
+use super::register::*;
+
+/// Timeslot 24 carries the D-channel on a 23B+D PRI.
+pub const D_CHANNEL_TIMESLOT: usize = 23;
+
+/// LAPD controller 2, counting from zero. Controller 1 is left to the ESF
+/// facility data link.
+pub const D_CHANNEL_HDLC_INDEX: usize = 1;
+
+/// Route timeslot 24 through LAPD controller 2, in both directions.
+pub fn configure_d_channel(channel: &Channel) -> Result<()> {
+    let timeslot = channel.timeslot(D_CHANNEL_TIMESLOT);
+
+    timeslot.tccr().modify(|m| m
+        .with_LAPDcntl(TransmitLAPDSource::LAPDController2)
+    )?;
+    timeslot.rccr().modify(|m| m
+        .with_LAPDcntl(ReceiveLAPDSource::LAPDController2)
+    )?;
+
+    // Robbed bits would corrupt every sixth frame of a clear channel.
+    timeslot.tscr().modify(|m| m
+        .with_Rob_Enb(0)
+    )?;
+
+    channel.lapdsr().modify(|m| m
+        .with_HDLC2en(1)
+    )?;
+
+    channel.dlcr(D_CHANNEL_HDLC_INDEX).modify(|m| m
+        .with_MOS_BOSn(1)
+        .with_Tx_FCS_EN(1)
+    )
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum DChannelEvent {
+    DataLink(DataLinkOutput),
+    Call(CallEvent),
+}
+
+/// ISDN PRI D-channel on one span: Q.921 over the framer's LAPD controller,
+/// Q.931 on top.
+///
+pub struct DChannel {
+    channel_index: usize,
+    data_link: DataLink,
+    call_control: CallControl,
+    transmitter: HdlcTransmitter,
+}
+
+impl DChannel {
+    pub fn new(channel_index: usize, side: Side) -> Self {
+        Self {
+            channel_index,
+            data_link: DataLink::new(side),
+            call_control: CallControl::new(side),
+            transmitter: HdlcTransmitter::new(),
+        }
+    }
+
+    pub fn data_link(&mut self) -> &mut DataLink {
+        &mut self.data_link
+    }
+
+    pub fn call_control(&mut self) -> &mut CallControl {
+        &mut self.call_control
+    }
+
+    pub fn handle_event(&mut self, event: &FramerEvent, now: Instant) {
+        match *event {
+            FramerEvent::HdlcFrame { channel_index, controller_index, ref data, length }
+                if channel_index == self.channel_index && controller_index == D_CHANNEL_HDLC_INDEX => {
+                self.data_link.receive(&data[0..length], now);
+            },
+            FramerEvent::HdlcTransmitComplete { channel_index, controller_index }
+                if channel_index == self.channel_index && controller_index == D_CHANNEL_HDLC_INDEX => {
+                self.transmitter.transmit_complete();
+            },
+            _ => {},
+        }
+    }
+
+    pub fn tick(&mut self, now: Instant) {
+        self.data_link.tick(now);
+    }
+
+    /// Pass messages between the layers until they settle, then hand the
+    /// next frame to the framer if it's ready for one.
+    pub fn process<F>(&mut self, control: &FramerControl, now: Instant, mut event_fn: F) -> control::Result<()>
+        where F: FnMut(DChannelEvent)
+    {
+        loop {
+            let mut idle = true;
+
+            while let Some(output) = self.call_control.poll_output() {
+                idle = false;
+                match output {
+                    CallControlOutput::Transmit(message) => {
+                        if let Err(e) = self.data_link.data_request(&message, now) {
+                            eprintln!("pri: q921: {e}");
+                        }
+                    },
+                    CallControlOutput::Event(event) => event_fn(DChannelEvent::Call(event)),
+                }
+            }
+
+            while let Some(output) = self.data_link.poll_output() {
+                idle = false;
+                match output {
+                    DataLinkOutput::Transmit(frame) => self.transmitter.push(&frame),
+                    DataLinkOutput::DataIndication(message) | DataLinkOutput::UnitDataIndication(message) => {
+                        if let Err(e) = self.call_control.receive(&message) {
+                            eprintln!("pri: q931: {e}");
+                        }
+                    },
+                    other => event_fn(DChannelEvent::DataLink(other)),
+                }
+            }
+
+            if idle {
+                break;
+            }
+        }
+
+        let channel_index = self.channel_index;
+        self.transmitter.poll(now, |frame| control.hdlc_transmit(channel_index, D_CHANNEL_HDLC_INDEX, frame))
+    }
+}
@@ -0,0 +1,254 @@
+use std::collections::VecDeque;
+
+/// Range of CRC error events reported for one second, per ANSI T1.403 PRM bits G1-G6.
+///
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum CrcErrorEvents {
+    None,
+    One,
+    TwoToFive,
+    SixToTen,
+    ElevenToOneHundred,
+    OneHundredOneTo319,
+    AtLeast320,
+}
+
+/// One second of a performance report message, as seen by the far end.
+///
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct PerformanceReportSecond {
+    pub crc_errors: CrcErrorEvents,
+    pub severely_errored_framing: bool,
+    pub frame_sync_bit_error: bool,
+    pub line_code_violation: bool,
+    pub slip: bool,
+    pub payload_loopback: bool,
+    pub sequence: u8,
+}
+
+impl PerformanceReportSecond {
+    // Octet A: G3 LV G4 U1 U2 G5 SL G6
+    // Octet B: FE SE LB G1 R  G2 Nm Nl
+    fn from_octets(a: u8, b: u8) -> Self {
+        let bit = |v: u8, n: u8| (v >> n) & 1 != 0;
+
+        // If the far end sets more than one G bit, believe the worst.
+        let crc_errors = if bit(a, 0) {
+            CrcErrorEvents::AtLeast320
+        } else if bit(a, 2) {
+            CrcErrorEvents::OneHundredOneTo319
+        } else if bit(a, 5) {
+            CrcErrorEvents::ElevenToOneHundred
+        } else if bit(a, 7) {
+            CrcErrorEvents::SixToTen
+        } else if bit(b, 2) {
+            CrcErrorEvents::TwoToFive
+        } else if bit(b, 4) {
+            CrcErrorEvents::One
+        } else {
+            CrcErrorEvents::None
+        };
+
+        Self {
+            crc_errors,
+            severely_errored_framing: bit(b, 6),
+            frame_sync_bit_error: bit(b, 7),
+            line_code_violation: bit(a, 6),
+            slip: bit(a, 1),
+            payload_loopback: bit(b, 5),
+            sequence: b & 3,
+        }
+    }
+
+    /// Far-end errored second: any CRC error or severely errored framing event.
+    pub fn errored(&self) -> bool {
+        self.crc_errors != CrcErrorEvents::None || self.severely_errored_framing
+    }
+
+    /// Far-end severely errored second: 320 or more CRC errors, or severely errored framing.
+    pub fn severely_errored(&self) -> bool {
+        self.crc_errors == CrcErrorEvents::AtLeast320 || self.severely_errored_framing
+    }
+}
+
+/// ANSI T1.403 performance report message (PRM), received on the ESF facility
+/// data link once per second. Each message carries the current second and the
+/// three before it, so a few lost messages don't leave a hole in the history.
+///
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct PerformanceReport {
+    /// C/R bit: set if the message was sent by the carrier, clear if sent by the CI.
+    pub from_carrier: bool,
+    /// Seconds t0, t0-1, t0-2, t0-3.
+    pub seconds: [PerformanceReportSecond; 4],
+}
+
+impl PerformanceReport {
+    const SAPI: u8 = 14;
+    const TEI: u8 = 0;
+    const CONTROL_UI: u8 = 0x03;
+
+    /// Address, control, and four seconds of two octets each.
+    const LENGTH: usize = 2 + 1 + 4 * 2;
+
+    /// Parse an HDLC frame from the FDL, which may or may not still have the FCS attached.
+    pub fn from_slice(b: &[u8]) -> Option<Self> {
+        if b.len() != Self::LENGTH && b.len() != Self::LENGTH + 2 {
+            return None;
+        }
+
+        let sapi = b[0] >> 2;
+        let from_carrier = (b[0] >> 1) & 1 != 0;
+        let ea0 = b[0] & 1;
+        let tei = b[1] >> 1;
+        let ea1 = b[1] & 1;
+        if sapi != Self::SAPI || ea0 != 0 || tei != Self::TEI || ea1 != 1 || b[2] != Self::CONTROL_UI {
+            return None;
+        }
+
+        let second = |n: usize| PerformanceReportSecond::from_octets(b[3 + n * 2], b[4 + n * 2]);
+
+        Some(Self {
+            from_carrier,
+            seconds: [second(0), second(1), second(2), second(3)],
+        })
+    }
+}
+
+/// Per-channel history of far-end performance, one entry per second.
+///
+pub struct PerformanceReportHistory {
+    seconds: VecDeque<PerformanceReportSecond>,
+    last_sequence: Option<u8>,
+}
+
+impl PerformanceReportHistory {
+    /// Fifteen minutes.
+    const CAPACITY: usize = 900;
+
+    pub fn new() -> Self {
+        Self {
+            seconds: VecDeque::with_capacity(Self::CAPACITY),
+            last_sequence: None,
+        }
+    }
+
+    /// Add the new second(s) from a report to the history. Returns the number of seconds added.
+    pub fn update(&mut self, report: &PerformanceReport) -> usize {
+        let t0 = report.seconds[0];
+
+        let count = match self.last_sequence {
+            // A repeated report doesn't tell us anything new.
+            Some(last) if last == t0.sequence => return 0,
+            // Backfill from the older seconds if we missed a report or two.
+            Some(last) => (t0.sequence.wrapping_sub(last) & 3) as usize,
+            None => 1,
+        };
+
+        for second in report.seconds[0..count].iter().rev() {
+            if self.seconds.len() == Self::CAPACITY {
+                self.seconds.pop_front();
+            }
+            self.seconds.push_back(*second);
+        }
+        self.last_sequence = Some(t0.sequence);
+
+        count
+    }
+
+    /// Seconds, oldest first.
+    pub fn seconds(&self) -> impl Iterator<Item=&PerformanceReportSecond> {
+        self.seconds.iter()
+    }
+
+    pub fn errored_seconds(&self) -> usize {
+        self.seconds().filter(|s| s.errored()).count()
+    }
+
+    pub fn severely_errored_seconds(&self) -> usize {
+        self.seconds().filter(|s| s.severely_errored()).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(from_carrier: bool, octets: [u8; 8]) -> Vec<u8> {
+        let mut b = vec![0x38 | ((from_carrier as u8) << 1), 0x01, 0x03];
+        b.extend_from_slice(&octets);
+        b
+    }
+
+    #[test]
+    fn parse() {
+        let b = report(true, [
+            0x00, 0x12,     // G1, sequence 2
+            0x42, 0x01,     // LV, SL, sequence 1
+            0x01, 0xc0,     // G6, FE, SE, sequence 0
+            0x00, 0x23,     // LB, sequence 3
+        ]);
+        let r = PerformanceReport::from_slice(&b).unwrap();
+        assert!(r.from_carrier);
+
+        assert_eq!(r.seconds[0].crc_errors, CrcErrorEvents::One);
+        assert_eq!(r.seconds[0].sequence, 2);
+        assert!(r.seconds[0].errored());
+        assert!(!r.seconds[0].severely_errored());
+
+        assert_eq!(r.seconds[1].crc_errors, CrcErrorEvents::None);
+        assert!(r.seconds[1].line_code_violation);
+        assert!(r.seconds[1].slip);
+        assert!(!r.seconds[1].errored());
+
+        assert_eq!(r.seconds[2].crc_errors, CrcErrorEvents::AtLeast320);
+        assert!(r.seconds[2].frame_sync_bit_error);
+        assert!(r.seconds[2].severely_errored_framing);
+        assert!(r.seconds[2].severely_errored());
+
+        assert!(r.seconds[3].payload_loopback);
+        assert_eq!(r.seconds[3].sequence, 3);
+    }
+
+    #[test]
+    fn parse_with_fcs() {
+        let mut b = report(false, [0; 8]);
+        b.extend_from_slice(&[0xaa, 0x55]);
+        let r = PerformanceReport::from_slice(&b).unwrap();
+        assert!(!r.from_carrier);
+    }
+
+    #[test]
+    fn parse_rejects_other_frames() {
+        let mut b = report(false, [0; 8]);
+        b[0] = 0x00;    // SAPI 0
+        assert_eq!(PerformanceReport::from_slice(&b), None);
+
+        let b = report(false, [0; 8]);
+        assert_eq!(PerformanceReport::from_slice(&b[..10]), None);
+    }
+
+    #[test]
+    fn history_backfills_missed_reports() {
+        let mut dut = PerformanceReportHistory::new();
+
+        let r = |seq: [u8; 4], g1: [bool; 4]| {
+            let mut octets = [0u8; 8];
+            for n in 0..4 {
+                octets[n * 2 + 1] = seq[n] | if g1[n] { 0x10 } else { 0x00 };
+            }
+            PerformanceReport::from_slice(&report(false, octets)).unwrap()
+        };
+
+        assert_eq!(dut.update(&r([0, 3, 2, 1], [true,  false, false, false])), 1);
+        assert_eq!(dut.update(&r([0, 3, 2, 1], [true,  false, false, false])), 0);
+        assert_eq!(dut.update(&r([1, 0, 3, 2], [false, true,  false, false])), 1);
+        // Missed sequence 2.
+        assert_eq!(dut.update(&r([3, 2, 1, 0], [false, true,  false, true ])), 2);
+
+        let sequences: Vec<u8> = dut.seconds().map(|s| s.sequence).collect();
+        assert_eq!(sequences, [0, 1, 2, 3]);
+        assert_eq!(dut.errored_seconds(), 2);
+        assert_eq!(dut.severely_errored_seconds(), 0);
+    }
+}
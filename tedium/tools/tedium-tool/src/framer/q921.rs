@@ -0,0 +1,697 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use thiserror::Error;
+
+use super::control::HDLC_TRANSMIT_BYTES_MAX;
+
+/// Which end of the PRI we are. Determines the sense of the C/R bit,
+/// and later, who owns B-channel selection and call reference flags.
+///
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Side {
+    User,
+    Network,
+}
+
+/// Q.921 frame control field.
+///
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Control {
+    I { ns: u8, nr: u8, p: bool },
+    RR { nr: u8, pf: bool },
+    Rnr { nr: u8, pf: bool },
+    Rej { nr: u8, pf: bool },
+    Sabme { p: bool },
+    UA { f: bool },
+    Disc { p: bool },
+    DM { f: bool },
+    Frmr { f: bool },
+    UI,
+    Unknown(u8),
+}
+
+/// A LAPD frame, without the FCS.
+///
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Frame {
+    pub sapi: u8,
+    pub command_response: bool,
+    pub tei: u8,
+    pub control: Control,
+    pub information: Vec<u8>,
+}
+
+impl Frame {
+    pub fn from_slice(b: &[u8]) -> Option<Self> {
+        if b.len() < 3 {
+            return None;
+        }
+
+        // Two-octet address: EA0 must be clear on the first octet and set on the second.
+        if b[0] & 1 != 0 || b[1] & 1 != 1 {
+            return None;
+        }
+        let sapi = b[0] >> 2;
+        let command_response = (b[0] >> 1) & 1 != 0;
+        let tei = b[1] >> 1;
+
+        let c0 = b[2];
+        let (control, information_offset) = if c0 & 1 == 0 {
+            let c1 = *b.get(3)?;
+            (Control::I { ns: c0 >> 1, nr: c1 >> 1, p: c1 & 1 != 0 }, 4)
+        } else if c0 & 3 == 1 {
+            let c1 = *b.get(3)?;
+            let (nr, pf) = (c1 >> 1, c1 & 1 != 0);
+            let control = match c0 {
+                0x01 => Control::RR { nr, pf },
+                0x05 => Control::Rnr { nr, pf },
+                0x09 => Control::Rej { nr, pf },
+                _ => Control::Unknown(c0),
+            };
+            (control, 4)
+        } else {
+            let pf = c0 & 0x10 != 0;
+            let control = match c0 & !0x10 {
+                0x6f => Control::Sabme { p: pf },
+                0x63 => Control::UA { f: pf },
+                0x43 => Control::Disc { p: pf },
+                0x0f => Control::DM { f: pf },
+                0x87 => Control::Frmr { f: pf },
+                0x03 => Control::UI,
+                _ => Control::Unknown(c0),
+            };
+            (control, 3)
+        };
+
+        Some(Self {
+            sapi,
+            command_response,
+            tei,
+            control,
+            information: b[information_offset..].to_vec(),
+        })
+    }
+
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut b = vec![
+            (self.sapi << 2) | ((self.command_response as u8) << 1),
+            (self.tei << 1) | 1,
+        ];
+
+        let s = |b: &mut Vec<u8>, c0: u8, nr: u8, pf: bool| b.extend_from_slice(&[c0, (nr << 1) | pf as u8]);
+        let u = |b: &mut Vec<u8>, c0: u8, pf: bool| b.push(c0 | ((pf as u8) << 4));
+
+        match self.control {
+            Control::I { ns, nr, p } => b.extend_from_slice(&[ns << 1, (nr << 1) | p as u8]),
+            Control::RR { nr, pf }   => s(&mut b, 0x01, nr, pf),
+            Control::Rnr { nr, pf }  => s(&mut b, 0x05, nr, pf),
+            Control::Rej { nr, pf }  => s(&mut b, 0x09, nr, pf),
+            Control::Sabme { p }     => u(&mut b, 0x6f, p),
+            Control::UA { f }        => u(&mut b, 0x63, f),
+            Control::Disc { p }      => u(&mut b, 0x43, p),
+            Control::DM { f }        => u(&mut b, 0x0f, f),
+            Control::Frmr { f }      => u(&mut b, 0x87, f),
+            Control::UI              => b.push(0x03),
+            Control::Unknown(c0)     => b.push(c0),
+        }
+
+        b.extend_from_slice(&self.information);
+        b
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DataLinkState {
+    /// TEI assigned, no multiple-frame operation (Q.921 state 4).
+    Released,
+    /// SABME sent, waiting for UA (state 5).
+    AwaitingEstablishment,
+    /// Multiple-frame operation (state 7).
+    Established,
+    /// T200 expired, polling the peer (state 8).
+    TimerRecovery,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Error)]
+pub enum DataLinkError {
+    #[error("{0} byte message is longer than N201 ({max})", max = DataLink::N201)]
+    MessageTooLong(usize),
+    #[error("N(R) {0} isn't between V(A) and V(S), re-establishing")]
+    SequenceError(u8),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum DataLinkOutput {
+    /// Frame to hand to the HDLC controller.
+    Transmit(Vec<u8>),
+    EstablishIndication,
+    EstablishConfirm,
+    ReleaseIndication,
+    /// MDL-ERROR indication.
+    ErrorIndication(DataLinkError),
+    /// Layer 3 message received in an I-frame.
+    DataIndication(Vec<u8>),
+    /// Layer 3 message received in a UI frame.
+    UnitDataIndication(Vec<u8>),
+}
+
+/// Q.921 data link for one D-channel, SAPI 0, TEI 0 (point-to-point PRI).
+///
+/// Time is passed in rather than read, so the timers can be driven from
+/// tests. Call `tick()` regularly to expire T200 and T203.
+///
+pub struct DataLink {
+    side: Side,
+    state: DataLinkState,
+
+    /// V(S), V(A), V(R), all modulo 128.
+    send_state: u8,
+    acknowledge_state: u8,
+    receive_state: u8,
+
+    peer_busy: bool,
+    reject_sent: bool,
+    retransmission_count: u32,
+
+    t200: Option<Instant>,
+    t203: Option<Instant>,
+
+    /// Layer 3 messages not yet sent as I-frames.
+    queue: VecDeque<Vec<u8>>,
+    /// I-frames sent but not yet acknowledged, in N(S) order starting at V(A).
+    unacknowledged: VecDeque<Vec<u8>>,
+
+    outputs: VecDeque<DataLinkOutput>,
+}
+
+impl DataLink {
+    pub const SAPI_CALL_CONTROL: u8 = 0;
+    pub const TEI: u8 = 0;
+
+    const T200: Duration = Duration::from_secs(1);
+    const T203: Duration = Duration::from_secs(10);
+    const N200: u32 = 3;
+    /// Maximum outstanding I-frames.
+    const K: u8 = 7;
+    /// Longest I-frame information field: what's left of the firmware's
+    /// transmit buffer after the address and control fields. The framer
+    /// adds the FCS.
+    pub const N201: usize = HDLC_TRANSMIT_BYTES_MAX - 4;
+
+    pub fn new(side: Side) -> Self {
+        Self {
+            side,
+            state: DataLinkState::Released,
+            send_state: 0,
+            acknowledge_state: 0,
+            receive_state: 0,
+            peer_busy: false,
+            reject_sent: false,
+            retransmission_count: 0,
+            t200: None,
+            t203: None,
+            queue: VecDeque::new(),
+            unacknowledged: VecDeque::new(),
+            outputs: VecDeque::new(),
+        }
+    }
+
+    pub fn poll_output(&mut self) -> Option<DataLinkOutput> {
+        self.outputs.pop_front()
+    }
+
+    /// DL-ESTABLISH request.
+    pub fn establish(&mut self, now: Instant) {
+        self.retransmission_count = 0;
+        self.send_sabme(now);
+    }
+
+    /// DL-DATA request. There's no segmentation, so a message has to fit in
+    /// one I-frame.
+    pub fn data_request(&mut self, message: &[u8], now: Instant) -> Result<(), DataLinkError> {
+        if message.len() > Self::N201 {
+            return Err(DataLinkError::MessageTooLong(message.len()));
+        }
+        self.queue.push_back(message.to_vec());
+        self.send_queued(now);
+        Ok(())
+    }
+
+    pub fn tick(&mut self, now: Instant) {
+        if matches!(self.t200, Some(t) if now >= t) {
+            self.t200 = None;
+            self.t200_expired(now);
+        }
+        if matches!(self.t203, Some(t) if now >= t) {
+            self.t203 = None;
+            if self.state == DataLinkState::Established {
+                self.enter_timer_recovery(now);
+            }
+        }
+    }
+
+    pub fn receive(&mut self, frame: &[u8], now: Instant) {
+        let frame = match Frame::from_slice(frame) {
+            Some(frame) => frame,
+            None => return,
+        };
+        if frame.sapi != Self::SAPI_CALL_CONTROL || frame.tei != Self::TEI {
+            return;
+        }
+
+        // The peer's commands carry the C/R value we use for responses.
+        let command = frame.command_response == self.response_cr();
+
+        match frame.control {
+            Control::Sabme { p } => {
+                self.send_response(Control::UA { f: p });
+                self.reset_variables();
+                self.t200 = None;
+                let previous = self.state;
+                self.state = DataLinkState::Established;
+                self.t203 = Some(now + Self::T203);
+                if previous != DataLinkState::Established && previous != DataLinkState::TimerRecovery {
+                    self.outputs.push_back(DataLinkOutput::EstablishIndication);
+                }
+            },
+            Control::Disc { p } => {
+                if self.is_multiple_frame() {
+                    self.send_response(Control::UA { f: p });
+                    self.enter_released(DataLinkOutput::ReleaseIndication);
+                } else {
+                    // No link to release.
+                    self.send_response(Control::DM { f: p });
+                }
+            },
+            Control::UA { f: _ } if self.state == DataLinkState::AwaitingEstablishment => {
+                self.reset_variables();
+                self.t200 = None;
+                self.t203 = Some(now + Self::T203);
+                self.state = DataLinkState::Established;
+                self.outputs.push_back(DataLinkOutput::EstablishConfirm);
+                self.send_queued(now);
+            },
+            Control::DM { f: _ } => {
+                match self.state {
+                    DataLinkState::AwaitingEstablishment => {
+                        self.enter_released(DataLinkOutput::ReleaseIndication);
+                    },
+                    DataLinkState::Established | DataLinkState::TimerRecovery => {
+                        // Peer lost its state. Try to bring the link back up.
+                        self.retransmission_count = 0;
+                        self.send_sabme(now);
+                    },
+                    _ => {},
+                }
+            },
+            Control::UI => {
+                self.outputs.push_back(DataLinkOutput::UnitDataIndication(frame.information));
+            },
+            Control::I { nr, .. } | Control::RR { nr, .. } | Control::Rnr { nr, .. } | Control::Rej { nr, .. }
+                if self.is_multiple_frame() && !self.nr_valid(nr) => {
+                // The peer acknowledged frames we haven't sent, so neither end
+                // can trust its sequence numbers.
+                self.outputs.push_back(DataLinkOutput::ErrorIndication(DataLinkError::SequenceError(nr)));
+                self.reestablish(now);
+            },
+            Control::I { ns, nr, p } if self.is_multiple_frame() => {
+                if ns == self.receive_state {
+                    self.receive_state = (self.receive_state + 1) % 128;
+                    self.reject_sent = false;
+                    self.outputs.push_back(DataLinkOutput::DataIndication(frame.information));
+                    self.send_response(Control::RR { nr: self.receive_state, pf: p });
+                } else if !self.reject_sent {
+                    self.reject_sent = true;
+                    self.send_response(Control::Rej { nr: self.receive_state, pf: p });
+                } else if p {
+                    self.send_response(Control::RR { nr: self.receive_state, pf: true });
+                }
+                self.acknowledge(nr, now);
+            },
+            Control::RR { nr, pf } | Control::Rnr { nr, pf } | Control::Rej { nr, pf } if self.is_multiple_frame() => {
+                self.peer_busy = matches!(frame.control, Control::Rnr { .. });
+
+                if command && pf {
+                    self.send_response(Control::RR { nr: self.receive_state, pf: true });
+                }
+
+                let recovered = self.state == DataLinkState::TimerRecovery && !command && pf;
+                let rejected = matches!(frame.control, Control::Rej { .. });
+
+                self.acknowledge(nr, now);
+
+                if recovered || rejected {
+                    if recovered {
+                        self.state = DataLinkState::Established;
+                        self.t200 = None;
+                        self.t203 = Some(now + Self::T203);
+                    }
+                    self.retransmit_unacknowledged(now);
+                }
+                self.send_queued(now);
+            },
+            _ => {},
+        }
+    }
+
+    fn is_multiple_frame(&self) -> bool {
+        matches!(self.state, DataLinkState::Established | DataLinkState::TimerRecovery)
+    }
+
+    /// C/R bit on commands we send: 0 from the user side, 1 from the network side.
+    fn command_cr(&self) -> bool {
+        self.side == Side::Network
+    }
+
+    fn response_cr(&self) -> bool {
+        !self.command_cr()
+    }
+
+    fn send(&mut self, command_response: bool, control: Control, information: &[u8]) {
+        let frame = Frame {
+            sapi: Self::SAPI_CALL_CONTROL,
+            command_response,
+            tei: Self::TEI,
+            control,
+            information: information.to_vec(),
+        };
+        self.outputs.push_back(DataLinkOutput::Transmit(frame.to_vec()));
+    }
+
+    fn send_command(&mut self, control: Control, information: &[u8]) {
+        self.send(self.command_cr(), control, information);
+    }
+
+    fn send_response(&mut self, control: Control) {
+        self.send(self.response_cr(), control, &[]);
+    }
+
+    fn send_sabme(&mut self, now: Instant) {
+        self.send_command(Control::Sabme { p: true }, &[]);
+        self.t200 = Some(now + Self::T200);
+        self.t203 = None;
+        self.state = DataLinkState::AwaitingEstablishment;
+    }
+
+    fn reset_variables(&mut self) {
+        self.send_state = 0;
+        self.acknowledge_state = 0;
+        self.receive_state = 0;
+        self.peer_busy = false;
+        self.reject_sent = false;
+        self.retransmission_count = 0;
+        self.unacknowledged.clear();
+    }
+
+    fn enter_released(&mut self, indication: DataLinkOutput) {
+        self.state = DataLinkState::Released;
+        self.t200 = None;
+        self.t203 = None;
+        self.queue.clear();
+        self.unacknowledged.clear();
+        self.outputs.push_back(indication);
+    }
+
+    fn enter_timer_recovery(&mut self, now: Instant) {
+        self.retransmission_count = 0;
+        self.state = DataLinkState::TimerRecovery;
+        self.send_enquiry(now);
+    }
+
+    fn send_enquiry(&mut self, now: Instant) {
+        self.send_command(Control::RR { nr: self.receive_state, pf: true }, &[]);
+        self.retransmission_count += 1;
+        self.t200 = Some(now + Self::T200);
+    }
+
+    fn t200_expired(&mut self, now: Instant) {
+        match self.state {
+            DataLinkState::AwaitingEstablishment => {
+                if self.retransmission_count < Self::N200 {
+                    self.retransmission_count += 1;
+                    self.send_sabme(now);
+                } else {
+                    self.enter_released(DataLinkOutput::ReleaseIndication);
+                }
+            },
+            DataLinkState::Established => {
+                self.enter_timer_recovery(now);
+            },
+            DataLinkState::TimerRecovery => {
+                if self.retransmission_count < Self::N200 {
+                    self.send_enquiry(now);
+                } else {
+                    // Peer has gone quiet.
+                    self.reestablish(now);
+                }
+            },
+            DataLinkState::Released => {},
+        }
+    }
+
+    /// Start multiple-frame operation over, keeping anything unsent.
+    fn reestablish(&mut self, now: Instant) {
+        let unacknowledged = std::mem::take(&mut self.unacknowledged);
+        for message in unacknowledged.into_iter().rev() {
+            self.queue.push_front(message);
+        }
+        self.retransmission_count = 0;
+        self.send_sabme(now);
+    }
+
+    fn outstanding(&self) -> u8 {
+        self.send_state.wrapping_sub(self.acknowledge_state) % 128
+    }
+
+    /// V(A) <= N(R) <= V(S).
+    fn nr_valid(&self, nr: u8) -> bool {
+        nr.wrapping_sub(self.acknowledge_state) % 128 <= self.outstanding()
+    }
+
+    fn acknowledge(&mut self, nr: u8, now: Instant) {
+        let acknowledged = nr.wrapping_sub(self.acknowledge_state) % 128;
+        for _ in 0..acknowledged {
+            self.unacknowledged.pop_front();
+        }
+        self.acknowledge_state = nr;
+
+        if self.state == DataLinkState::Established {
+            if self.outstanding() == 0 {
+                self.t200 = None;
+                self.t203 = Some(now + Self::T203);
+            } else if acknowledged > 0 {
+                self.t200 = Some(now + Self::T200);
+            }
+        }
+    }
+
+    fn send_i_frame(&mut self, ns: u8, message: &[u8], now: Instant) {
+        let control = Control::I { ns, nr: self.receive_state, p: false };
+        self.send_command(control, message);
+        if self.t200.is_none() {
+            self.t200 = Some(now + Self::T200);
+            self.t203 = None;
+        }
+    }
+
+    fn send_queued(&mut self, now: Instant) {
+        if self.state != DataLinkState::Established {
+            return;
+        }
+
+        while !self.peer_busy && self.outstanding() < Self::K {
+            let message = match self.queue.pop_front() {
+                Some(message) => message,
+                None => break,
+            };
+            let ns = self.send_state;
+            self.send_i_frame(ns, &message, now);
+            self.unacknowledged.push_back(message);
+            self.send_state = (self.send_state + 1) % 128;
+        }
+    }
+
+    fn retransmit_unacknowledged(&mut self, now: Instant) {
+        let messages: Vec<Vec<u8>> = self.unacknowledged.iter().cloned().collect();
+        for (i, message) in messages.iter().enumerate() {
+            let ns = (self.acknowledge_state + i as u8) % 128;
+            self.send_i_frame(ns, message, now);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run both ends until neither has anything left to say, and return everything
+    /// else either end reported.
+    fn exchange(user: &mut DataLink, network: &mut DataLink, now: Instant) -> (Vec<DataLinkOutput>, Vec<DataLinkOutput>) {
+        let mut user_outputs = Vec::new();
+        let mut network_outputs = Vec::new();
+        loop {
+            let mut idle = true;
+            while let Some(output) = user.poll_output() {
+                idle = false;
+                match output {
+                    DataLinkOutput::Transmit(frame) => network.receive(&frame, now),
+                    other => user_outputs.push(other),
+                }
+            }
+            while let Some(output) = network.poll_output() {
+                idle = false;
+                match output {
+                    DataLinkOutput::Transmit(frame) => user.receive(&frame, now),
+                    other => network_outputs.push(other),
+                }
+            }
+            if idle {
+                return (user_outputs, network_outputs);
+            }
+        }
+    }
+
+    fn transmitted(dut: &mut DataLink) -> Vec<Frame> {
+        let mut frames = Vec::new();
+        while let Some(output) = dut.poll_output() {
+            if let DataLinkOutput::Transmit(frame) = output {
+                frames.push(Frame::from_slice(&frame).unwrap());
+            }
+        }
+        frames
+    }
+
+    #[test]
+    fn frame_round_trip() {
+        let frames = [
+            Frame { sapi: 0, command_response: false, tei: 0, control: Control::Sabme { p: true }, information: vec![] },
+            Frame { sapi: 0, command_response: true, tei: 0, control: Control::UA { f: true }, information: vec![] },
+            Frame { sapi: 0, command_response: false, tei: 0, control: Control::I { ns: 5, nr: 127, p: false }, information: vec![0x08, 0x02] },
+            Frame { sapi: 0, command_response: true, tei: 0, control: Control::Rnr { nr: 3, pf: true }, information: vec![] },
+            Frame { sapi: 63, command_response: false, tei: 127, control: Control::UI, information: vec![1, 2, 3] },
+        ];
+        for frame in &frames {
+            assert_eq!(Frame::from_slice(&frame.to_vec()).unwrap(), *frame);
+        }
+
+        // User-side SABME, as seen on the wire.
+        assert_eq!(frames[0].to_vec(), [0x00, 0x01, 0x7f]);
+    }
+
+    #[test]
+    fn establish_and_transfer() {
+        let now = Instant::now();
+        let mut user = DataLink::new(Side::User);
+        let mut network = DataLink::new(Side::Network);
+
+        user.establish(now);
+        let (u, n) = exchange(&mut user, &mut network, now);
+        assert_eq!(u, [DataLinkOutput::EstablishConfirm]);
+        assert_eq!(n, [DataLinkOutput::EstablishIndication]);
+        assert_eq!(user.state, DataLinkState::Established);
+        assert_eq!(network.state, DataLinkState::Established);
+
+        user.data_request(&[1], now).unwrap();
+        user.data_request(&[2], now).unwrap();
+        network.data_request(&[3], now).unwrap();
+        let (u, n) = exchange(&mut user, &mut network, now);
+        assert_eq!(u, [DataLinkOutput::DataIndication(vec![3])]);
+        assert_eq!(n, [DataLinkOutput::DataIndication(vec![1]), DataLinkOutput::DataIndication(vec![2])]);
+
+        // Everything acknowledged, so T200 isn't running and nothing happens later.
+        user.tick(now + Duration::from_secs(2));
+        assert!(transmitted(&mut user).is_empty());
+
+        // Longer than the firmware can send.
+        let message = [0u8; DataLink::N201 + 1];
+        assert_eq!(user.data_request(&message, now), Err(DataLinkError::MessageTooLong(93)));
+        assert!(user.data_request(&message[..DataLink::N201], now).is_ok());
+    }
+
+    #[test]
+    fn disc() {
+        let now = Instant::now();
+        let mut dut = DataLink::new(Side::User);
+        let disc = Frame { sapi: 0, command_response: true, tei: 0, control: Control::Disc { p: true }, information: vec![] };
+
+        // Nothing to release.
+        dut.receive(&disc.to_vec(), now);
+        assert_eq!(transmitted(&mut dut)[0].control, Control::DM { f: true });
+
+        let sabme = Frame { control: Control::Sabme { p: true }, ..disc.clone() };
+        dut.receive(&sabme.to_vec(), now);
+        assert_eq!(transmitted(&mut dut)[0].control, Control::UA { f: true });
+
+        dut.receive(&disc.to_vec(), now);
+        assert_eq!(dut.poll_output().map(|o| matches!(o, DataLinkOutput::Transmit(_))), Some(true));
+        assert_eq!(dut.poll_output(), Some(DataLinkOutput::ReleaseIndication));
+        assert_eq!(dut.state, DataLinkState::Released);
+    }
+
+    #[test]
+    fn nr_sequence_error_reestablishes() {
+        let now = Instant::now();
+        let mut user = DataLink::new(Side::User);
+        let mut network = DataLink::new(Side::Network);
+
+        user.establish(now);
+        exchange(&mut user, &mut network, now);
+        user.data_request(&[1], now).unwrap();
+        transmitted(&mut user);
+
+        // Acknowledges two frames, when only one was sent.
+        let rr = Frame { sapi: 0, command_response: false, tei: 0, control: Control::RR { nr: 2, pf: false }, information: vec![] };
+        user.receive(&rr.to_vec(), now);
+        assert_eq!(user.poll_output(), Some(DataLinkOutput::ErrorIndication(DataLinkError::SequenceError(2))));
+        assert_eq!(user.state, DataLinkState::AwaitingEstablishment);
+
+        // The unacknowledged frame goes again once the link is back.
+        let (u, n) = exchange(&mut user, &mut network, now);
+        assert_eq!(u, [DataLinkOutput::EstablishConfirm]);
+        assert_eq!(n, [DataLinkOutput::DataIndication(vec![1])]);
+    }
+
+    #[test]
+    fn establishment_gives_up_after_n200() {
+        let now = Instant::now();
+        let mut dut = DataLink::new(Side::User);
+
+        dut.establish(now);
+        assert_eq!(transmitted(&mut dut).len(), 1);
+
+        for n in 1..=3 {
+            dut.tick(now + DataLink::T200 * n);
+            let frames = transmitted(&mut dut);
+            assert_eq!(frames.len(), 1);
+            assert_eq!(frames[0].control, Control::Sabme { p: true });
+        }
+
+        dut.tick(now + DataLink::T200 * 4);
+        assert_eq!(dut.poll_output(), Some(DataLinkOutput::ReleaseIndication));
+        assert_eq!(dut.state, DataLinkState::Released);
+    }
+
+    #[test]
+    fn lost_i_frame_recovered_by_enquiry() {
+        let now = Instant::now();
+        let mut user = DataLink::new(Side::User);
+        let mut network = DataLink::new(Side::Network);
+
+        user.establish(now);
+        exchange(&mut user, &mut network, now);
+
+        // I-frame lost on the way to the network.
+        user.data_request(&[7], now).unwrap();
+        assert_eq!(transmitted(&mut user).len(), 1);
+
+        // T200 expires, user polls with RR P=1, network answers, user retransmits.
+        let later = now + DataLink::T200;
+        user.tick(later);
+        assert_eq!(user.state, DataLinkState::TimerRecovery);
+        let (u, n) = exchange(&mut user, &mut network, later);
+        assert!(u.is_empty());
+        assert_eq!(n, [DataLinkOutput::DataIndication(vec![7])]);
+        assert_eq!(user.state, DataLinkState::Established);
+    }
+}
@@ -0,0 +1,787 @@
+use std::collections::{HashMap, VecDeque};
+
+use thiserror::Error;
+
+use super::q921::Side;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum Q931Error {
+    #[error("message truncated")]
+    Truncated,
+    #[error("protocol discriminator {0:#04x} is not Q.931")]
+    ProtocolDiscriminator(u8),
+    #[error("call reference length {0} not supported")]
+    CallReferenceLength(u8),
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum MessageType {
+    Alerting,
+    CallProceeding,
+    Connect,
+    ConnectAcknowledge,
+    Setup,
+    Disconnect,
+    Release,
+    ReleaseComplete,
+    Status,
+    StatusEnquiry,
+    Unknown(u8),
+}
+
+impl MessageType {
+    fn from_u8(v: u8) -> Self {
+        match v {
+            0x01 => Self::Alerting,
+            0x02 => Self::CallProceeding,
+            0x07 => Self::Connect,
+            0x0f => Self::ConnectAcknowledge,
+            0x05 => Self::Setup,
+            0x45 => Self::Disconnect,
+            0x4d => Self::Release,
+            0x5a => Self::ReleaseComplete,
+            0x7d => Self::Status,
+            0x75 => Self::StatusEnquiry,
+            v    => Self::Unknown(v),
+        }
+    }
+
+    fn to_u8(self) -> u8 {
+        match self {
+            Self::Alerting           => 0x01,
+            Self::CallProceeding     => 0x02,
+            Self::Connect            => 0x07,
+            Self::ConnectAcknowledge => 0x0f,
+            Self::Setup              => 0x05,
+            Self::Disconnect         => 0x45,
+            Self::Release            => 0x4d,
+            Self::ReleaseComplete    => 0x5a,
+            Self::Status             => 0x7d,
+            Self::StatusEnquiry      => 0x75,
+            Self::Unknown(v)         => v,
+        }
+    }
+}
+
+/// Call reference value, and the flag saying which side allocated it.
+/// PRI always uses two-octet call references.
+///
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct CallReference {
+    pub value: u16,
+    /// Set on messages sent by the side that did *not* originate the call reference.
+    pub from_destination: bool,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum BearerCapability {
+    Speech,
+    UnrestrictedDigital,
+    Audio3_1kHz,
+}
+
+/// Cause values we generate. Others are passed through as numbers.
+pub mod cause {
+    pub const NORMAL_CALL_CLEARING: u8 = 16;
+    pub const RESPONSE_TO_STATUS_ENQUIRY: u8 = 30;
+    pub const MESSAGE_NOT_COMPATIBLE_WITH_CALL_STATE: u8 = 101;
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum InformationElement {
+    SendingComplete,
+    BearerCapability(BearerCapability),
+    Cause { location: u8, value: u8 },
+    /// B-channel 1 through 23, on the interface carrying the D-channel.
+    ChannelIdentification { exclusive: bool, channel: u8 },
+    ProgressIndicator { location: u8, description: u8 },
+    Display(String),
+    CallingPartyNumber { type_plan: u8, digits: String },
+    CalledPartyNumber { type_plan: u8, digits: String },
+    Unknown { identifier: u8, contents: Vec<u8> },
+}
+
+impl InformationElement {
+    const SENDING_COMPLETE: u8 = 0xa1;
+    const BEARER_CAPABILITY: u8 = 0x04;
+    const CAUSE: u8 = 0x08;
+    const CHANNEL_IDENTIFICATION: u8 = 0x18;
+    const PROGRESS_INDICATOR: u8 = 0x1e;
+    const DISPLAY: u8 = 0x28;
+    const CALLING_PARTY_NUMBER: u8 = 0x6c;
+    const CALLED_PARTY_NUMBER: u8 = 0x70;
+
+    /// Unknown number type, ISDN/telephony numbering plan.
+    pub const TYPE_PLAN_UNKNOWN_ISDN: u8 = 0x81;
+
+    fn decode(identifier: u8, contents: &[u8]) -> Self {
+        let unknown = || Self::Unknown { identifier, contents: contents.to_vec() };
+        let digits = |b: &[u8]| String::from_utf8_lossy(b).into_owned();
+
+        match (identifier, contents) {
+            (Self::BEARER_CAPABILITY, [itc, ..]) => {
+                match itc & 0x1f {
+                    0x00 => Self::BearerCapability(BearerCapability::Speech),
+                    0x08 => Self::BearerCapability(BearerCapability::UnrestrictedDigital),
+                    0x10 => Self::BearerCapability(BearerCapability::Audio3_1kHz),
+                    _ => unknown(),
+                }
+            },
+            (Self::CAUSE, [location, rest @ ..]) => {
+                // Octet 3a (recommendation) is present if octet 3 has its extension bit clear.
+                let value = if location & 0x80 == 0 { rest.get(1) } else { rest.first() };
+                match value {
+                    Some(value) => Self::Cause { location: location & 0x0f, value: value & 0x7f },
+                    None => unknown(),
+                }
+            },
+            // Interface implicit, PRI, information channel selection "as indicated".
+            (Self::CHANNEL_IDENTIFICATION, [octet3, 0x83, channel]) if octet3 & 0xf7 == 0xa1 => {
+                Self::ChannelIdentification { exclusive: octet3 & 0x08 != 0, channel: channel & 0x7f }
+            },
+            (Self::PROGRESS_INDICATOR, [location, description]) => {
+                Self::ProgressIndicator { location: location & 0x0f, description: description & 0x7f }
+            },
+            (Self::DISPLAY, text) => Self::Display(digits(text)),
+            (Self::CALLING_PARTY_NUMBER, [type_plan, rest @ ..]) => {
+                // Skip octet 3a (presentation/screening) if present.
+                let number = if type_plan & 0x80 == 0 { rest.get(1..).unwrap_or(&[]) } else { rest };
+                Self::CallingPartyNumber { type_plan: type_plan | 0x80, digits: digits(number) }
+            },
+            (Self::CALLED_PARTY_NUMBER, [type_plan, number @ ..]) => {
+                Self::CalledPartyNumber { type_plan: *type_plan, digits: digits(number) }
+            },
+            _ => unknown(),
+        }
+    }
+
+    fn encode(&self, b: &mut Vec<u8>) {
+        fn variable(b: &mut Vec<u8>, identifier: u8, contents: &[u8]) {
+            b.push(identifier);
+            b.push(contents.len() as u8);
+            b.extend_from_slice(contents);
+        }
+
+        fn number(b: &mut Vec<u8>, identifier: u8, type_plan: u8, digits: &str) {
+            let mut contents = vec![type_plan | 0x80];
+            contents.extend_from_slice(digits.as_bytes());
+            variable(b, identifier, &contents);
+        }
+
+        match self {
+            Self::SendingComplete => b.push(Self::SENDING_COMPLETE),
+            Self::BearerCapability(capability) => {
+                // ITU-T coding standard; circuit mode, 64 kbit/s; G.711 u-law where applicable.
+                let contents: &[u8] = match capability {
+                    BearerCapability::Speech              => &[0x80, 0x90, 0xa2],
+                    BearerCapability::UnrestrictedDigital => &[0x88, 0x90],
+                    BearerCapability::Audio3_1kHz         => &[0x90, 0x90, 0xa2],
+                };
+                variable(b, Self::BEARER_CAPABILITY, contents);
+            },
+            Self::Cause { location, value } => variable(b, Self::CAUSE, &[0x80 | location, 0x80 | value]),
+            Self::ChannelIdentification { exclusive, channel } => {
+                variable(b, Self::CHANNEL_IDENTIFICATION, &[0xa1 | ((*exclusive as u8) << 3), 0x83, 0x80 | channel]);
+            },
+            Self::ProgressIndicator { location, description } => {
+                variable(b, Self::PROGRESS_INDICATOR, &[0x80 | location, 0x80 | description]);
+            },
+            Self::Display(text) => variable(b, Self::DISPLAY, text.as_bytes()),
+            Self::CallingPartyNumber { type_plan, digits } => number(b, Self::CALLING_PARTY_NUMBER, *type_plan, digits),
+            Self::CalledPartyNumber { type_plan, digits } => number(b, Self::CALLED_PARTY_NUMBER, *type_plan, digits),
+            Self::Unknown { identifier, contents } => {
+                if identifier & 0x80 != 0 {
+                    b.push(*identifier);
+                } else {
+                    variable(b, *identifier, contents);
+                }
+            },
+        }
+    }
+}
+
+/// Q.931 message, as carried in a Q.921 I-frame.
+///
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Message {
+    pub call_reference: CallReference,
+    pub message_type: MessageType,
+    pub information_elements: Vec<InformationElement>,
+}
+
+impl Message {
+    const PROTOCOL_DISCRIMINATOR: u8 = 0x08;
+
+    pub fn new(call_reference: CallReference, message_type: MessageType) -> Self {
+        Self {
+            call_reference,
+            message_type,
+            information_elements: Vec::new(),
+        }
+    }
+
+    pub fn with(mut self, information_element: InformationElement) -> Self {
+        self.information_elements.push(information_element);
+        self
+    }
+
+    pub fn from_slice(b: &[u8]) -> Result<Self, Q931Error> {
+        let (&pd, b) = b.split_first().ok_or(Q931Error::Truncated)?;
+        if pd != Self::PROTOCOL_DISCRIMINATOR {
+            return Err(Q931Error::ProtocolDiscriminator(pd));
+        }
+
+        let (&cr_length, b) = b.split_first().ok_or(Q931Error::Truncated)?;
+        if cr_length != 2 {
+            return Err(Q931Error::CallReferenceLength(cr_length));
+        }
+        let (cr, b) = (b.get(0..2).ok_or(Q931Error::Truncated)?, &b[2..]);
+        let call_reference = CallReference {
+            value: (((cr[0] & 0x7f) as u16) << 8) | cr[1] as u16,
+            from_destination: cr[0] & 0x80 != 0,
+        };
+
+        let (&message_type, mut b) = b.split_first().ok_or(Q931Error::Truncated)?;
+        let message_type = MessageType::from_u8(message_type);
+
+        let mut information_elements = Vec::new();
+        while let Some((&identifier, rest)) = b.split_first() {
+            if identifier & 0x80 != 0 {
+                // Single-octet information element. Codeset shifts aren't followed.
+                information_elements.push(if identifier == InformationElement::SENDING_COMPLETE {
+                    InformationElement::SendingComplete
+                } else {
+                    InformationElement::Unknown { identifier, contents: Vec::new() }
+                });
+                b = rest;
+            } else {
+                let (&length, rest) = rest.split_first().ok_or(Q931Error::Truncated)?;
+                let contents = rest.get(0..length as usize).ok_or(Q931Error::Truncated)?;
+                information_elements.push(InformationElement::decode(identifier, contents));
+                b = &rest[length as usize..];
+            }
+        }
+
+        Ok(Self {
+            call_reference,
+            message_type,
+            information_elements,
+        })
+    }
+
+    pub fn to_vec(&self) -> Vec<u8> {
+        let cr = self.call_reference;
+        let mut b = vec![
+            Self::PROTOCOL_DISCRIMINATOR,
+            2,
+            ((cr.from_destination as u8) << 7) | ((cr.value >> 8) as u8 & 0x7f),
+            cr.value as u8,
+            self.message_type.to_u8(),
+        ];
+        for ie in &self.information_elements {
+            ie.encode(&mut b);
+        }
+        b
+    }
+
+    pub fn channel(&self) -> Option<(bool, u8)> {
+        self.information_elements.iter().find_map(|ie| match ie {
+            InformationElement::ChannelIdentification { exclusive, channel } => Some((*exclusive, *channel)),
+            _ => None,
+        })
+    }
+
+    pub fn cause(&self) -> Option<u8> {
+        self.information_elements.iter().find_map(|ie| match ie {
+            InformationElement::Cause { value, .. } => Some(*value),
+            _ => None,
+        })
+    }
+
+    pub fn called_party_number(&self) -> Option<&str> {
+        self.information_elements.iter().find_map(|ie| match ie {
+            InformationElement::CalledPartyNumber { digits, .. } => Some(digits.as_str()),
+            _ => None,
+        })
+    }
+
+    pub fn calling_party_number(&self) -> Option<&str> {
+        self.information_elements.iter().find_map(|ie| match ie {
+            InformationElement::CallingPartyNumber { digits, .. } => Some(digits.as_str()),
+            _ => None,
+        })
+    }
+}
+
+///////////////////////////////////////////////////////////////////////
+
+/// Q.931 call states. Numbering follows Q.931 (U-states on the user side,
+/// N-states on the network side share the same numbers for the states we use).
+///
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum CallState {
+    Null,
+    CallInitiated,
+    OutgoingCallProceeding,
+    CallDelivered,
+    CallPresent,
+    CallReceived,
+    ConnectRequest,
+    IncomingCallProceeding,
+    Active,
+    DisconnectRequest,
+    DisconnectIndication,
+    ReleaseRequest,
+}
+
+impl CallState {
+    pub fn number(&self) -> u8 {
+        match self {
+            Self::Null                   => 0,
+            Self::CallInitiated          => 1,
+            Self::OutgoingCallProceeding => 3,
+            Self::CallDelivered          => 4,
+            Self::CallPresent            => 6,
+            Self::CallReceived           => 7,
+            Self::ConnectRequest         => 8,
+            Self::IncomingCallProceeding => 9,
+            Self::Active                 => 10,
+            Self::DisconnectRequest      => 11,
+            Self::DisconnectIndication   => 12,
+            Self::ReleaseRequest         => 19,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum CallEvent {
+    /// Incoming call. Answer with `proceeding()`, `alerting()`, `connect()`, or `disconnect()`.
+    Setup { call: CallReference, channel: Option<u8>, called: Option<String>, calling: Option<String> },
+    Proceeding { call: CallReference, channel: Option<u8> },
+    Alerting { call: CallReference },
+    Connected { call: CallReference },
+    Disconnected { call: CallReference, cause: Option<u8> },
+    Released { call: CallReference },
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum CallControlOutput {
+    /// Q.931 message to send in an I-frame.
+    Transmit(Vec<u8>),
+    Event(CallEvent),
+}
+
+struct Call {
+    state: CallState,
+    channel: Option<u8>,
+    /// Network side: the B-channel has yet to be confirmed to the user.
+    channel_unconfirmed: bool,
+}
+
+/// Q.931 call control for one D-channel, user or network side.
+///
+/// Calls are keyed by the call reference as seen from our side: the flag is
+/// clear for calls we originated, set for calls the far end originated.
+///
+pub struct CallControl {
+    side: Side,
+    calls: HashMap<CallReference, Call>,
+    next_call_reference: u16,
+    outputs: VecDeque<CallControlOutput>,
+}
+
+impl CallControl {
+    pub fn new(side: Side) -> Self {
+        Self {
+            side,
+            calls: HashMap::new(),
+            next_call_reference: 1,
+            outputs: VecDeque::new(),
+        }
+    }
+
+    pub fn poll_output(&mut self) -> Option<CallControlOutput> {
+        self.outputs.pop_front()
+    }
+
+    pub fn state(&self, call: CallReference) -> CallState {
+        self.calls.get(&call).map(|c| c.state).unwrap_or(CallState::Null)
+    }
+
+    /// Place an outgoing call. On the network side, the channel is exclusive.
+    pub fn setup(&mut self, called: &str, calling: Option<&str>, channel: u8) -> CallReference {
+        let call = CallReference { value: self.next_call_reference, from_destination: false };
+        self.next_call_reference = (self.next_call_reference % 0x7fff) + 1;
+
+        let mut message = Message::new(call, MessageType::Setup)
+            .with(InformationElement::BearerCapability(BearerCapability::Speech))
+            .with(InformationElement::ChannelIdentification { exclusive: self.side == Side::Network, channel });
+        if let Some(calling) = calling {
+            message = message.with(InformationElement::CallingPartyNumber { type_plan: InformationElement::TYPE_PLAN_UNKNOWN_ISDN, digits: calling.into() });
+        }
+        message = message
+            .with(InformationElement::CalledPartyNumber { type_plan: InformationElement::TYPE_PLAN_UNKNOWN_ISDN, digits: called.into() })
+            .with(InformationElement::SendingComplete);
+
+        self.calls.insert(call, Call { state: CallState::CallInitiated, channel: Some(channel), channel_unconfirmed: false });
+        self.send(message);
+        call
+    }
+
+    pub fn proceeding(&mut self, call: CallReference) {
+        if self.state(call) == CallState::CallPresent {
+            self.respond(call, MessageType::CallProceeding, CallState::IncomingCallProceeding);
+        }
+    }
+
+    pub fn alerting(&mut self, call: CallReference) {
+        if matches!(self.state(call), CallState::CallPresent | CallState::IncomingCallProceeding) {
+            self.respond(call, MessageType::Alerting, CallState::CallReceived);
+        }
+    }
+
+    pub fn connect(&mut self, call: CallReference) {
+        if matches!(self.state(call), CallState::CallPresent | CallState::IncomingCallProceeding | CallState::CallReceived) {
+            // The network considers the call active as soon as it connects; the user waits for CONNECT ACK.
+            let next = match self.side {
+                Side::User => CallState::ConnectRequest,
+                Side::Network => CallState::Active,
+            };
+            self.respond(call, MessageType::Connect, next);
+            if next == CallState::Active {
+                self.outputs.push_back(CallControlOutput::Event(CallEvent::Connected { call }));
+            }
+        }
+    }
+
+    pub fn disconnect(&mut self, call: CallReference, cause: u8) {
+        match self.state(call) {
+            CallState::Null | CallState::DisconnectRequest | CallState::ReleaseRequest => {},
+            CallState::DisconnectIndication => self.release(call, None),
+            _ => {
+                let message = Message::new(call, MessageType::Disconnect).with(Self::cause(cause));
+                self.set_state(call, CallState::DisconnectRequest);
+                self.send(message);
+            },
+        }
+    }
+
+    pub fn receive(&mut self, b: &[u8]) -> Result<(), Q931Error> {
+        let message = Message::from_slice(b)?;
+
+        // Flip the flag so the key matches how we filed the call.
+        let call = CallReference {
+            value: message.call_reference.value,
+            from_destination: !message.call_reference.from_destination,
+        };
+        let state = self.state(call);
+
+        match (message.message_type, state) {
+            (MessageType::Setup, CallState::Null) => {
+                let channel = message.channel().map(|(_, channel)| channel);
+                let channel_unconfirmed = self.side == Side::Network;
+                self.calls.insert(call, Call { state: CallState::CallPresent, channel, channel_unconfirmed });
+                self.event(CallEvent::Setup {
+                    call,
+                    channel,
+                    called: message.called_party_number().map(String::from),
+                    calling: message.calling_party_number().map(String::from),
+                });
+            },
+            (MessageType::CallProceeding, CallState::CallInitiated) => {
+                let channel = self.update_channel(call, &message);
+                self.set_state(call, CallState::OutgoingCallProceeding);
+                self.event(CallEvent::Proceeding { call, channel });
+            },
+            (MessageType::Alerting, CallState::CallInitiated | CallState::OutgoingCallProceeding) => {
+                self.update_channel(call, &message);
+                self.set_state(call, CallState::CallDelivered);
+                self.event(CallEvent::Alerting { call });
+            },
+            (MessageType::Connect, CallState::CallInitiated | CallState::OutgoingCallProceeding | CallState::CallDelivered) => {
+                self.update_channel(call, &message);
+                self.set_state(call, CallState::Active);
+                self.send(Message::new(call, MessageType::ConnectAcknowledge));
+                self.event(CallEvent::Connected { call });
+            },
+            (MessageType::ConnectAcknowledge, CallState::ConnectRequest) => {
+                self.set_state(call, CallState::Active);
+                self.event(CallEvent::Connected { call });
+            },
+            (MessageType::ConnectAcknowledge, CallState::Active) => {},
+            (MessageType::Disconnect, CallState::Null) => {
+                self.send(Message::new(call, MessageType::ReleaseComplete).with(Self::cause(cause::MESSAGE_NOT_COMPATIBLE_WITH_CALL_STATE)));
+            },
+            (MessageType::Disconnect, CallState::DisconnectRequest) => {
+                // Both ends cleared at once.
+                self.release(call, None);
+            },
+            (MessageType::Disconnect, _) => {
+                self.set_state(call, CallState::DisconnectIndication);
+                self.event(CallEvent::Disconnected { call, cause: message.cause() });
+                // Nothing to keep the call around for, so clear it right away.
+                self.release(call, None);
+            },
+            (MessageType::Release, CallState::Null) => {
+                self.send(Message::new(call, MessageType::ReleaseComplete));
+            },
+            (MessageType::Release, _) => {
+                self.send(Message::new(call, MessageType::ReleaseComplete));
+                if state != CallState::DisconnectRequest {
+                    self.event(CallEvent::Disconnected { call, cause: message.cause() });
+                }
+                self.clear(call);
+            },
+            (MessageType::ReleaseComplete, CallState::Null) => {},
+            (MessageType::ReleaseComplete, _) => {
+                if !matches!(state, CallState::DisconnectRequest | CallState::ReleaseRequest) {
+                    self.event(CallEvent::Disconnected { call, cause: message.cause() });
+                }
+                self.clear(call);
+            },
+            (MessageType::StatusEnquiry, _) => {
+                let status = Message::new(call, MessageType::Status)
+                    .with(Self::cause(cause::RESPONSE_TO_STATUS_ENQUIRY))
+                    .with(InformationElement::Unknown { identifier: 0x14, contents: vec![state.number()] });
+                self.send(status);
+            },
+            (MessageType::Status, _) => {},
+            (_, _) => {
+                let status = Message::new(call, MessageType::Status)
+                    .with(Self::cause(cause::MESSAGE_NOT_COMPATIBLE_WITH_CALL_STATE))
+                    .with(InformationElement::Unknown { identifier: 0x14, contents: vec![state.number()] });
+                self.send(status);
+            },
+        }
+
+        Ok(())
+    }
+
+    fn cause(value: u8) -> InformationElement {
+        // Location: private network serving the local user.
+        InformationElement::Cause { location: 1, value }
+    }
+
+    fn respond(&mut self, call: CallReference, message_type: MessageType, next: CallState) {
+        let mut message = Message::new(call, message_type);
+        if let Some(c) = self.calls.get_mut(&call) {
+            // The network must tell the user which B-channel it picked in its first response.
+            if c.channel_unconfirmed {
+                if let Some(channel) = c.channel {
+                    message = message.with(InformationElement::ChannelIdentification { exclusive: true, channel });
+                }
+                c.channel_unconfirmed = false;
+            }
+        }
+        self.set_state(call, next);
+        self.send(message);
+    }
+
+    fn release(&mut self, call: CallReference, cause: Option<u8>) {
+        let mut message = Message::new(call, MessageType::Release);
+        if let Some(cause) = cause {
+            message = message.with(Self::cause(cause));
+        }
+        self.set_state(call, CallState::ReleaseRequest);
+        self.send(message);
+    }
+
+    fn update_channel(&mut self, call: CallReference, message: &Message) -> Option<u8> {
+        let c = self.calls.get_mut(&call)?;
+        if let Some((_, channel)) = message.channel() {
+            c.channel = Some(channel);
+        }
+        c.channel
+    }
+
+    fn set_state(&mut self, call: CallReference, state: CallState) {
+        if let Some(c) = self.calls.get_mut(&call) {
+            c.state = state;
+        }
+    }
+
+    fn clear(&mut self, call: CallReference) {
+        self.calls.remove(&call);
+        self.event(CallEvent::Released { call });
+    }
+
+    fn send(&mut self, message: Message) {
+        self.outputs.push_back(CallControlOutput::Transmit(message.to_vec()));
+    }
+
+    fn event(&mut self, event: CallEvent) {
+        self.outputs.push_back(CallControlOutput::Event(event));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exchange(a: &mut CallControl, b: &mut CallControl) -> (Vec<CallEvent>, Vec<CallEvent>) {
+        let mut a_events = Vec::new();
+        let mut b_events = Vec::new();
+        loop {
+            let mut idle = true;
+            while let Some(output) = a.poll_output() {
+                idle = false;
+                match output {
+                    CallControlOutput::Transmit(m) => b.receive(&m).unwrap(),
+                    CallControlOutput::Event(e) => a_events.push(e),
+                }
+            }
+            while let Some(output) = b.poll_output() {
+                idle = false;
+                match output {
+                    CallControlOutput::Transmit(m) => a.receive(&m).unwrap(),
+                    CallControlOutput::Event(e) => b_events.push(e),
+                }
+            }
+            if idle {
+                return (a_events, b_events);
+            }
+        }
+    }
+
+    fn flipped(call: CallReference) -> CallReference {
+        CallReference { value: call.value, from_destination: !call.from_destination }
+    }
+
+    #[test]
+    fn message_round_trip() {
+        let message = Message::new(CallReference { value: 0x1234, from_destination: true }, MessageType::Setup)
+            .with(InformationElement::BearerCapability(BearerCapability::Speech))
+            .with(InformationElement::ChannelIdentification { exclusive: true, channel: 5 })
+            .with(InformationElement::ProgressIndicator { location: 0, description: 3 })
+            .with(InformationElement::Display("Tedium".into()))
+            .with(InformationElement::CallingPartyNumber { type_plan: 0xa1, digits: "5551212".into() })
+            .with(InformationElement::CalledPartyNumber { type_plan: 0xa1, digits: "5550100".into() })
+            .with(InformationElement::Cause { location: 1, value: 16 })
+            .with(InformationElement::SendingComplete)
+            .with(InformationElement::Unknown { identifier: 0x7c, contents: vec![1, 2] });
+
+        let b = message.to_vec();
+        assert_eq!(&b[0..5], [0x08, 0x02, 0x92, 0x34, 0x05]);
+        assert_eq!(Message::from_slice(&b).unwrap(), message);
+    }
+
+    #[test]
+    fn decode_rejects_bad_messages() {
+        assert_eq!(Message::from_slice(&[]), Err(Q931Error::Truncated));
+        assert_eq!(Message::from_slice(&[0x09, 0x02, 0x00, 0x01, 0x05]), Err(Q931Error::ProtocolDiscriminator(0x09)));
+        assert_eq!(Message::from_slice(&[0x08, 0x01, 0x01, 0x05]), Err(Q931Error::CallReferenceLength(1)));
+        assert_eq!(Message::from_slice(&[0x08, 0x02, 0x00, 0x01, 0x05, 0x70, 0x05, 0x81]), Err(Q931Error::Truncated));
+    }
+
+    #[test]
+    fn call_from_user_answered_by_network() {
+        let mut user = CallControl::new(Side::User);
+        let mut network = CallControl::new(Side::Network);
+
+        let call = user.setup("5550100", Some("5551212"), 3);
+        let (u, n) = exchange(&mut user, &mut network);
+        assert!(u.is_empty());
+        assert_eq!(n, [CallEvent::Setup {
+            call: flipped(call),
+            channel: Some(3),
+            called: Some("5550100".into()),
+            calling: Some("5551212".into()),
+        }]);
+
+        network.proceeding(flipped(call));
+        network.alerting(flipped(call));
+        let (u, _) = exchange(&mut user, &mut network);
+        assert_eq!(u, [CallEvent::Proceeding { call, channel: Some(3) }, CallEvent::Alerting { call }]);
+        assert_eq!(user.state(call), CallState::CallDelivered);
+
+        network.connect(flipped(call));
+        let (u, n) = exchange(&mut user, &mut network);
+        assert_eq!(u, [CallEvent::Connected { call }]);
+        assert_eq!(n, [CallEvent::Connected { call: flipped(call) }]);
+        assert_eq!(user.state(call), CallState::Active);
+        assert_eq!(network.state(flipped(call)), CallState::Active);
+
+        // Far end hangs up.
+        let disconnect = Message::new(flipped(call), MessageType::Disconnect)
+            .with(InformationElement::Cause { location: 0, value: 16 });
+        user.receive(&disconnect.to_vec()).unwrap();
+        let (u, n) = exchange(&mut user, &mut network);
+        assert_eq!(u, [
+            CallEvent::Disconnected { call, cause: Some(16) },
+            CallEvent::Released { call },
+        ]);
+        assert_eq!(n, [
+            CallEvent::Disconnected { call: flipped(call), cause: None },
+            CallEvent::Released { call: flipped(call) },
+        ]);
+        assert_eq!(user.state(call), CallState::Null);
+        assert_eq!(network.state(flipped(call)), CallState::Null);
+    }
+
+    #[test]
+    fn local_disconnect() {
+        let mut user = CallControl::new(Side::User);
+        let mut network = CallControl::new(Side::Network);
+
+        let call = user.setup("5550100", None, 3);
+        exchange(&mut user, &mut network);
+        network.connect(flipped(call));
+        exchange(&mut user, &mut network);
+        assert_eq!(user.state(call), CallState::Active);
+
+        user.disconnect(call, cause::NORMAL_CALL_CLEARING);
+        assert_eq!(user.state(call), CallState::DisconnectRequest);
+        let (u, n) = exchange(&mut user, &mut network);
+        assert_eq!(u, [CallEvent::Released { call }]);
+        assert_eq!(n, [
+            CallEvent::Disconnected { call: flipped(call), cause: Some(cause::NORMAL_CALL_CLEARING) },
+            CallEvent::Released { call: flipped(call) },
+        ]);
+        assert_eq!(user.state(call), CallState::Null);
+        assert_eq!(network.state(flipped(call)), CallState::Null);
+
+        // Answered with RELEASE COMPLETE instead of RELEASE.
+        let call = user.setup("5550100", None, 4);
+        exchange(&mut user, &mut network);
+        network.connect(flipped(call));
+        exchange(&mut user, &mut network);
+        user.disconnect(call, cause::NORMAL_CALL_CLEARING);
+        user.poll_output();
+        let release_complete = Message::new(flipped(call), MessageType::ReleaseComplete);
+        user.receive(&release_complete.to_vec()).unwrap();
+        assert_eq!(user.poll_output(), Some(CallControlOutput::Event(CallEvent::Released { call })));
+        assert_eq!(user.state(call), CallState::Null);
+    }
+
+    #[test]
+    fn call_from_network_answered_by_user() {
+        let mut user = CallControl::new(Side::User);
+        let mut network = CallControl::new(Side::Network);
+
+        let call = network.setup("100", None, 1);
+        exchange(&mut user, &mut network);
+        assert_eq!(user.state(flipped(call)), CallState::CallPresent);
+
+        user.connect(flipped(call));
+        let (u, n) = exchange(&mut user, &mut network);
+        assert_eq!(u, [CallEvent::Connected { call: flipped(call) }]);
+        assert_eq!(n, [CallEvent::Connected { call }]);
+    }
+
+    #[test]
+    fn unexpected_message_gets_status() {
+        let mut dut = CallControl::new(Side::Network);
+        let connect = Message::new(CallReference { value: 9, from_destination: false }, MessageType::Connect);
+        dut.receive(&connect.to_vec()).unwrap();
+
+        match dut.poll_output() {
+            Some(CallControlOutput::Transmit(b)) => {
+                let status = Message::from_slice(&b).unwrap();
+                assert_eq!(status.message_type, MessageType::Status);
+                assert_eq!(status.cause(), Some(cause::MESSAGE_NOT_COMPATIBLE_WITH_CALL_STATE));
+                assert!(status.call_reference.from_destination);
+            },
+            other => panic!("{other:?}"),
+        }
+    }
+}
@@ -633,6 +633,7 @@ pub struct TSPRMCR {
 #[derive(Copy, Clone, BitfieldSpecifier, Debug)]
 #[bits=4]
 pub enum BERTPattern {
+    PRBS_X23_X18_1 = 0b0001,
     PRBS_X20_X3_1 = 0b0010,
     QRSS_X20_X17_1 = 0b0011,
     AllOnes = 0b0100,
@@ -1313,14 +1314,14 @@ pub struct LIUCCR0 {
     pub EQC: B5,
 }
 
-#[derive(Copy, Clone, BitfieldSpecifier, Debug)]
+#[derive(Copy, Clone, BitfieldSpecifier, PartialEq, Eq, Debug)]
 #[bits=1]
 pub enum Termination {
     HighImpedance = 0b0,
     Internal = 0b1,
 }
 
-#[derive(Copy, Clone, BitfieldSpecifier, Debug)]
+#[derive(Copy, Clone, BitfieldSpecifier, PartialEq, Eq, Debug)]
 #[bits=2]
 pub enum TerminationImpedance {
     Ohms100 = 0b00,
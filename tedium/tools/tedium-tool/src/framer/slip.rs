@@ -0,0 +1,302 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use super::register::SBISR;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SlipBuffer {
+    /// Between the recovered line clock and the system side.
+    Receive,
+    /// Between the system side and the transmit clock.
+    Transmit,
+}
+
+/// How the slip buffer got back in step.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SlipDirection {
+    /// The buffer ran dry, so a frame was repeated.
+    Repeated,
+    /// The buffer overflowed, so a frame was deleted.
+    Deleted,
+    /// The framer reported the slip without saying which way.
+    Unknown,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ControlledSlip {
+    pub buffer: SlipBuffer,
+    pub direction: SlipDirection,
+}
+
+impl ControlledSlip {
+    pub fn from_sbisr(sbisr: SBISR) -> Vec<Self> {
+        let direction = |empty: u8, full: u8| match (empty != 0, full != 0) {
+            (true, false) => SlipDirection::Repeated,
+            (false, true) => SlipDirection::Deleted,
+            _ => SlipDirection::Unknown,
+        };
+
+        let mut slips = Vec::new();
+        if sbisr.RxSB_SLIP() != 0 {
+            slips.push(Self { buffer: SlipBuffer::Receive, direction: direction(sbisr.RxSB_EMPT(), sbisr.RxSB_FULL()) });
+        }
+        if sbisr.TxSB_SLIP() != 0 {
+            slips.push(Self { buffer: SlipBuffer::Transmit, direction: direction(sbisr.TxSB_EMPT(), sbisr.TxSB_FULL()) });
+        }
+        slips
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SlipCause {
+    /// The span's clock and ours disagree.
+    LineClock,
+    /// The USB FIFOs slipped at the same time, so host or USB timing is to blame.
+    HostTiming,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct SlipRecord {
+    pub timestamp: Instant,
+    pub slip: ControlledSlip,
+    pub cause: SlipCause,
+}
+
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub struct SlipCounts {
+    pub repeated: u32,
+    pub deleted: u32,
+    pub unknown: u32,
+}
+
+impl SlipCounts {
+    fn count(&mut self, direction: SlipDirection) {
+        match direction {
+            SlipDirection::Repeated => self.repeated += 1,
+            SlipDirection::Deleted => self.deleted += 1,
+            SlipDirection::Unknown => self.unknown += 1,
+        }
+    }
+}
+
+/// Controlled slips on one span.
+///
+#[derive(Default)]
+pub struct ChannelSlips {
+    pub receive: SlipCounts,
+    pub transmit: SlipCounts,
+    pub line_clock: u32,
+    pub host_timing: u32,
+    recent: VecDeque<Instant>,
+}
+
+impl ChannelSlips {
+    fn record(&mut self, record: &SlipRecord) {
+        match record.slip.buffer {
+            SlipBuffer::Receive => self.receive.count(record.slip.direction),
+            SlipBuffer::Transmit => self.transmit.count(record.slip.direction),
+        }
+        match record.cause {
+            SlipCause::LineClock => self.line_clock += 1,
+            SlipCause::HostTiming => self.host_timing += 1,
+        }
+        self.recent.push_back(record.timestamp);
+    }
+
+    /// Slips in the `SlipAccounting::RATE_WINDOW` up to `now`.
+    pub fn recent(&mut self, now: Instant) -> usize {
+        while self.recent.front().is_some_and(|&t| now.saturating_duration_since(t) > SlipAccounting::RATE_WINDOW) {
+            self.recent.pop_front();
+        }
+        self.recent.len()
+    }
+
+    /// Most slips are deleted or repeated frames from a clock that is fast
+    /// or slow, not a mix, so say which.
+    pub fn trend(&self) -> Option<SlipDirection> {
+        let (repeated, deleted) = (self.receive.repeated + self.transmit.repeated, self.receive.deleted + self.transmit.deleted);
+        match repeated.cmp(&deleted) {
+            std::cmp::Ordering::Greater => Some(SlipDirection::Repeated),
+            std::cmp::Ordering::Less => Some(SlipDirection::Deleted),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+}
+
+impl fmt::Display for ChannelSlips {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "rx {}+/{}-/{}? tx {}+/{}-/{}? line {} host {}",
+            self.receive.repeated, self.receive.deleted, self.receive.unknown,
+            self.transmit.repeated, self.transmit.deleted, self.transmit.unknown,
+            self.line_clock, self.host_timing,
+        )
+    }
+}
+
+/// The USB side's FIFO error counters, from `FramerCumulativeStatistics`.
+///
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub struct UsbFifoSlips {
+    pub rx_underflows: u32,
+    pub tx_overflows: u32,
+}
+
+/// Tracks framer slips on every span alongside USB FIFO slips, and blames
+/// each framer slip on whichever clock is the likely culprit.
+///
+pub struct SlipAccounting {
+    channels: [ChannelSlips; 8],
+    usb_fifo: UsbFifoSlips,
+    usb_fifo_counts: Option<(u16, u16)>,
+    usb_fifo_last_slip: Option<Instant>,
+    /// Framer slips blamed on the line, kept for `CORRELATION_WINDOW` in case
+    /// the USB FIFO counters that explain them arrive afterward.
+    line_clock_recent: VecDeque<(usize, SlipRecord)>,
+}
+
+impl SlipAccounting {
+    /// Framer slips this close to USB FIFO slips, before or after, are blamed on the host.
+    pub const CORRELATION_WINDOW: Duration = Duration::from_secs(1);
+
+    /// Window for slip rates, matching the performance monitor intervals.
+    pub const RATE_WINDOW: Duration = Duration::from_secs(15 * 60);
+
+    pub fn new() -> Self {
+        Self {
+            channels: Default::default(),
+            usb_fifo: UsbFifoSlips::default(),
+            usb_fifo_counts: None,
+            usb_fifo_last_slip: None,
+            line_clock_recent: VecDeque::new(),
+        }
+    }
+
+    pub fn channel(&mut self, channel_index: usize) -> &mut ChannelSlips {
+        &mut self.channels[channel_index]
+    }
+
+    pub fn usb_fifo(&self) -> UsbFifoSlips {
+        self.usb_fifo
+    }
+
+    /// Account for a slip buffer interrupt.
+    pub fn framer_slip(&mut self, channel_index: usize, sbisr: SBISR, now: Instant) -> Vec<SlipRecord> {
+        let host_timing = self.usb_fifo_last_slip.is_some_and(|t| now.saturating_duration_since(t) <= Self::CORRELATION_WINDOW);
+        let cause = if host_timing { SlipCause::HostTiming } else { SlipCause::LineClock };
+
+        let records: Vec<_> = ControlledSlip::from_sbisr(sbisr).into_iter()
+            .map(|slip| SlipRecord { timestamp: now, slip, cause })
+            .collect();
+        self.expire_line_clock_recent(now);
+        for record in &records {
+            self.channels[channel_index].record(record);
+            if record.cause == SlipCause::LineClock {
+                self.line_clock_recent.push_back((channel_index, *record));
+            }
+        }
+        records
+    }
+
+    fn expire_line_clock_recent(&mut self, now: Instant) {
+        while self.line_clock_recent.front().is_some_and(|(_, r)| now.saturating_duration_since(r.timestamp) > Self::CORRELATION_WINDOW) {
+            self.line_clock_recent.pop_front();
+        }
+    }
+
+    /// Account for the latest USB FIFO error counters, returning the new slips,
+    /// and the framer slips just before them that are now blamed on the host
+    /// instead of the line. The counters are 16 bits and wrap.
+    pub fn usb_fifo_counts(&mut self, rx_underflow_count: u16, tx_overflow_count: u16, now: Instant) -> (UsbFifoSlips, Vec<(usize, SlipRecord)>) {
+        let slips = match self.usb_fifo_counts {
+            Some((rx, tx)) => UsbFifoSlips {
+                rx_underflows: rx_underflow_count.wrapping_sub(rx) as u32,
+                tx_overflows: tx_overflow_count.wrapping_sub(tx) as u32,
+            },
+            // Whatever happened before we were watching doesn't count.
+            None => UsbFifoSlips::default(),
+        };
+        self.usb_fifo_counts = Some((rx_underflow_count, tx_overflow_count));
+
+        let mut reblamed = Vec::new();
+        if slips != UsbFifoSlips::default() {
+            self.usb_fifo.rx_underflows += slips.rx_underflows;
+            self.usb_fifo.tx_overflows += slips.tx_overflows;
+            self.usb_fifo_last_slip = Some(now);
+
+            self.expire_line_clock_recent(now);
+            for (channel_index, mut record) in self.line_clock_recent.drain(..) {
+                let channel = &mut self.channels[channel_index];
+                channel.line_clock -= 1;
+                channel.host_timing += 1;
+                record.cause = SlipCause::HostTiming;
+                reblamed.push((channel_index, record));
+            }
+        }
+        (slips, reblamed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slip_direction() {
+        let sbisr = SBISR::new().with_RxSB_SLIP(1).with_RxSB_FULL(1).with_TxSB_SLIP(1).with_TxSB_EMPT(1);
+        assert_eq!(ControlledSlip::from_sbisr(sbisr), [
+            ControlledSlip { buffer: SlipBuffer::Receive, direction: SlipDirection::Deleted },
+            ControlledSlip { buffer: SlipBuffer::Transmit, direction: SlipDirection::Repeated },
+        ]);
+
+        // Full or empty without a slip is just the buffer's level.
+        assert!(ControlledSlip::from_sbisr(SBISR::new().with_RxSB_EMPT(1)).is_empty());
+    }
+
+    #[test]
+    fn blame() {
+        let t0 = Instant::now();
+        let s = |ms: u64| t0 + Duration::from_millis(ms);
+        let slip = SBISR::new().with_RxSB_SLIP(1).with_RxSB_EMPT(1);
+
+        let mut dut = SlipAccounting::new();
+        assert_eq!(dut.usb_fifo_counts(0xfffe, 7, t0).0, UsbFifoSlips::default());
+
+        assert_eq!(dut.framer_slip(2, slip, s(100))[0].cause, SlipCause::LineClock);
+
+        assert_eq!(dut.usb_fifo_counts(1, 7, s(5000)), (UsbFifoSlips { rx_underflows: 3, tx_overflows: 0 }, vec![]));
+        assert_eq!(dut.framer_slip(2, slip, s(5500))[0].cause, SlipCause::HostTiming);
+        assert_eq!(dut.framer_slip(2, slip, s(6500))[0].cause, SlipCause::LineClock);
+
+        let channel = dut.channel(2);
+        assert_eq!(channel.receive.repeated, 3);
+        assert_eq!((channel.line_clock, channel.host_timing), (2, 1));
+        assert_eq!(channel.trend(), Some(SlipDirection::Repeated));
+        assert_eq!(channel.recent(s(6500)), 3);
+        assert_eq!(channel.recent(s(100) + SlipAccounting::RATE_WINDOW + Duration::from_millis(1)), 2);
+    }
+
+    #[test]
+    fn blame_before_usb_update() {
+        let t0 = Instant::now();
+        let s = |ms: u64| t0 + Duration::from_millis(ms);
+        let slip = SBISR::new().with_TxSB_SLIP(1);
+
+        let mut dut = SlipAccounting::new();
+        dut.usb_fifo_counts(0, 0, t0);
+
+        // Too long before the USB FIFO slip to be its fault...
+        assert_eq!(dut.framer_slip(1, slip, s(1000))[0].cause, SlipCause::LineClock);
+        // ...but this one isn't.
+        assert_eq!(dut.framer_slip(3, slip, s(2500))[0].cause, SlipCause::LineClock);
+
+        let (_, reblamed) = dut.usb_fifo_counts(0, 1, s(3000));
+        assert_eq!(reblamed.len(), 1);
+        assert_eq!((reblamed[0].0, reblamed[0].1.cause), (3, SlipCause::HostTiming));
+
+        assert_eq!((dut.channel(1).line_clock, dut.channel(1).host_timing), (1, 0));
+        assert_eq!((dut.channel(3).line_clock, dut.channel(3).host_timing), (0, 1));
+        assert_eq!(dut.channel(3).transmit.unknown, 1);
+        assert_eq!(dut.channel(3).to_string(), "rx 0+/0-/0? tx 0+/0-/1? line 0 host 1");
+    }
+}
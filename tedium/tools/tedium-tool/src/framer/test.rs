@@ -199,3 +199,38 @@ fn set_test_mode_liu_digital_loopback(channel: &Channel) -> Result<()> {
         .with_LOOP2_n(LIULoopback::Digital)
     )
 }
+
+///////////////////////////////////////////////////////////////////////
+
+/// Take a channel out of framer and LIU loopback.
+///
+/// The clock source and robbed-bit signaling changes some test modes make
+/// are left alone, since there's no telling what they were before. Use
+/// `loopback_save` and `loopback_restore` to undo those too.
+///
+pub(crate) fn clear_test_mode(channel: &Channel) -> Result<()> {
+    channel.liuccr2().modify(|m| m
+        .with_LOOP2_n(LIULoopback::None)
+    )?;
+    channel.licr().modify(|m| m
+        .with_LB(FramerLoopback::No)
+    )
+}
+
+/// Loopback and clock source settings of a channel. Fields the register
+/// holds an undefined value for are `None`.
+///
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct TestModeStatus {
+    pub framer: FramerLoopback,
+    pub liu: Option<LIULoopback>,
+    pub clock_source: Option<ClockSource>,
+}
+
+pub(crate) fn test_mode_status(channel: &Channel) -> Result<TestModeStatus> {
+    Ok(TestModeStatus {
+        framer: channel.licr().read()?.LB(),
+        liu: channel.liuccr2().read()?.LOOP2_n_or_err().ok(),
+        clock_source: channel.csr().read()?.CSS_or_err().ok(),
+    })
+}
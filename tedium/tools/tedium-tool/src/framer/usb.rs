@@ -31,7 +31,7 @@ pub(crate) enum EndpointNumber {
 
 // TODO: Keep synchronized with `gateware/descriptors_vendor.py`.
 // TODO: This is a duplicate of the data in the `tedium-tool` project.
-const FRAMER_CONTROL_BYTES_MAX: usize = 512;
+pub const FRAMER_CONTROL_BYTES_MAX: usize = 512;
 
 // TODO: Borrowed from rusb::ffi, because it's pub(crate).
 #[doc(hidden)]
@@ -53,6 +53,9 @@ pub fn from_libusb(err: i32) -> rusb::Error {
     }
 }
 
+// TODO: Keep synchronized with `bcdDevice` in `gateware/descriptors_vendor.py`.
+pub const DESCRIPTOR_VERSION: u16 = 0x0101;
+
 // TODO: Keep synchronized with `gateware/descriptors_vendor.py`.
 pub const INTERRUPT_BYTES_MAX: usize = 256;
 
@@ -201,24 +204,6 @@ impl Transfer {
     }
 }
 
-pub struct CallbackWrapper<T> {
-    handler: Arc<Mutex<T>>,
-}
-
-impl<T> CallbackWrapper<T> {
-    pub fn new(handler: Arc<Mutex<T>>) -> Self {
-        Self {
-            handler,
-        }
-    }
-}
-
-impl<T: TransferHandler> TransferHandler for CallbackWrapper<T> {
-    fn callback(&self, transfer: *mut ffi::libusb_transfer) {
-        self.handler.lock().unwrap().callback(transfer);
-    }
-}
-
 pub trait CallbackIn {
     fn callback_in(&mut self, transfer: *mut ffi::libusb_transfer);
 }
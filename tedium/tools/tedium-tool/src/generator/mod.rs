@@ -5,3 +5,4 @@ pub trait ToneGenerator {
 }
 
 pub mod dual_tone;
+pub mod prbs;
@@ -0,0 +1,94 @@
+/// Pseudo-random bit sequences, per ITU-T O.150, without the inversion O.150
+/// specifies for some of them.
+///
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum PrbsPattern {
+    /// 2^9-1: x^9 + x^5 + 1
+    Prbs9,
+    /// 2^11-1: x^11 + x^9 + 1
+    Prbs11,
+    /// 2^15-1: x^15 + x^14 + 1
+    Prbs15,
+    /// 2^20-1: x^20 + x^3 + 1
+    Prbs20,
+    /// 2^23-1: x^23 + x^18 + 1
+    Prbs23,
+}
+
+impl PrbsPattern {
+    /// Register length and the other feedback tap.
+    fn taps(&self) -> (u32, u32) {
+        match self {
+            Self::Prbs9  => (9, 5),
+            Self::Prbs11 => (11, 9),
+            Self::Prbs15 => (15, 14),
+            Self::Prbs20 => (20, 3),
+            Self::Prbs23 => (23, 18),
+        }
+    }
+
+    pub fn order(&self) -> u32 {
+        self.taps().0
+    }
+
+    /// The bit that follows the `order` bits held in `state`, most recent in bit 0.
+    pub(crate) fn feedback(&self, state: u32) -> bool {
+        let (a, b) = self.taps();
+        ((state >> (a - 1)) ^ (state >> (b - 1))) & 1 != 0
+    }
+
+    pub(crate) fn mask(&self) -> u32 {
+        (1 << self.order()) - 1
+    }
+}
+
+/// Fibonacci LFSR producing a PRBS, most significant bit of each byte first.
+///
+#[derive(Copy, Clone, Debug)]
+pub struct PrbsGenerator {
+    pattern: PrbsPattern,
+    state: u32,
+}
+
+impl PrbsGenerator {
+    pub fn new(pattern: PrbsPattern) -> Self {
+        Self {
+            pattern,
+            state: pattern.mask(),
+        }
+    }
+
+    pub fn next_bit(&mut self) -> bool {
+        let bit = self.pattern.feedback(self.state);
+        self.state = ((self.state << 1) | bit as u32) & self.pattern.mask();
+        bit
+    }
+
+    pub fn next_byte(&mut self) -> u8 {
+        (0..8).fold(0, |byte, _| (byte << 1) | self.next_bit() as u8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maximal_length() {
+        for pattern in [PrbsPattern::Prbs9, PrbsPattern::Prbs11, PrbsPattern::Prbs15, PrbsPattern::Prbs20, PrbsPattern::Prbs23] {
+            let mut dut = PrbsGenerator::new(pattern);
+            let start = dut.state;
+            let period = (1u32 << pattern.order()) - 1;
+
+            let mut ones = 0;
+            for n in 1..=period {
+                ones += dut.next_bit() as u32;
+                assert!(n == period || dut.state != start, "{pattern:?} repeated after {n} bits");
+            }
+            assert_eq!(dut.state, start);
+
+            // An m-sequence has one more one than zeros.
+            assert_eq!(ones, period.div_ceil(2));
+        }
+    }
+}
@@ -1,18 +1,41 @@
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
 use std::time::{Duration, Instant};
 
 use clap::{Parser, Subcommand, Args, ArgEnum};
 
-use crossbeam::channel::{unbounded, Receiver};
+use crossbeam::channel::{unbounded, Receiver, RecvTimeoutError};
 use framer::FramerEvent;
+use framer::alarm::{AlarmTransition, SpanAlarms};
+use framer::clock::{ClockFailover, ClockSwitch};
+use framer::control::{FramerControl, FramerControlError, HdlcTransmitStatus, InterruptEnable, TimingReference};
+use framer::bert::{bert_error_count, bert_locked, bert_start, bert_stop, BertConfig, BertPatternSelect, BertStatistics};
+use framer::crc6::Crc6Statistics;
+use framer::mtp2::{configure_signalling_link_monitor, Mtp2Monitor, Mtp2MonitorEvent};
+use framer::mtp3::{Mtp3Message, Variant};
+use framer::pmon::{PerformanceMonitor, PmonCounts};
+use framer::pri::{configure_d_channel, DChannel, DChannelEvent};
+use framer::prm::{PerformanceReport, PerformanceReportHistory};
+use framer::q921::{DataLinkOutput, Side};
+use framer::q931::{cause, CallEvent, CallReference};
 use framer::interrupt::FramerInterruptThread;
+use framer::fdl::{BitOrientedCode, BitOrientedMessage};
+use framer::loopback::{configure_loopback_code_detection, fdl_code_send, inband_code_start, inband_code_stop, loopback_enter, loopback_restore, loopback_save, InbandLoopbackCode, LoopbackAction, LoopbackPolicy, LoopbackResponder, LoopbackSavedState, FDL_COMMAND_REPETITIONS};
 use framer::dump::{registers_dump_raw, registers_dump_global, registers_dump_channel};
-use framer::interrupt::{FramerInterruptStatus, print_framer_interrupt_status};
-use framer::register::RSAR;
+use framer::dump::{register_dump, registers_dump_decoded, registers_dump_json, registers_read, RegisterReference};
+use framer::map::{parse_number, RegisterTarget};
+use framer::interrupt::{alarm_bits, dlsr_bits, fisr_bits, liuccsr_bits, rlcisr_bytes, sbisr_bits};
+use framer::liu::{liu_configure, liu_status, JitterAttenuator, JitterAttenuatorBandwidth, JitterAttenuatorFifo, LineBuildOut, LiuConfig};
+use framer::register::{BERTPattern, BitErrorInsertionRate, Termination, TerminationImpedance};
+use framer::slip::{ChannelSlips, SlipAccounting, SlipCause, SlipDirection, SlipRecord, UsbFifoSlips};
 use framer::test::{set_test_mode_liu, LIUTestMode, set_test_mode_framer, FramerTestMode};
+use framer::test::{clear_test_mode, test_mode_status};
 
-use crate::framer::audio::{TimeslotAddress, ProcessorMessage, Patch, ToneSource, DebugMessage};
-use crate::framer::device::{Device, Result};
+use crate::framer::audio::{TimeslotAddress, ProcessorMessage, Patch, ToneSource, DebugMessage, SoftBertStatistics};
+use crate::generator::prbs::PrbsPattern;
+use crate::framer::device::{Channel, Device, Result};
 
 mod codec;
 mod detector;
@@ -35,6 +58,10 @@ pub(crate) enum TestMode {
     FramerLocalLoopback,
     FramerRemoteLineLoopback,
     FramerPayloadLoopback,
+    /// Take the channel out of loopback.
+    Clear,
+    /// Show the loopback settings of the channel, or of every channel.
+    Status,
 }
 
 #[derive(Args)]
@@ -42,8 +69,12 @@ pub(crate) struct TestArgs {
     #[clap(arg_enum)]
     mode: TestMode,
 
+    #[clap(long, parse(try_from_str=parse_channel))]
+    pub channel: Option<usize>,
+
+    /// Stay in the test mode for this many seconds (or until Ctrl-C), then put the channel back the way it was.
     #[clap(long)]
-    pub channel: usize,
+    pub duration: Option<u64>,
 }
 
 #[derive(Subcommand, Clone)]
@@ -58,6 +89,30 @@ pub(crate) enum DumpMode {
 
     #[clap(name="all")]
     All,
+
+    /// Every register in the map, decoded, compared against the power-up
+    /// defaults or a saved snapshot.
+    #[clap(name="registers")]
+    Registers {
+        /// Only this channel's registers, plus the device-wide ones.
+        #[clap(long, parse(try_from_str=parse_channel))]
+        channel: Option<usize>,
+
+        /// Also read status registers and PMON counters, which clears them.
+        #[clap(long)]
+        clear_on_read: bool,
+
+        /// Compare against a snapshot saved with `--json`.
+        #[clap(long)]
+        compare: Option<String>,
+
+        /// Only show registers that differ.
+        #[clap(long)]
+        changed: bool,
+
+        #[clap(long)]
+        json: bool,
+    },
 }
 
 #[derive(Args)]
@@ -66,10 +121,355 @@ pub(crate) struct DumpArgs {
     mode: DumpMode,
 }
 
+#[derive(Subcommand)]
+pub(crate) enum RegCommand {
+    #[clap(name="read")]
+    Read {
+        register: RegisterTarget,
+    },
+
+    /// Write a value, or named fields with everything else zero.
+    #[clap(name="write")]
+    Write {
+        register: RegisterTarget,
+
+        /// A value, or FIELD=VALUE pairs.
+        #[clap(required=true)]
+        values: Vec<String>,
+    },
+
+    /// Change named fields, leaving the rest of the register as it is.
+    #[clap(name="modify")]
+    Modify {
+        register: RegisterTarget,
+
+        #[clap(required=true, parse(try_from_str=parse_field_assignment))]
+        fields: Vec<(String, String)>,
+    },
+}
+
+/// Registers are an address (`0x3100`) or a name, with a channel and timeslot
+/// where it has them (`devid`, `csr@3`, `tccr@2.17`).
+#[derive(Args)]
+pub(crate) struct RegArgs {
+    #[clap(subcommand)]
+    command: RegCommand,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum InterruptsCommand {
+    /// Show a channel's interrupt enable registers.
+    #[clap(name="show")]
+    Show {
+        channel: usize,
+    },
+
+    /// Set an interrupt enable register to a value, or change FIELD=VALUE
+    /// fields of its current value.
+    #[clap(name="set")]
+    Set {
+        channel: usize,
+
+        /// bier, fier, aeier, dlier1-dlier3, sbier or rlcier0-rlcier7.
+        register: String,
+
+        #[clap(required=true)]
+        values: Vec<String>,
+    },
+
+    /// Stop reporting a channel's interrupts.
+    #[clap(name="disable")]
+    Disable {
+        channel: usize,
+    },
+
+    /// Resume reporting a channel's interrupts.
+    #[clap(name="enable")]
+    Enable {
+        channel: usize,
+    },
+}
+
+/// Interrupt sources are set through the firmware, which is the one
+/// servicing them.
+#[derive(Args)]
+pub(crate) struct InterruptsArgs {
+    #[clap(subcommand)]
+    command: InterruptsCommand,
+}
+
+#[derive(ArgEnum, Clone, Copy)]
+pub(crate) enum JitterAttenuatorArg {
+    Off,
+    Receive,
+    Transmit,
+}
+
+#[derive(ArgEnum, Clone, Copy)]
+pub(crate) enum JitterAttenuatorBandwidthArg {
+    Wide,
+    Narrow,
+}
+
+#[derive(ArgEnum, Clone, Copy)]
+pub(crate) enum JitterAttenuatorFifoArg {
+    Bits32,
+    Bits64,
+}
+
+#[derive(ArgEnum, Clone, Copy)]
+pub(crate) enum TerminationArg {
+    Internal,
+    HighImpedance,
+}
+
+#[derive(ArgEnum, Clone, Copy)]
+pub(crate) enum ImpedanceArg {
+    Ohms75,
+    Ohms100,
+    Ohms110,
+    Ohms120,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum LiuCommand {
+    /// Show the line interface setup and status of one or every channel.
+    #[clap(name="status")]
+    Status {
+        #[clap(parse(try_from_str=parse_channel))]
+        channel: Option<usize>,
+    },
+
+    /// Change a channel's line interface setup. Anything not given is left as it is.
+    #[clap(name="set")]
+    Set {
+        #[clap(parse(try_from_str=parse_channel))]
+        channel: usize,
+
+        /// Line build-out: dsx-133, dsx-266, dsx-399, dsx-533, dsx-655 (feet to
+        /// the cross-connect), or csu-0, csu-7.5, csu-15, csu-22.5 (dB, long haul).
+        #[clap(long)]
+        lbo: Option<String>,
+
+        #[clap(arg_enum, long)]
+        jitter_attenuator: Option<JitterAttenuatorArg>,
+
+        #[clap(arg_enum, long)]
+        jitter_attenuator_bandwidth: Option<JitterAttenuatorBandwidthArg>,
+
+        #[clap(arg_enum, long)]
+        jitter_attenuator_fifo: Option<JitterAttenuatorFifoArg>,
+
+        /// Receive termination. High impedance bridges a span without loading it.
+        #[clap(arg_enum, long)]
+        rx_termination: Option<TerminationArg>,
+
+        #[clap(arg_enum, long)]
+        tx_termination: Option<TerminationArg>,
+
+        #[clap(arg_enum, long)]
+        impedance: Option<ImpedanceArg>,
+    },
+}
+
+/// Line interface (LIU) build-out, equalizer, jitter attenuator and termination.
+#[derive(Args)]
+pub(crate) struct LiuArgs {
+    #[clap(subcommand)]
+    command: LiuCommand,
+}
+
 #[derive(Args)]
 pub(crate) struct MonitorArgs {
     // #[clap(long)]
     // pub channel: usize,
+
+    /// Seconds a span must be free of defects before its alarm clears. Defaults to 10.
+    #[clap(long)]
+    pub alarm_clearing: Option<u64>,
+
+    /// Enter and leave loopback when the far end asks, with in-band codes or FDL messages.
+    #[clap(long)]
+    pub loopback_response: bool,
+
+    /// Leave a far-end requested loopback after this many minutes.
+    #[clap(long)]
+    pub loopback_timeout: Option<u64>,
+
+    /// Time the transmitters from the first healthy span in this list,
+    /// failing over down the list, or to free-run, when it fails.
+    #[clap(long, use_value_delimiter=true)]
+    pub clock_failover: Vec<usize>,
+
+    /// Read the PMON counters every second for T1.231 performance parameters.
+    /// Reading clears them, including PBEC, so `bert` can't count bit errors meanwhile.
+    #[clap(long)]
+    pub performance: bool,
+}
+
+/// Choose where the transmitters get their timing.
+#[derive(Args)]
+pub(crate) struct ClockArgs {
+    /// Span to loop-time from. Free-run from MCLK if omitted.
+    pub channel: Option<usize>,
+}
+
+#[derive(ArgEnum, Clone)]
+pub(crate) enum LoopbackCodeArg {
+    LineUp,
+    LineDown,
+    PayloadUp,
+    PayloadDown,
+    UniversalDown,
+}
+
+#[derive(Args)]
+pub(crate) struct LoopbackSendArgs {
+    #[clap(arg_enum)]
+    code: LoopbackCodeArg,
+
+    #[clap(long, parse(try_from_str=parse_channel))]
+    pub channel: usize,
+
+    /// Send the in-band code instead of the ESF FDL message. Only line loopback has in-band codes.
+    #[clap(long)]
+    pub inband: bool,
+
+    /// Seconds to send an in-band code for.
+    #[clap(long, default_value="10")]
+    pub duration: u64,
+}
+
+#[derive(Args)]
+pub(crate) struct HdlcSendArgs {
+    #[clap(long)]
+    pub channel: usize,
+
+    /// HDLC controller, 0 through 2.
+    #[clap(long, default_value="0")]
+    pub controller: usize,
+
+    /// Frame contents as hex digits, without FCS.
+    pub frame: String,
+}
+
+#[derive(ArgEnum, Clone)]
+pub(crate) enum PriSide {
+    User,
+    Network,
+}
+
+#[derive(Args)]
+pub(crate) struct PriArgs {
+    #[clap(long, parse(try_from_str=parse_channel))]
+    pub channel: usize,
+
+    #[clap(arg_enum, long, default_value="user")]
+    side: PriSide,
+
+    /// Answer incoming calls.
+    #[clap(long)]
+    pub answer: bool,
+
+    /// Place a call to this number once the data link is up. Ctrl-C hangs up.
+    #[clap(long)]
+    pub call: Option<String>,
+
+    /// B-channel for the outgoing call, 1 through 23.
+    #[clap(long, default_value="1", parse(try_from_str=parse_bchannel))]
+    pub bchannel: u8,
+}
+
+#[derive(ArgEnum, Clone)]
+pub(crate) enum Ss7Variant {
+    Ansi,
+    Itu,
+}
+
+#[derive(Args)]
+pub(crate) struct Ss7MonitorArgs {
+    #[clap(long, parse(try_from_str=parse_channel))]
+    pub channel: usize,
+
+    /// Timeslot carrying the signalling link, 0 through 23.
+    #[clap(long)]
+    pub timeslot: usize,
+
+    /// HDLC controller, 0 through 2. Controller 0 is left to the ESF facility data link.
+    #[clap(long, default_value="2")]
+    pub controller: usize,
+
+    #[clap(arg_enum, long, default_value="ansi")]
+    variant: Ss7Variant,
+}
+
+#[derive(ArgEnum, Clone)]
+pub(crate) enum BertPatternArg {
+    Prbs15,
+    Prbs20,
+    Prbs20X17,
+    Prbs23,
+    Qrss,
+    AllOnes,
+    AllZeros,
+    ThreeIn24,
+    OneIn8,
+    FiftyFiveOctet,
+    Daly,
+}
+
+#[derive(ArgEnum, Clone)]
+pub(crate) enum BertErrorRate {
+    Off,
+    OneInThousand,
+    OneInMillion,
+}
+
+#[derive(Args)]
+pub(crate) struct BertArgs {
+    #[clap(long, parse(try_from_str=parse_channel))]
+    pub channel: usize,
+
+    #[clap(arg_enum, long, default_value="prbs15")]
+    pattern: BertPatternArg,
+
+    /// Insert bit errors into the transmitted pattern at this rate.
+    #[clap(arg_enum, long, default_value="off")]
+    insert: BertErrorRate,
+
+    /// Fill the F-bits with pattern too.
+    #[clap(long)]
+    pub unframed: bool,
+
+    /// Test duration, in seconds.
+    #[clap(long, default_value="10")]
+    pub duration: u64,
+}
+
+#[derive(ArgEnum, Clone)]
+pub(crate) enum SoftBertPattern {
+    Prbs9,
+    Prbs11,
+    Prbs15,
+    Prbs20,
+    Prbs23,
+}
+
+#[derive(Args)]
+pub(crate) struct SoftBertArgs {
+    #[clap(long, parse(try_from_str=parse_channel))]
+    pub channel: usize,
+
+    /// Timeslots carrying the pattern, 0 through 23, as a list of numbers and ranges (e.g. "0-5,12").
+    #[clap(long, default_value="0-23", parse(try_from_str=parse_timeslots))]
+    pub timeslots: u32,
+
+    #[clap(arg_enum, long, default_value="prbs23")]
+    pattern: SoftBertPattern,
+
+    /// Test duration, in seconds.
+    #[clap(long, default_value="10")]
+    pub duration: u64,
 }
 
 #[derive(Subcommand)]
@@ -80,12 +480,283 @@ pub(crate) enum Commands {
     #[clap(name="dump")]
     Dump(DumpArgs),
 
+    #[clap(name="reg")]
+    Reg(RegArgs),
+
+    #[clap(name="interrupts")]
+    Interrupts(InterruptsArgs),
+
+    #[clap(name="liu")]
+    Liu(LiuArgs),
+
+    /// Firmware version and configuration.
+    #[clap(name="info")]
+    Info,
+
     #[clap(name="monitor")]
     Monitor(MonitorArgs),
+
+    #[clap(name="clock")]
+    Clock(ClockArgs),
+
+    #[clap(name="bert")]
+    Bert(BertArgs),
+
+    #[clap(name="soft-bert")]
+    SoftBert(SoftBertArgs),
+
+    #[clap(name="loopback-send")]
+    LoopbackSend(LoopbackSendArgs),
+
+    #[clap(name="hdlc-send")]
+    HdlcSend(HdlcSendArgs),
+
+    #[clap(name="pri")]
+    Pri(PriArgs),
+
+    #[clap(name="ss7-monitor")]
+    Ss7Monitor(Ss7MonitorArgs),
+}
+
+fn interrupts(device: &Device, control: &FramerControl, command: InterruptsCommand) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let target = |channel: usize, register: InterruptEnable| format!("{}@{channel}", register.name()).parse::<RegisterTarget>();
+
+    match command {
+        InterruptsCommand::Show { channel } => {
+            for register in InterruptEnable::ALL {
+                let target = target(channel, register)?;
+                register_dump(&target, device.register_read(target.address)?);
+            }
+        },
+        InterruptsCommand::Set { channel, register, values } => {
+            let register = InterruptEnable::from_name(&register)
+                .ok_or_else(|| format!("{register:?} isn't an interrupt enable register"))?;
+            let target = target(channel, register)?;
+            let value = register_value(&target, &values, || device.register_read(target.address))?;
+            control.interrupt_enable(channel, register, value)?;
+            register_dump(&target, device.register_read(target.address)?);
+        },
+        InterruptsCommand::Disable { channel } => control.channel_enable(channel, false)?,
+        InterruptsCommand::Enable { channel } => control.channel_enable(channel, true)?,
+    }
+
+    Ok(())
+}
+
+fn liu(device: &Device, command: LiuCommand) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    match command {
+        LiuCommand::Status { channel } => {
+            let channels = match channel {
+                Some(channel_index) => channel_index..channel_index + 1,
+                None => 0..8,
+            };
+            for channel_index in channels {
+                let status = liu_status(&device.channel(channel_index))?;
+                let config = status.config.map_or_else(|| format!("EQC={:#04x}", status.eqc), |c| c.to_string());
+                let on_off = |on: bool| if on { "on" } else { "off" };
+                println!("LIU: {channel_index} {config} receiver {} transmitter {} cable loss {} dB {}",
+                    on_off(status.receiver_on), on_off(status.transmitter_on), status.cable_loss, liuccsr_bits(status.liuccsr),
+                );
+            }
+        },
+        LiuCommand::Set { channel, lbo, jitter_attenuator, jitter_attenuator_bandwidth, jitter_attenuator_fifo, rx_termination, tx_termination, impedance } => {
+            let channel = device.channel(channel);
+            let mut config = match (liu_status(&channel)?.config, &lbo) {
+                (Some(config), _) => config,
+                // Start from the defaults, since we can't make sense of what's there.
+                (None, Some(_)) => LiuConfig::default(),
+                (None, None) => return Err("unknown equalizer setting, give --lbo".into()),
+            };
+
+            let termination = |t| match t {
+                TerminationArg::Internal => Termination::Internal,
+                TerminationArg::HighImpedance => Termination::HighImpedance,
+            };
+            if let Some(lbo) = lbo {
+                config.line_build_out = LineBuildOut::from_name(&lbo)
+                    .ok_or_else(|| format!("{lbo:?} isn't a line build-out"))?;
+            }
+            if let Some(a) = jitter_attenuator {
+                config.jitter_attenuator = match a {
+                    JitterAttenuatorArg::Off      => JitterAttenuator::Disabled,
+                    JitterAttenuatorArg::Receive  => JitterAttenuator::Receive,
+                    JitterAttenuatorArg::Transmit => JitterAttenuator::Transmit,
+                };
+            }
+            if let Some(a) = jitter_attenuator_bandwidth {
+                config.jitter_attenuator_bandwidth = match a {
+                    JitterAttenuatorBandwidthArg::Wide   => JitterAttenuatorBandwidth::Wide,
+                    JitterAttenuatorBandwidthArg::Narrow => JitterAttenuatorBandwidth::Narrow,
+                };
+            }
+            if let Some(a) = jitter_attenuator_fifo {
+                config.jitter_attenuator_fifo = match a {
+                    JitterAttenuatorFifoArg::Bits32 => JitterAttenuatorFifo::Bits32,
+                    JitterAttenuatorFifoArg::Bits64 => JitterAttenuatorFifo::Bits64,
+                };
+            }
+            if let Some(t) = rx_termination {
+                config.termination.receive = termination(t);
+            }
+            if let Some(t) = tx_termination {
+                config.termination.transmit = termination(t);
+            }
+            if let Some(a) = impedance {
+                config.termination.impedance = match a {
+                    ImpedanceArg::Ohms75  => TerminationImpedance::Ohms75,
+                    ImpedanceArg::Ohms100 => TerminationImpedance::Ohms100,
+                    ImpedanceArg::Ohms110 => TerminationImpedance::Ohms110,
+                    ImpedanceArg::Ohms120 => TerminationImpedance::Ohms120,
+                };
+            }
+
+            liu_configure(&channel, &config)?;
+            println!("LIU: {config}");
+        },
+    }
+
+    Ok(())
+}
+
+/// Open the firmware's control interface, reporting why if the firmware
+/// isn't one we can talk to.
+fn framer_control_open(context: &mut rusb::Context) -> Result<FramerControl> {
+    match FramerControl::open(context) {
+        Ok(control) => Ok(control),
+        Err(FramerControlError::Usb(e)) => Err(e),
+        Err(e) => {
+            eprintln!("error: {e}");
+            Err(rusb::Error::NotSupported)
+        },
+    }
+}
+
+/// A register value from the command line: a number, or FIELD=VALUE changes
+/// to the value `base` provides.
+fn register_value<F>(target: &RegisterTarget, values: &[String], base: F) -> std::result::Result<u8, Box<dyn std::error::Error>>
+    where F: FnOnce() -> Result<u8>
+{
+    match values {
+        [value] if !value.contains('=') => {
+            let value = parse_number(value).and_then(|v| u8::try_from(v).ok())
+                .ok_or_else(|| format!("{value:?} isn't a register value"))?;
+            Ok(value)
+        },
+        values => {
+            let fields = values.iter()
+                .map(|v| parse_field_assignment(v))
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            Ok(target.with_fields(base()?, &fields)?)
+        },
+    }
+}
+
+fn reg(device: &Device, command: RegCommand) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let target = match command {
+        RegCommand::Read { register } => register,
+        RegCommand::Write { register, values } => {
+            let value = register_value(&register, &values, || Ok(0))?;
+            device.register_write(register.address, value)?;
+            register
+        },
+        RegCommand::Modify { register, fields } => {
+            let value = device.register_read(register.address)?;
+            device.register_write(register.address, register.with_fields(value, &fields)?)?;
+            register
+        },
+    };
+
+    register_dump(&target, device.register_read(target.address)?);
+    Ok(())
+}
+
+fn parse_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len()).step_by(2).map(|i| u8::from_str_radix(s.get(i..i+2)?, 16).ok()).collect()
+}
+
+fn parse_channel(s: &str) -> std::result::Result<usize, String> {
+    match s.parse() {
+        Ok(channel) if channel < 8 => Ok(channel),
+        _ => Err(format!("{s:?} isn't a channel, 0 through 7")),
+    }
+}
+
+fn parse_bchannel(s: &str) -> std::result::Result<u8, String> {
+    match s.parse() {
+        Ok(bchannel @ 1..=23) => Ok(bchannel),
+        _ => Err(format!("{s:?} isn't a B-channel, 1 through 23")),
+    }
 }
 
-fn main() -> Result<()> {
-    let args = Cli::parse();
+fn parse_field_assignment(s: &str) -> std::result::Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((field, value)) if !field.is_empty() && !value.is_empty() => Ok((field.into(), value.into())),
+        _ => Err(format!("{s:?} isn't FIELD=VALUE")),
+    }
+}
+
+/// Timeslot list, such as "0-5,12", as a bit mask.
+/// Timeslot list to a mask, bit n for timeslot n.
+fn parse_timeslots(s: &str) -> std::result::Result<u32, String> {
+    let error = || format!("{s:?} isn't a list of timeslots and ranges, 0 through 23");
+    let number = |n: &str| n.trim().parse::<u32>().map_err(|_| error());
+
+    let mut mask = 0;
+    for range in s.split(',') {
+        let (first, last) = match range.split_once('-') {
+            Some((first, last)) => (number(first)?, number(last)?),
+            None => {
+                let n = number(range)?;
+                (n, n)
+            },
+        };
+        if first > last || last >= 24 {
+            return Err(error());
+        }
+        mask |= ((1 << (last - first + 1)) - 1) << first;
+    }
+    Ok(mask)
+}
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn interrupt_handler(_signal: libc::c_int) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+/// Turn Ctrl-C into a flag, so whatever is running can clean up before exiting.
+fn catch_interrupt() {
+    unsafe {
+        libc::signal(libc::SIGINT, interrupt_handler as extern "C" fn(libc::c_int) as libc::sighandler_t);
+    }
+}
+
+/// Sleep for `duration`, or until Ctrl-C if `catch_interrupt` was called.
+fn wait_interruptible(duration: Duration) {
+    let deadline = Instant::now() + duration;
+    while !INTERRUPTED.load(Ordering::SeqCst) {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        thread::sleep(remaining.min(Duration::from_millis(100)));
+    }
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        },
+    }
+}
+
+fn run(args: Cli) -> std::result::Result<(), Box<dyn std::error::Error>> {
 
     let mut context = rusb::Context::new()?;
     let device = Device::open(&mut context).expect("device open");
@@ -93,6 +764,23 @@ fn main() -> Result<()> {
     match args.command {
         Commands::Test(a) => {
             let f = match a.mode {
+                TestMode::Status => {
+                    let channels = match a.channel {
+                        Some(channel_index) => channel_index..channel_index + 1,
+                        None => 0..8,
+                    };
+                    for channel_index in channels {
+                        let status = test_mode_status(&device.channel(channel_index))?;
+                        let or_invalid = |v: Option<String>| v.unwrap_or("invalid".into());
+                        println!("Test mode: {channel_index} framer={:?} liu={} clock={}",
+                            status.framer,
+                            or_invalid(status.liu.map(|l| format!("{l:?}"))),
+                            or_invalid(status.clock_source.map(|c| format!("{c:?}"))),
+                        );
+                    }
+                    return Ok(());
+                },
+                TestMode::Clear                    => |c| clear_test_mode(c),
                 TestMode::LIUDualLoopback          => |c| set_test_mode_liu(c, LIUTestMode::DualLoopback),
                 TestMode::LIUAnalogLoopback        => |c| set_test_mode_liu(c, LIUTestMode::AnalogLoopback),
                 TestMode::LIURemoteLoopback        => |c| set_test_mode_liu(c, LIUTestMode::RemoteLoopback),
@@ -102,8 +790,17 @@ fn main() -> Result<()> {
                 TestMode::FramerPayloadLoopback    => |c| set_test_mode_framer(c, FramerTestMode::PayloadLoopback),
             };
 
-            let channel = device.channel(a.channel);
-            f(&channel)?;
+            let channel = device.channel(a.channel.ok_or("test: give --channel")?);
+            if let Some(duration) = a.duration {
+                let saved = loopback_save(&channel)?;
+                catch_interrupt();
+                // Put the channel back even if the test mode only got partway set up.
+                let result = f(&channel).map(|()| wait_interruptible(Duration::from_secs(duration)));
+                loopback_restore(&channel, &saved)?;
+                result?;
+            } else {
+                f(&channel)?;
+            }
         },
         Commands::Dump(a) => {
             match a.mode {
@@ -117,9 +814,48 @@ fn main() -> Result<()> {
                     let channel = device.channel(channel);
                     registers_dump_channel(&channel)?;
                 },
+                DumpMode::Registers { channel, clear_on_read, compare, changed, json } => {
+                    let reference = match compare {
+                        Some(path) => {
+                            let snapshot = std::fs::read_to_string(&path).map_err(|e| format!("{path}: {e}"))?;
+                            RegisterReference::from_snapshot(&snapshot).map_err(|e| format!("{path}: {e}"))?
+                        },
+                        None => RegisterReference::Defaults,
+                    };
+                    let readings = registers_read(&device, channel, clear_on_read)?;
+                    if json {
+                        println!("{:#}", registers_dump_json(&readings, &reference));
+                    } else {
+                        registers_dump_decoded(&readings, &reference, changed);
+                    }
+                },
             }
         },
-        Commands::Monitor(_) => {
+        Commands::Reg(a) => {
+            reg(&device, a.command).map_err(|e| format!("reg: {e}"))?;
+        },
+        Commands::Interrupts(a) => {
+            let control = framer_control_open(&mut context)?;
+            interrupts(&device, &control, a.command).map_err(|e| format!("interrupts: {e}"))?;
+        },
+        Commands::Liu(a) => {
+            liu(&device, a.command).map_err(|e| format!("liu: {e}"))?;
+        },
+        Commands::Info => {
+            let control = framer_control_open(&mut context)?;
+            let info = control.info()?;
+            let (major, minor, patch) = info.version;
+            println!("firmware {major}.{minor}.{patch} ({})", info.git_hash);
+            println!("descriptors {}", info.descriptor_version);
+            println!("channels {}", info.channels);
+            println!("framing {:?}, clock source {:?}", info.framing, info.clock_source);
+        },
+        Commands::Monitor(a) => {
+            if a.clock_failover.iter().any(|&c| c >= 8) {
+                return Err(rusb::Error::InvalidParam.into());
+            }
+
+            let control = Arc::new(framer_control_open(&mut context)?);
             let (patch_sender, patch_receiver) = unbounded();
             let (event_sender, event_receiver) = unbounded();
             let (debug_sender, debug_receiver) = unbounded();
@@ -128,8 +864,9 @@ fn main() -> Result<()> {
                 .name("fr_int".to_string())
                 .spawn({
                     let event_sender = event_sender.clone();
+                    let control = control.clone();
                     move || {
-                        if let Err(e) = FramerInterruptThread::run(event_sender) {
+                        if let Err(e) = FramerInterruptThread::run(event_sender, control) {
                             eprintln!("error: framer interrupt pump: {e:?}");
                         }
                         eprintln!("done: framer interrupt pump");
@@ -165,18 +902,16 @@ fn main() -> Result<()> {
                                     let mut range_str = ['\u{2500}'; 32];
                                     range_str[r.0 as usize] = '\u{2524}';
                                     range_str[r.1 as usize] = '\u{251c}';
-                                    for i in (r.0 as usize)+1..(r.1 as usize) {
-                                        range_str[i] = ' ';
-                                    }
+                                    range_str[(r.0 as usize)+1..(r.1 as usize)].fill(' ');
                                     let range_str = range_str.iter().cloned().collect::<String>();
 
-                                    eprint!("{:6}.{:06}: {}\n", elapsed.as_secs(), elapsed.subsec_micros(), range_str);
+                                    eprintln!("{:6}.{:06}: {}", elapsed.as_secs(), elapsed.subsec_micros(), range_str);
                                     tx_fifo_level_range = r;
                                 }
                             },
                             DebugMessage::FramerStatistics(p, c) => {
                                 if DEBUG_PRINT {
-                                    eprint!("{p:?} {c:?}\n");
+                                    eprintln!("{p:?} {c:?}");
                                 }
                             },
                         }
@@ -212,14 +947,410 @@ fn main() -> Result<()> {
                     }
                 }).unwrap();
 
-            monitor(event_receiver);
+            let loopback_policy = if a.loopback_response {
+                for channel in device.channels() {
+                    configure_loopback_code_detection(&channel)?;
+
+                    // The firmware passes along detector 0 status once it's enabled.
+                    let rlcier0 = channel.rlcier0().read()?.with_RXAENB(1).with_RXDENB(1);
+                    control.interrupt_enable(channel.index(), InterruptEnable::Rlcier(0), rlcier0.into())?;
+                    let bier = channel.bier().read()?.with_LBCODE_ENB(1);
+                    control.interrupt_enable(channel.index(), InterruptEnable::Bier, bier.into())?;
+                }
+                Some(LoopbackPolicy {
+                    timeout: a.loopback_timeout.map(|m| Duration::from_secs(m * 60)),
+                    ..Default::default()
+                })
+            } else {
+                None
+            };
+
+            let clock_failover = if a.clock_failover.is_empty() {
+                None
+            } else {
+                Some(ClockFailover::new(&a.clock_failover))
+            };
+
+            // Put BIER back afterwards, so ONESEC stops clearing PBEC under `bert`.
+            let mut saved_bier = Vec::new();
+            if a.performance {
+                for channel in device.channels() {
+                    let bier = channel.bier().read()?;
+                    saved_bier.push((channel.index(), bier));
+                    control.interrupt_enable(channel.index(), InterruptEnable::Bier, bier.with_ONESEC_ENB(1).into())?;
+                }
+            }
+
+            monitor(&device, &control, event_receiver, a.alarm_clearing.map(Duration::from_secs), loopback_policy, clock_failover);
+
+            for (channel_index, bier) in saved_bier {
+                control.interrupt_enable(channel_index, InterruptEnable::Bier, bier.into())?;
+            }
             eprintln!("done: monitor");
         },
+        Commands::Clock(a) => {
+            let control = framer_control_open(&mut context)?;
+            let reference = a.channel.map_or(TimingReference::FreeRun, TimingReference::Channel);
+            if let Err(e) = control.timing_reference(reference) {
+                eprintln!("clock: {e}");
+            }
+        },
+        Commands::Bert(a) => {
+            bert(&device.channel(a.channel), a)?;
+        },
+        Commands::SoftBert(a) => {
+            soft_bert(a);
+        },
+        Commands::LoopbackSend(a) => {
+            let channel = device.channel(a.channel);
+            if a.inband {
+                let code = match a.code {
+                    LoopbackCodeArg::LineUp   => InbandLoopbackCode::LoopUp,
+                    LoopbackCodeArg::LineDown => InbandLoopbackCode::LoopDown,
+                    _ => return Err(rusb::Error::InvalidParam.into()),
+                };
+                inband_code_start(&channel, code)?;
+                thread::sleep(Duration::from_secs(a.duration));
+                inband_code_stop(&channel)?;
+            } else {
+                let code = match a.code {
+                    LoopbackCodeArg::LineUp        => BitOrientedCode::LineLoopbackActivate,
+                    LoopbackCodeArg::LineDown      => BitOrientedCode::LineLoopbackDeactivate,
+                    LoopbackCodeArg::PayloadUp     => BitOrientedCode::PayloadLoopbackActivate,
+                    LoopbackCodeArg::PayloadDown   => BitOrientedCode::PayloadLoopbackDeactivate,
+                    LoopbackCodeArg::UniversalDown => BitOrientedCode::UniversalLoopbackDeactivate,
+                };
+                fdl_code_send(&channel, code, FDL_COMMAND_REPETITIONS)?;
+            }
+        },
+        Commands::HdlcSend(a) => {
+            let frame = parse_hex(&a.frame).ok_or(rusb::Error::InvalidParam)?;
+            let control = framer_control_open(&mut context)?;
+
+            // Without the interrupt stream to tell us about TxEOT, just retry for a while.
+            let deadline = Instant::now() + Duration::from_secs(1);
+            loop {
+                match control.hdlc_transmit(a.channel, a.controller, &frame) {
+                    Ok(HdlcTransmitStatus::Queued) => break,
+                    Ok(HdlcTransmitStatus::Busy) => {},
+                    Err(FramerControlError::Usb(e)) => return Err(e.into()),
+                    Err(e) => {
+                        eprintln!("hdlc-send: {e}");
+                        break;
+                    },
+                }
+                if Instant::now() > deadline {
+                    eprintln!("hdlc-send: controller busy");
+                    break;
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+        },
+        Commands::Pri(a) => {
+            configure_d_channel(&device.channel(a.channel))?;
+            let control = Arc::new(framer_control_open(&mut context)?);
+            pri(control, a)?;
+        },
+        Commands::Ss7Monitor(a) => {
+            if a.timeslot >= 24 || a.controller >= 3 {
+                return Err(rusb::Error::InvalidParam.into());
+            }
+            configure_signalling_link_monitor(&device.channel(a.channel), a.timeslot, a.controller)?;
+            let control = Arc::new(framer_control_open(&mut context)?);
+            ss7_monitor(control, a);
+        },
+    }
+
+    Ok(())
+}
+
+fn bert(channel: &Channel, args: BertArgs) -> Result<()> {
+    let pattern = match args.pattern {
+        BertPatternArg::Prbs15         => BertPatternSelect::Prbs15,
+        BertPatternArg::Prbs20         => BertPatternSelect::Pattern(BERTPattern::PRBS_X20_X3_1),
+        BertPatternArg::Prbs20X17      => BertPatternSelect::Pattern(BERTPattern::PRBS_X20_X17_1),
+        BertPatternArg::Prbs23         => BertPatternSelect::Pattern(BERTPattern::PRBS_X23_X18_1),
+        BertPatternArg::Qrss           => BertPatternSelect::Pattern(BERTPattern::QRSS_X20_X17_1),
+        BertPatternArg::AllOnes        => BertPatternSelect::Pattern(BERTPattern::AllOnes),
+        BertPatternArg::AllZeros       => BertPatternSelect::Pattern(BERTPattern::AllZeros),
+        BertPatternArg::ThreeIn24      => BertPatternSelect::Pattern(BERTPattern::ThreeIn24),
+        BertPatternArg::OneIn8         => BertPatternSelect::Pattern(BERTPattern::OneIn8),
+        BertPatternArg::FiftyFiveOctet => BertPatternSelect::Pattern(BERTPattern::Fifty5Octet),
+        BertPatternArg::Daly           => BertPatternSelect::Pattern(BERTPattern::Daly),
+    };
+    let error_insertion = match args.insert {
+        BertErrorRate::Off           => BitErrorInsertionRate::Disable,
+        BertErrorRate::OneInThousand => BitErrorInsertionRate::OneOfOneThousand,
+        BertErrorRate::OneInMillion  => BitErrorInsertionRate::OneOfOneMillion,
+    };
+    let config = BertConfig {
+        pattern,
+        error_insertion,
+        unframed: args.unframed,
+    };
+
+    // Ctrl-C ends the test early, with the channel put back as it was.
+    catch_interrupt();
+    let saved = bert_start(channel, &config)?;
+
+    let mut statistics = BertStatistics::new();
+    let mut poll = || -> Result<()> {
+        let start = Instant::now();
+        let mut last = start;
+        for second in 1..=args.duration {
+            // Sleep until the next whole second since the start, so we don't drift.
+            let next = start + Duration::from_secs(second);
+            wait_interruptible(next.saturating_duration_since(Instant::now()));
+            if INTERRUPTED.load(Ordering::SeqCst) {
+                break;
+            }
+
+            let now = Instant::now();
+            let locked = bert_locked(channel)?;
+            let bit_errors = bert_error_count(channel)?;
+            if let Some(event) = statistics.update(locked, bit_errors, now - last, config.bit_rate()) {
+                eprintln!("BERT: {second:4}s {event:?}");
+            }
+            if bit_errors > 0 {
+                eprintln!("BERT: {second:4}s {bit_errors} bit errors");
+            }
+            last = now;
+        }
+        Ok(())
+    };
+    let result = poll();
+
+    bert_stop(channel, &saved)?;
+    result?;
+
+    let ber = statistics.bit_error_rate().map(|ber| format!("{ber:.3e}")).unwrap_or("-".into());
+    println!("BERT: {}s bits={} errors={} BER={ber} ES={} sync-losses={}",
+        statistics.seconds, statistics.bits, statistics.bit_errors, statistics.errored_seconds, statistics.sync_losses,
+    );
+
+    Ok(())
+}
+
+fn print_soft_bert_statistics(seconds: u64, statistics: &SoftBertStatistics) {
+    let checker = &statistics.checker;
+    let ber = statistics.bit_error_rate().map(|ber| format!("{ber:.3e}")).unwrap_or("-".into());
+    println!("SoftBERT: {seconds}s bits={} errors={} BER={ber} sync-losses={} (line={} usb={}) usb-slips={}",
+        checker.bits, checker.bit_errors, checker.sync_losses, statistics.line_sync_losses, statistics.usb_sync_losses, statistics.usb_slips,
+    );
+}
+
+fn soft_bert(args: SoftBertArgs) {
+    let timeslots = args.timeslots;
+    let pattern = match args.pattern {
+        SoftBertPattern::Prbs9  => PrbsPattern::Prbs9,
+        SoftBertPattern::Prbs11 => PrbsPattern::Prbs11,
+        SoftBertPattern::Prbs15 => PrbsPattern::Prbs15,
+        SoftBertPattern::Prbs20 => PrbsPattern::Prbs20,
+        SoftBertPattern::Prbs23 => PrbsPattern::Prbs23,
+    };
+
+    let (patch_sender, patch_receiver) = unbounded();
+    let (event_sender, event_receiver) = unbounded();
+    let (debug_sender, debug_receiver) = unbounded();
+
+    thread::Builder::new()
+        .name("fr_aud".to_string())
+        .spawn(move || {
+            if let Err(e) = framer::audio::pump_loopback(patch_receiver, event_sender, debug_sender) {
+                eprintln!("error: audio pump: {:?}", e);
+            }
+            eprintln!("done: audio pump");
+        }).unwrap();
+
+    thread::Builder::new()
+        .name("fr_dbg".into())
+        .spawn(move || {
+            for _ in debug_receiver {}
+        }).unwrap();
+
+    let addresses = (0..24)
+        .filter(|n| (timeslots >> n) & 1 != 0)
+        .map(|n| TimeslotAddress::new(args.channel, n));
+    for address in addresses.clone() {
+        patch_sender.send(ProcessorMessage::Patch(address, Patch::Prbs(pattern))).unwrap();
+    }
+    patch_sender.send(ProcessorMessage::CheckPrbs(args.channel, timeslots, Some(pattern))).unwrap();
+
+    // The audio processor reports once a second of frames.
+    let mut statistics = SoftBertStatistics::default();
+    let mut seconds = 0;
+    let deadline = Instant::now() + Duration::from_secs(args.duration);
+    while Instant::now() < deadline {
+        let event = match event_receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(event) => event,
+            Err(RecvTimeoutError::Timeout) => break,
+            Err(RecvTimeoutError::Disconnected) => {
+                eprintln!("soft-bert: audio pump stopped");
+                break;
+            },
+        };
+
+        if let FramerEvent::SoftBert(_frame_count, channel_index, update) = event {
+            if channel_index != args.channel {
+                continue;
+            }
+            seconds += 1;
+            if update.checker.in_sync != statistics.checker.in_sync {
+                eprintln!("SoftBERT: {seconds:4}s {}", if update.checker.in_sync { "SyncAcquired" } else { "SyncLost" });
+            }
+            if update.usb_slips != statistics.usb_slips {
+                eprintln!("SoftBERT: {seconds:4}s {} USB slips", update.usb_slips - statistics.usb_slips);
+            }
+            if update.checker.bit_errors > statistics.checker.bit_errors {
+                eprintln!("SoftBERT: {seconds:4}s {} bit errors", update.checker.bit_errors - statistics.checker.bit_errors);
+            }
+            statistics = update;
+        }
+    }
+
+    patch_sender.send(ProcessorMessage::CheckPrbs(args.channel, timeslots, None)).unwrap();
+    for address in addresses {
+        patch_sender.send(ProcessorMessage::Patch(address, Patch::Idle)).unwrap();
+    }
+
+    print_soft_bert_statistics(seconds, &statistics);
+}
+
+/// How long to wait for the far end to release our calls after Ctrl-C. Same as T305 plus T308.
+const PRI_CLEARING_TIMEOUT: Duration = Duration::from_secs(34);
+
+fn pri(control: Arc<FramerControl>, mut args: PriArgs) -> Result<()> {
+    let (event_sender, event_receiver) = unbounded();
+
+    thread::Builder::new()
+        .name("fr_int".to_string())
+        .spawn({
+            let control = control.clone();
+            move || {
+                if let Err(e) = FramerInterruptThread::run(event_sender, control) {
+                    eprintln!("error: framer interrupt pump: {e:?}");
+                }
+                eprintln!("done: framer interrupt pump");
+            }
+        }).unwrap();
+
+    let side = match args.side {
+        PriSide::User => Side::User,
+        PriSide::Network => Side::Network,
+    };
+    let mut d_channel = DChannel::new(args.channel, side);
+    d_channel.data_link().establish(Instant::now());
+
+    // On Ctrl-C, clear the calls we know of, and give the far end a moment to release them.
+    catch_interrupt();
+    let mut calls: Vec<CallReference> = Vec::new();
+    let mut clearing_since: Option<Instant> = None;
+
+    let mut events = Vec::new();
+    loop {
+        let now = Instant::now();
+        if clearing_since.is_none() && INTERRUPTED.load(Ordering::SeqCst) {
+            for &call in &calls {
+                d_channel.call_control().disconnect(call, cause::NORMAL_CALL_CLEARING);
+            }
+            clearing_since = Some(now);
+        }
+        if clearing_since.is_some_and(|t| calls.is_empty() || now.saturating_duration_since(t) >= PRI_CLEARING_TIMEOUT) {
+            break;
+        }
+
+        match event_receiver.recv_timeout(Duration::from_millis(100)) {
+            Ok(event) => d_channel.handle_event(&event, now),
+            Err(RecvTimeoutError::Timeout) => {},
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        d_channel.tick(now);
+        match d_channel.process(&control, now, |event| events.push(event)) {
+            Ok(()) => {},
+            Err(FramerControlError::Usb(e)) => return Err(e),
+            Err(e) => eprintln!("pri: {e}"),
+        }
+
+        for event in events.drain(..) {
+            eprintln!("PRI: {event:?}");
+            match event {
+                DChannelEvent::DataLink(DataLinkOutput::EstablishConfirm | DataLinkOutput::EstablishIndication) => {
+                    if let Some(number) = args.call.take() {
+                        calls.push(d_channel.call_control().setup(&number, None, args.bchannel));
+                    }
+                },
+                DChannelEvent::DataLink(DataLinkOutput::ReleaseIndication) => {
+                    // Keep trying to bring the link up, as PRI equipment does.
+                    d_channel.data_link().establish(now);
+                },
+                DChannelEvent::Call(CallEvent::Setup { call, .. }) => {
+                    calls.push(call);
+                    if args.answer {
+                        let call_control = d_channel.call_control();
+                        call_control.proceeding(call);
+                        call_control.alerting(call);
+                        call_control.connect(call);
+                    }
+                },
+                DChannelEvent::Call(CallEvent::Released { call }) => {
+                    calls.retain(|&c| c != call);
+                },
+                _ => {},
+            }
+        }
     }
 
     Ok(())
 }
 
+fn ss7_monitor(control: Arc<FramerControl>, args: Ss7MonitorArgs) {
+    let (event_sender, event_receiver) = unbounded();
+
+    thread::Builder::new()
+        .name("fr_int".to_string())
+        .spawn(move || {
+            if let Err(e) = FramerInterruptThread::run(event_sender, control) {
+                eprintln!("error: framer interrupt pump: {e:?}");
+            }
+            eprintln!("done: framer interrupt pump");
+        }).unwrap();
+
+    let variant = match args.variant {
+        Ss7Variant::Ansi => Variant::Ansi,
+        Ss7Variant::Itu => Variant::Itu,
+    };
+    let mut monitor = Mtp2Monitor::new();
+
+    while let Ok(event) = event_receiver.recv() {
+        match event {
+            FramerEvent::HdlcFrame { channel_index, controller_index, data, length }
+                if channel_index == args.channel && controller_index == args.controller => {
+                monitor.push(&data[0..length], |event| match event {
+                    Mtp2MonitorEvent::LinkState(state) => {
+                        eprintln!("MTP2: {state:?}");
+                    },
+                    Mtp2MonitorEvent::Msu { fsn, retransmission, sio, sif } => {
+                        let retransmission = if retransmission { " (retransmission)" } else { "" };
+                        match Mtp3Message::from_slice(sio, sif, variant) {
+                            Some(message) => eprintln!("MSU: FSN={fsn} {message}{retransmission}"),
+                            None => eprintln!("MSU: FSN={fsn} SIO={sio:02x} {sif:02x?}{retransmission}"),
+                        }
+                    },
+                    Mtp2MonitorEvent::Error(e) => {
+                        eprintln!("MTP2: {e}");
+                    },
+                });
+            },
+            FramerEvent::Ss7Status { channel_index, controller_index }
+                if channel_index == args.channel && controller_index == args.controller => {
+                monitor.ss7_status();
+                eprintln!("MTP2: {:?}", monitor.statistics());
+            },
+            _ => {},
+        }
+    }
+}
+
 ///////////////////////////////////////////////////////////////////////
 
 #[derive(Copy, Clone, Debug)]
@@ -238,8 +1369,7 @@ impl Default for LineStateInterrupt {
 }
 
 impl LineStateInterrupt {
-    fn set_state(&mut self, timestamp: Instant, rsar: RSAR) -> Option<(Duration, bool)> {
-        let new_abcd = (rsar.A() << 3) | (rsar.B() << 2) | (rsar.C() << 1) | (rsar.D() << 0);
+    fn set_state(&mut self, timestamp: Instant, new_abcd: u8) -> Option<(Duration, bool)> {
         if new_abcd != self.abcd {
             let duration = timestamp - self.timestamp;
             self.timestamp = timestamp;
@@ -289,33 +1419,196 @@ impl LineStateRobbedBitState {
     }
 }
 
-fn monitor(receiver: Receiver<FramerEvent>) {
+fn print_alarm_transition(channel_index: usize, transition: &AlarmTransition) {
+    let change = if transition.active { "declared" } else { "cleared" };
+    eprintln!("Alarm: {channel_index} {:?} {change}", transition.alarm);
+}
+
+fn print_slip(channel_index: usize, record: &SlipRecord, slips: &mut ChannelSlips, now: Instant) {
+    let slip = record.slip;
+    let direction = match slip.direction {
+        SlipDirection::Repeated => "frame repeated",
+        SlipDirection::Deleted => "frame deleted",
+        SlipDirection::Unknown => "slip",
+    };
+    let cause = match record.cause {
+        SlipCause::LineClock => "line clock",
+        SlipCause::HostTiming => "host/USB timing",
+    };
+    // A clock that's consistently off slips the same way every time.
+    let trend = match slips.trend() {
+        Some(SlipDirection::Repeated) => ", mostly repeats",
+        Some(SlipDirection::Deleted) => ", mostly deletes",
+        _ => "",
+    };
+    eprintln!("Slip: {channel_index} {:?} {direction} ({cause}), {} in 15 min, {slips}{trend}",
+        slip.buffer, slips.recent(now),
+    );
+}
+
+fn print_clock_switch(switch: &ClockSwitch, applied: &framer::control::Result<()>) {
+    let from = switch.from.map_or_else(|| "current".to_string(), |r| r.to_string());
+    eprintln!("Clock: reference {from} -> {} ({})", switch.to, switch.reason);
+    if let Err(e) = applied {
+        eprintln!("framer: clock: {}: {e}", switch.to);
+    }
+}
+
+/// How many of each span's most recent alarm transitions to show in the summary.
+const ALARM_SUMMARY_HISTORY: usize = 4;
+
+fn print_alarm_summary(alarms: &[SpanAlarms], now: Instant) {
+    let spans: Vec<_> = alarms.iter().enumerate().map(|(i, a)| format!("{i}:{a}")).collect();
+    eprintln!("Alarms: {}", spans.join(" "));
+
+    for (channel_index, alarms) in alarms.iter().enumerate() {
+        let history: Vec<_> = alarms.history().collect();
+        if history.is_empty() {
+            continue;
+        }
+        let recent: Vec<_> = history[history.len().saturating_sub(ALARM_SUMMARY_HISTORY)..].iter().map(|t| {
+            let change = if t.active { "declared" } else { "cleared" };
+            format!("{:?} {change} {:.1}s ago", t.alarm, now.saturating_duration_since(t.timestamp).as_secs_f32())
+        }).collect();
+        eprintln!("Alarm history: {channel_index} {}", recent.join(", "));
+    }
+}
+
+/// The interval in progress, then the completed ones, most recent first.
+fn print_performance_summary(channel_index: usize, performance_monitor: &PerformanceMonitor) {
+    let current = performance_monitor.current();
+    if current.seconds == 0 && performance_monitor.intervals().next().is_none() {
+        // Never got a ONESEC, so there's nothing to say.
+        return;
+    }
+    eprintln!("Performance: {channel_index} current {}s {:?}", current.seconds, current.counters);
+    for (n, interval) in performance_monitor.intervals().enumerate() {
+        eprintln!("Performance: {channel_index} interval -{} {}s {:?}", n + 1, interval.seconds, interval.counters);
+    }
+}
+
+/// Leave whatever loopback the channel is in, then enter the new one, if any.
+fn apply_loopback_action(channel: &Channel, action: LoopbackAction, saved: &mut Option<LoopbackSavedState>) -> Result<()> {
+    if let Some(previous) = saved.take() {
+        loopback_restore(channel, &previous)?;
+    }
+    if let LoopbackAction::Enter(loopback) = action {
+        *saved = Some(loopback_enter(channel, loopback)?);
+    }
+    Ok(())
+}
+
+fn monitor(device: &Device, control: &FramerControl, receiver: Receiver<FramerEvent>, alarm_clearing: Option<Duration>, loopback_policy: Option<LoopbackPolicy>, mut clock_failover: Option<ClockFailover>) {
     let mut line_state_interrupt = [[LineStateInterrupt::default(); 24]; 8];
     let mut line_state_robbed_bit_state = [[LineStateRobbedBitState::default(); 24]; 8];
+    let mut crc6_statistics = [Crc6Statistics::default(); 8];
+    let mut performance_report_history = [(); 8].map(|_| PerformanceReportHistory::new());
+    let mut span_alarms = [(); 8].map(|_| alarm_clearing.map_or_else(SpanAlarms::new, SpanAlarms::with_clearing));
+    let mut performance_monitors = [(); 8].map(|_| PerformanceMonitor::new());
+    let mut prbs_bit_errors = [0u64; 8];
+    let mut slip_accounting = SlipAccounting::new();
+    let mut loopback_responders = [(); 8].map(|_| loopback_policy.map(LoopbackResponder::new));
+    let mut loopback_saved: [Option<LoopbackSavedState>; 8] = [None; 8];
+
+    let mut loopback_action = |channel_index: usize, action: Option<LoopbackAction>| {
+        if let Some(action) = action {
+            eprintln!("Loopback: {channel_index} {action:?}");
+            if let Err(e) = apply_loopback_action(&device.channel(channel_index), action, &mut loopback_saved[channel_index]) {
+                eprintln!("framer: loopback: {channel_index}: {e:?}");
+            }
+        }
+    };
+
+    let clock_switch = |switch: Option<ClockSwitch>| {
+        if let Some(switch) = switch {
+            let applied = control.timing_reference(switch.to);
+            print_clock_switch(&switch, &applied);
+        }
+    };
+
+    catch_interrupt();
+    while !INTERRUPTED.load(Ordering::SeqCst) {
+        // Wake up now and then, so alarm timers run out even when the framer is quiet.
+        let m = match receiver.recv_timeout(Duration::from_millis(250)) {
+            Ok(m) => Some(m),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+
+        let now = Instant::now();
+        let mut alarms_changed = false;
+        for (channel_index, alarms) in span_alarms.iter_mut().enumerate() {
+            alarms.tick(now, |t| {
+                print_alarm_transition(channel_index, &t);
+                alarms_changed = true;
+            });
+        }
+        if alarms_changed {
+            print_alarm_summary(&span_alarms, now);
+        }
+
+        for (channel_index, responder) in loopback_responders.iter_mut().enumerate() {
+            if let Some(responder) = responder {
+                loopback_action(channel_index, responder.tick(now));
+            }
+        }
+
+        if let Some(failover) = &mut clock_failover {
+            clock_switch(failover.tick(now));
+        }
+
+        let Some(m) = m else {
+            continue;
+        };
 
-    while let Ok(m) = receiver.recv() {
         match m {
-            FramerEvent::Interrupt { timestamp, data, length } => {
-                let truncated = &data[0..length];
-                if let Ok(status) = FramerInterruptStatus::from_slice(truncated) {
-                    print_framer_interrupt_status(&status);
-
-                    let channel_index = status.channel_index;
-
-                    if let Some(t1frame) = status.t1frame {
-                        if let Some(sig) = t1frame.sig {
-                            for timeslot_index in 0..24 {
-                                let rsar = sig.rsars[timeslot_index];
-                                if let Some((duration, off_hook)) = line_state_interrupt[channel_index][timeslot_index].set_state(timestamp, rsar) {
-                                    eprintln!("Interrupt: {channel_index}.{timeslot_index:02} {duration:?} {off_hook:?}");
-                                }
-                            }
-                        }
-                    }
-                } else {
-                    eprintln!("framer: interrupt: bad struct: {data:?}");
+            FramerEvent::InterruptError { error, data, length } => {
+                eprintln!("framer: interrupt: {error}: {:?}", &data[0..length]);
+            },
+            FramerEvent::LoopbackCode { timestamp, channel_index, status } => {
+                eprintln!("Loopback code: {channel_index} {}", rlcisr_bytes(&status));
+                if let Some(responder) = &mut loopback_responders[channel_index] {
+                    loopback_action(channel_index, responder.inband_status(&status, timestamp));
+                }
+            },
+            FramerEvent::Slip { timestamp, channel_index, sbisr } => {
+                eprintln!("Slip buffer: {channel_index} {}", sbisr_bits(sbisr));
+                for record in slip_accounting.framer_slip(channel_index, sbisr, timestamp) {
+                    print_slip(channel_index, &record, slip_accounting.channel(channel_index), timestamp);
                 }
             },
+            FramerEvent::Alarm { timestamp, channel_index, defects, status } => {
+                eprintln!("Alarm status: {channel_index} {}", alarm_bits(&status));
+
+                // Integration starts when the framer saw the defect, not when we got around to it.
+                let mut alarms_changed = false;
+                span_alarms[channel_index].update(defects, timestamp, |t| {
+                    print_alarm_transition(channel_index, &t);
+                    alarms_changed = true;
+                });
+                if alarms_changed {
+                    print_alarm_summary(&span_alarms, now);
+                }
+
+                if let Some(failover) = &mut clock_failover {
+                    clock_switch(failover.defects(channel_index, defects, timestamp));
+                }
+            },
+            FramerEvent::Framing { channel_index, fisr } => {
+                eprintln!("Framing: {channel_index} {}", fisr_bits(fisr));
+            },
+            FramerEvent::Signaling { timestamp, address, old_abcd, new_abcd } => {
+                let TimeslotAddress { channel: channel_index, timeslot: timeslot_index } = address;
+                if let Some((duration, off_hook)) = line_state_interrupt[channel_index][timeslot_index].set_state(timestamp, new_abcd) {
+                    eprintln!("Interrupt: {channel_index}.{timeslot_index:02} {duration:?} {off_hook:?}");
+                }
+                if let Some(old_abcd) = old_abcd {
+                    eprintln!("Signaling: {channel_index}.{timeslot_index:02} {old_abcd:04b} -> {new_abcd:04b}");
+                }
+            },
+            FramerEvent::HdlcChunk { channel_index, controller_index, dlsr, buffer_index, data, length } => {
+                eprintln!("HdlcChunk: {channel_index}.{controller_index} {} LAPDBCR{buffer_index}:{:02x?}", dlsr_bits(dlsr), &data[0..length]);
+            },
             FramerEvent::Digit(address, event) => {
                 eprintln!("Digit {address:?}: {event:?}");
             },
@@ -327,6 +1620,100 @@ fn monitor(receiver: Receiver<FramerEvent>) {
                     eprintln!("RobbedBitState: {channel_index}.{timeslot_index:02} {duration_state_s} for {duration:?}, now {now_state_s}");
                 }
             },
+            FramerEvent::BitOrientedMessage(_frame_count, channel_index, message) => {
+                eprintln!("BitOrientedMessage: {channel_index} {message:?}");
+                if let (BitOrientedMessage::Start(code), Some(responder)) = (message, &mut loopback_responders[channel_index]) {
+                    loopback_action(channel_index, responder.bit_oriented_message(code, now));
+                }
+            },
+            FramerEvent::HdlcFrame { channel_index, controller_index, data, length } => {
+                let frame = &data[0..length];
+                if let Some(report) = PerformanceReport::from_slice(frame) {
+                    let history = &mut performance_report_history[channel_index];
+                    if history.update(&report) > 0 && report.seconds[0].errored() {
+                        eprintln!("PerformanceReport: {channel_index} far-end ES={} SES={} {:?}",
+                            history.errored_seconds(), history.severely_errored_seconds(), report.seconds[0],
+                        );
+                    }
+                } else {
+                    eprintln!("HdlcFrame: {channel_index}.{controller_index} {frame:02x?}");
+                }
+            },
+            FramerEvent::HdlcTransmitComplete { .. } => {},
+            FramerEvent::RecoveredClock { channel_index, lost } => {
+                eprintln!("Clock: {channel_index} recovered clock {}", if lost { "lost" } else { "restored" });
+                if let Some(failover) = &mut clock_failover {
+                    clock_switch(failover.recovered_clock(channel_index, lost, now));
+                }
+            },
+            FramerEvent::OneSecond { channel_index } => {
+                match PmonCounts::read(&device.channel(channel_index)) {
+                    Ok(counts) => {
+                        // Reading PMON cleared PBEC, so this is the only count of them there is.
+                        if counts.prbs_bit_errors > 0 {
+                            prbs_bit_errors[channel_index] += counts.prbs_bit_errors as u64;
+                            eprintln!("Performance: {channel_index} {} PRBS bit errors ({} total)",
+                                counts.prbs_bit_errors, prbs_bit_errors[channel_index],
+                            );
+                        }
+
+                        let ais = span_alarms[channel_index].defects().ais;
+                        let performance_monitor = &mut performance_monitors[channel_index];
+                        let was_unavailable = performance_monitor.is_unavailable();
+                        if let Some(interval) = performance_monitor.update(&counts, ais) {
+                            eprintln!("Performance: {channel_index} interval {:?} 24h {:?}",
+                                interval.counters, performance_monitor.total().counters,
+                            );
+                        }
+                        if performance_monitor.is_unavailable() != was_unavailable {
+                            eprintln!("Performance: {channel_index} path {}",
+                                if performance_monitor.is_unavailable() { "unavailable" } else { "available" },
+                            );
+                        }
+                    },
+                    Err(e) => eprintln!("framer: pmon: {channel_index}: {e:?}"),
+                }
+            },
+            FramerEvent::Ss7Status { channel_index, controller_index } => {
+                eprintln!("Ss7Status: {channel_index}.{controller_index}");
+            },
+            FramerEvent::Crc6Statistics(_frame_count, channel_index, statistics) => {
+                // Only report when something has gone wrong.
+                if statistics.crc_errors != crc6_statistics[channel_index].crc_errors {
+                    eprintln!("Crc6Statistics: {channel_index} superframes={} crc_errors={} errored_seconds={}",
+                        statistics.superframes, statistics.crc_errors, statistics.errored_seconds,
+                    );
+                }
+                crc6_statistics[channel_index] = statistics;
+            },
+            FramerEvent::SoftBert(..) => {},
+            FramerEvent::UsbFifoErrors { timestamp, rx_underflow_count, tx_overflow_count } => {
+                let (slips, reblamed) = slip_accounting.usb_fifo_counts(rx_underflow_count, tx_overflow_count, timestamp);
+                if slips != UsbFifoSlips::default() {
+                    let total = slip_accounting.usb_fifo();
+                    eprintln!("USB FIFO: {} rx underflows, {} tx overflows ({} and {} total)",
+                        slips.rx_underflows, slips.tx_overflows, total.rx_underflows, total.tx_overflows,
+                    );
+                }
+                for (channel_index, record) in reblamed {
+                    eprintln!("Slip: {channel_index} {:?} slip {:?} earlier was host/USB timing after all, {}",
+                        record.slip.buffer, timestamp.saturating_duration_since(record.timestamp), slip_accounting.channel(channel_index),
+                    );
+                }
+            },
+        }
+    }
+
+    for (channel_index, performance_monitor) in performance_monitors.iter().enumerate() {
+        print_performance_summary(channel_index, performance_monitor);
+    }
+
+    // Don't leave spans looped back after we stop answering the far end.
+    for (channel_index, saved) in loopback_saved.iter().enumerate() {
+        if let Some(saved) = saved {
+            if let Err(e) = loopback_restore(&device.channel(channel_index), saved) {
+                eprintln!("framer: loopback: {channel_index}: {e:?}");
+            }
         }
     }
 }
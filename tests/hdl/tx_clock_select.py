@@ -0,0 +1,22 @@
+from amaranth import *
+from amaranth.sim.core import Settle
+
+from tedium.gateware.framer.tx_clock_select import TxClockSelect
+
+from tests.hdl import TestCase
+
+class TxClockSelectTest(TestCase):
+    def test_select(self):
+        m = Module()
+        dut = m.submodules.dut = TxClockSelect(8)
+
+        with self.assertSimulation(m, filename="tx_clock_select") as sim:
+            def process():
+                for select in range(8):
+                    yield dut.select.eq(select)
+                    for sclk in (1 << select, ~(1 << select) & 0xff):
+                        yield dut.sclk.eq(sclk)
+                        yield Settle()
+                        self.assertEqual((yield dut.serclk), (sclk >> select) & 1)
+
+            sim.add_process(process)
@@ -65,6 +65,14 @@ const DEFAULTS_XXNX: [Row; 1] = [
 ];
 
 impl Row {
+    fn value(&self, base_address: u16, address: u16) -> Option<u8> {
+        let offset = address.checked_sub(base_address)? as usize;
+        match *self.1.get(offset)? {
+            NOOP | READ | IGNR => None,
+            value => Some(value),
+        }
+    }
+
     fn write<D: Xyz>(&self, base_address: u16, device: &D) -> Result<()> {
         for (n, &value) in self.1.iter().enumerate() {
             let address = base_address + n as u16;
@@ -83,7 +91,7 @@ impl Row {
 }
 
 /// Apply XRT86V38 register defaults to a device through its "uP" interface.
-/// 
+///
 pub fn framer_defaults<D: Xyz>(device: &D) -> Result<()> {
     // TODO: Disable FPGA outputs to framer when we change configuration.
 	// device.framer_interface_control(false)?;
@@ -116,3 +124,26 @@ pub fn framer_defaults<D: Xyz>(device: &D) -> Result<()> {
 
     Ok(())
 }
+
+/// Power-up default of the register at `address`, from the same tables
+/// `framer_defaults` writes. Registers that aren't written (status, counters,
+/// unknowns) have no default to report.
+///
+pub fn default_value(address: u16) -> Option<u8> {
+    let xxxx = DEFAULTS_XXXX.iter().map(|row| (row.0, row));
+    let nxxx = DEFAULTS_NXXX.iter().flat_map(|row| (0..8).map(move |c| ((c << 12) | row.0, row)));
+    let xxnx = DEFAULTS_XXNX.iter().flat_map(|row| (0..8).map(move |c| ((c << 4) | row.0, row)));
+
+    xxxx.chain(nxxx).chain(xxnx)
+        .find_map(|(base_address, row)| row.value(base_address, address))
+}
+
+/// LIUCCR0 EQC `tedium-soc` gives every channel at startup, and `tedium-tool`
+/// treats as the line interface default: DSX-1, 0 to 133 feet.
+///
+pub const LIU_EQC: u8 = 0x08;
+
+/// LIUCCR1 to go with `LIU_EQC`: receive jitter attenuator, wide bandwidth,
+/// 32 bit FIFO, 100 ohm internal termination on both sides.
+///
+pub const LIU_LIUCCR1: u8 = 0b1100_1100;
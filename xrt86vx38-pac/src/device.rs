@@ -249,8 +249,8 @@ where D: Xyz,
     pub fn rscr1   (&self) -> Access<D, RSChR>    { self.rscr(1) }
     pub fn rscr2   (&self) -> Access<D, RSChR>    { self.rscr(2) }
     pub fn rifr    (&self) -> Access<D, RIFR>     { self.access_framer(0x112) }
-    pub fn dlcr1   (&self) -> Access<D, DLCR>     { self.access_framer(0x113) }
-    pub fn tdlbcr1 (&self) -> Access<D, TDLBCR>   { self.access_framer(0x114) }
+    pub fn dlcr1   (&self) -> Access<D, DLCR>     { self.dlcr(0) }
+    pub fn tdlbcr1 (&self) -> Access<D, TDLBCR>   { self.tdlbcr(0) }
     pub fn rdlbcr1 (&self) -> Access<D, RDLBCR>   { self.rdlbcr(0) }
     pub fn sbcr    (&self) -> Access<D, SBCR>     { self.access_framer(0x116) }
     pub fn fifolr  (&self) -> Access<D, FIFOLR>   { self.access_framer(0x117) }
@@ -278,8 +278,8 @@ where D: Xyz,
     pub fn tlcgs   (&self) -> Access<D, TLCGS>    { self.access_framer(0x140) }
     pub fn lcts    (&self) -> Access<D, LCTS>     { self.access_framer(0x141) }
     pub fn tsprmcr (&self) -> Access<D, TSPRMCR>  { self.access_framer(0x142) }
-    pub fn dlcr2   (&self) -> Access<D, DLCR>     { self.access_framer(0x143) }
-    pub fn tdlbcr2 (&self) -> Access<D, TDLBCR>   { self.access_framer(0x144) }
+    pub fn dlcr2   (&self) -> Access<D, DLCR>     { self.dlcr(1) }
+    pub fn tdlbcr2 (&self) -> Access<D, TDLBCR>   { self.tdlbcr(1) }
     pub fn rdlbcr2 (&self) -> Access<D, RDLBCR>   { self.rdlbcr(1) }
     pub fn lccr3   (&self) -> Access<D, LCCR>     { self.access_framer(0x146) }
     pub fn rlacr3  (&self) -> Access<D, RLACR>    { self.access_framer(0x147) }
@@ -293,8 +293,8 @@ where D: Xyz,
     pub fn lccr6   (&self) -> Access<D, LCCR>     { self.access_framer(0x14f) }
     pub fn rlacr6  (&self) -> Access<D, RLACR>    { self.access_framer(0x150) }
     pub fn rldcr6  (&self) -> Access<D, RLDCR>    { self.access_framer(0x151) }
-    pub fn dlcr3   (&self) -> Access<D, DLCR>     { self.access_framer(0x153) }
-    pub fn tdlbcr3 (&self) -> Access<D, TDLBCR>   { self.access_framer(0x154) }
+    pub fn dlcr3   (&self) -> Access<D, DLCR>     { self.dlcr(2) }
+    pub fn tdlbcr3 (&self) -> Access<D, TDLBCR>   { self.tdlbcr(2) }
     pub fn rdlbcr3 (&self) -> Access<D, RDLBCR>   { self.rdlbcr(2) }
     pub fn lccr7   (&self) -> Access<D, LCCR>     { self.access_framer(0x156) }
     pub fn rlacr7  (&self) -> Access<D, RLACR>    { self.access_framer(0x157) }
@@ -329,6 +329,18 @@ where D: Xyz,
         self.access_framer(MAP[index])
     }
 
+    pub fn dlcr(&self, index: usize) -> Access<D, DLCR> {
+        const MAP: [usize; 3] = [0x113, 0x143, 0x153];
+        assert!(index < MAP.len());
+        self.access_framer(MAP[index])
+    }
+
+    pub fn tdlbcr(&self, index: usize) -> Access<D, TDLBCR> {
+        const MAP: [usize; 3] = [0x114, 0x144, 0x154];
+        assert!(index < MAP.len());
+        self.access_framer(MAP[index])
+    }
+
     // Receive Signaling Array Registers
 
     pub fn rsar(&self, index: usize) -> Access<D, RSAR> {
@@ -337,6 +349,7 @@ where D: Xyz,
     }
 
     // LAPD buffers 0, 1
+    // NOTE: Firmware goes through index 0 only, the buffer pointer auto-increments.
 
     pub fn lapdbcr0(&self, index: usize) -> Access<D, LAPDBCR> {
         assert!(index < 96);
@@ -378,7 +391,7 @@ where D: Xyz,
     pub fn fisr    (&self) -> Access<D, FISR>     { self.access_framer(0xb04) }
     pub fn fier    (&self) -> Access<D, FIER>     { self.access_framer(0xb05) }
     pub fn dlsr1   (&self) -> Access<D, DLSRx>    { self.dlsr(0) }
-    pub fn dlier1  (&self) -> Access<D, DLIERx>   { self.access_framer(0xb07) }
+    pub fn dlier1  (&self) -> Access<D, DLIERx>   { self.dlier(0) }
     pub fn sbisr   (&self) -> Access<D, SBISR>    { self.access_framer(0xb08) }
     pub fn sbier   (&self) -> Access<D, SBIER>    { self.access_framer(0xb09) }
     pub fn rlcisr0 (&self) -> Access<D, RLCISRx>  { self.access_framer(0xb0a) }
@@ -386,15 +399,15 @@ where D: Xyz,
     pub fn exzsr   (&self) -> Access<D, EXZSR>    { self.access_framer(0xb0e) }
     pub fn exzer   (&self) -> Access<D, EXZER>    { self.access_framer(0xb0f) }
     pub fn ss7sr1  (&self) -> Access<D, SS7SRx>   { self.ss7sr(0) }
-    pub fn ss7er1  (&self) -> Access<D, SS7ERx>   { self.access_framer(0xb11) }
+    pub fn ss7er1  (&self) -> Access<D, SS7ERx>   { self.ss7er(0) }
     pub fn rlcisr  (&self) -> Access<D, RLCISR>   { self.access_framer(0xb12) }
     pub fn rlcier  (&self) -> Access<D, RLCIER>   { self.access_framer(0xb13) }
     pub fn rlcisr1 (&self) -> Access<D, RLCISRx>  { self.access_framer(0xb14) }
     pub fn rlcier1 (&self) -> Access<D, RLCIERx>  { self.access_framer(0xb15) }
     pub fn dlsr2   (&self) -> Access<D, DLSRx>    { self.dlsr(1) }
-    pub fn dlier2  (&self) -> Access<D, DLIERx>   { self.access_framer(0xb17) }
+    pub fn dlier2  (&self) -> Access<D, DLIERx>   { self.dlier(1) }
     pub fn ss7sr2  (&self) -> Access<D, SS7SRx>   { self.ss7sr(1) }
-    pub fn ss7er2  (&self) -> Access<D, SS7ERx>   { self.access_framer(0xb19) }
+    pub fn ss7er2  (&self) -> Access<D, SS7ERx>   { self.ss7er(1) }
     pub fn rlcisr2 (&self) -> Access<D, RLCISRx>  { self.access_framer(0xb1a) }
     pub fn rlcier2 (&self) -> Access<D, RLCIERx>  { self.access_framer(0xb1b) }
     pub fn rlcisr3 (&self) -> Access<D, RLCISRx>  { self.access_framer(0xb1c) }
@@ -408,9 +421,9 @@ where D: Xyz,
     pub fn rlcisr7 (&self) -> Access<D, RLCISRx>  { self.access_framer(0xb24) }
     pub fn rlcier7 (&self) -> Access<D, RLCIERx>  { self.access_framer(0xb25) }
     pub fn dlsr3   (&self) -> Access<D, DLSRx>    { self.dlsr(2) }
-    pub fn dlier3  (&self) -> Access<D, DLIERx>   { self.access_framer(0xb27) }
+    pub fn dlier3  (&self) -> Access<D, DLIERx>   { self.dlier(2) }
     pub fn ss7sr3  (&self) -> Access<D, SS7SRx>   { self.ss7sr(2) }
-    pub fn ss7er3  (&self) -> Access<D, SS7ERx>   { self.access_framer(0xb29) }
+    pub fn ss7er3  (&self) -> Access<D, SS7ERx>   { self.ss7er(2) }
     pub fn ciasr   (&self) -> Access<D, CIASR>    { self.access_framer(0xb40) }
     pub fn ciaier  (&self) -> Access<D, CIAIER>   { self.access_framer(0xb41) }
     pub fn bocisr  (&self) -> Access<D, BOCISR>   { self.access_framer(0xb70) }
@@ -436,12 +449,24 @@ where D: Xyz,
         self.access_framer(MAP[index])
     }
 
+    pub fn dlier(&self, index: usize) -> Access<D, DLIERx> {
+        const MAP: [usize; 3] = [0xb07, 0xb17, 0xb27];
+        assert!(index < MAP.len());
+        self.access_framer(MAP[index])
+    }
+
     pub fn ss7sr(&self, index: usize) -> Access<D, SS7SRx> {
         const MAP: [usize; 3] = [0xb10, 0xb18, 0xb28];
         assert!(index < MAP.len());
         self.access_framer(MAP[index])
     }
 
+    pub fn ss7er(&self, index: usize) -> Access<D, SS7ERx> {
+        const MAP: [usize; 3] = [0xb11, 0xb19, 0xb29];
+        assert!(index < MAP.len());
+        self.access_framer(MAP[index])
+    }
+
     // LIU
 
     pub fn liuccr0 (&self) -> Access<D, LIUCCR0>  { self.access_liu(0x0) }
@@ -635,6 +635,7 @@ pub struct TSPRMCR {
 #[derive(Copy, Clone, BitfieldSpecifier, Debug)]
 #[bits=4]
 pub enum BERTPattern {
+    PRBS_X23_X18_1 = 0b0001,
     PRBS_X20_X3_1 = 0b0010,
     QRSS_X20_X17_1 = 0b0011,
     AllOnes = 0b0100,